@@ -2,23 +2,29 @@ use std::{sync::Arc, time::Duration};
 
 use ratatui::crossterm::event::{self, KeyCode, KeyEventKind};
 use tracing::info;
-use wherehouse::package_manager::{Command, PackageLocality, PackageManager};
+use wherehouse::package_manager::{Backend, Command, Package, PackageLocality, registry::Registry};
 
 use crate::{
-    state::{InputMode, Pane, State},
+    state::{HealthState, InputMode, NodeKind, Pane, State, TreeNode},
     task_manager::TaskManager, // trace_dbg,
 };
 
-pub struct InputHandler<T> {
-    task_manager: TaskManager<T>,
+pub struct InputHandler {
+    task_manager: TaskManager<Registry>,
+    registry: Arc<Registry>,
     state: Arc<State>,
     update: bool,
 }
 
-impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
-    pub fn new(state: Arc<State>, task_manager: TaskManager<T>) -> Self {
+impl InputHandler {
+    pub fn new(
+        state: Arc<State>,
+        task_manager: TaskManager<Registry>,
+        registry: Arc<Registry>,
+    ) -> Self {
         Self {
             task_manager,
+            registry,
             state,
             update: false,
         }
@@ -26,10 +32,10 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
     pub fn run(&mut self) -> color_eyre::Result<()> {
         loop {
             self.capture_input()?;
-            if let Ok(should_quit) = self.state.should_quit.try_lock() {
-                if *should_quit {
-                    break;
-                }
+            if let Ok(should_quit) = self.state.should_quit.try_lock()
+                && *should_quit
+            {
+                break;
             }
         }
         Ok(())
@@ -45,10 +51,18 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
             }
         } else {
             self.update_search()?;
+            self.refresh_search()?;
         }
         Ok(())
     }
     fn handle_key_press(&mut self, key_event: event::KeyEvent) -> color_eyre::Result<()> {
+        if self.state.confirmation.lock().unwrap().is_some() {
+            return self.handle_confirmation(key_event);
+        }
+        if self.state.prompt.lock().unwrap().is_some() {
+            return self.handle_prompt(key_event);
+        }
+
         let state = self.state.clone();
         let mut current_pane = state.current_pane();
         let mut input_mode = state.input_mode.lock().unwrap();
@@ -58,7 +72,7 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
                 KeyCode::Char('1') => {
                     *current_pane = Pane::Info;
                     self.state
-                        .update_context(self.state.config.lock().unwrap().system_config.clone());
+                        .update_context(self.state.config.lock().unwrap().system_report());
                 }
                 KeyCode::Char('2') => {
                     *current_pane = Pane::SearchInput;
@@ -75,7 +89,23 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
                             .clone(),
                     );
                 }
+                KeyCode::Char('4') => {
+                    *current_pane = Pane::Outdated;
+                    self.state.update_context(String::default());
+                    self.task_manager.execute(Command::OutdatedPackages, true)?;
+                }
+                KeyCode::Char('5') => {
+                    *current_pane = Pane::Taps;
+                    self.state.update_context(String::default());
+                    self.task_manager.execute(Command::Taps, true)?;
+                }
+                KeyCode::Char('6') => {
+                    *current_pane = Pane::Services;
+                    self.state.update_context(String::default());
+                    self.task_manager.execute(Command::Services, true)?;
+                }
                 KeyCode::Char('q') => self.quit()?,
+                KeyCode::Char('x') => self.cancel_operation()?,
                 _ => {}
             }
         }
@@ -84,12 +114,74 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
             Pane::Info => {
                 if let InputMode::Normal = *input_mode {
                     match key_event.code {
-                        KeyCode::Char('I') => self.state.update_context(
-                            self.state.config.lock().unwrap().system_config.clone(),
-                        ),
+                        KeyCode::Char('I') => self
+                            .state
+                            .update_context(self.state.config.lock().unwrap().system_report()),
                         KeyCode::Char('C') => {
+                            self.state.health.lock().unwrap().requested = true;
                             self.task_manager.execute(Command::CheckHealth, true)?;
                         }
+                        // the warnings are only listed once a check was asked for
+                        KeyCode::Char('k' | 'j' | 'f' | 'i' | 'u' | 'r')
+                            if !self.state.health.lock().unwrap().requested => {}
+                        KeyCode::Char('k') => self.select_previous_health_warning(),
+                        KeyCode::Char('j') => self.select_next_health_warning(),
+                        KeyCode::Char('f') => self.fix_health_warning(),
+                        KeyCode::Char('i') => self.ignore_health_warning(),
+                        KeyCode::Char('u') => self.unignore_health_warnings(),
+                        KeyCode::Char('r') => {
+                            self.task_manager.execute(Command::RecheckHealth, true)?;
+                        }
+                        KeyCode::Char('c') => {
+                            self.task_manager.execute(Command::PreviewClean, true)?;
+                        }
+                        KeyCode::Char('e') => {
+                            let backend = self.state.config.lock().unwrap().package_manager;
+                            self.state.request_input(
+                                Command::ExportGraph,
+                                "Export the dependency graph to (.dot, .json, or both without an extension)"
+                                    .to_string(),
+                                format!("{}-dependencies", backend.alias()),
+                            );
+                        }
+                        KeyCode::Char('b') => self.state.request_input(
+                            Command::ExportBrewfile,
+                            "Export a Brewfile of everything installed to".to_string(),
+                            "Brewfile".to_string(),
+                        ),
+                        KeyCode::Char('B') => self.state.request_input(
+                            Command::PlanBrewfile,
+                            "Import the Brewfile at".to_string(),
+                            "Brewfile".to_string(),
+                        ),
+                        KeyCode::Char('d') => self.state.request_input(
+                            Command::BrewfileDrift,
+                            "Compare what is installed with the Brewfile at".to_string(),
+                            "Brewfile".to_string(),
+                        ),
+                        KeyCode::Char('s') => self.state.request_input(
+                            Command::SnapshotManifest,
+                            "Snapshot this machine into the manifest at".to_string(),
+                            "wherehouse.json".to_string(),
+                        ),
+                        KeyCode::Char('m') => self.state.request_input(
+                            Command::DiffManifest,
+                            "Compare what is installed with the manifest at".to_string(),
+                            "wherehouse.json".to_string(),
+                        ),
+                        KeyCode::Char('M') => self.state.request_input(
+                            Command::PlanManifest,
+                            "Apply the manifest at".to_string(),
+                            "wherehouse.json".to_string(),
+                        ),
+                        KeyCode::Char('h') => {
+                            let backend = self.registry.select_previous();
+                            self.switch_package_manager(backend)?;
+                        }
+                        KeyCode::Char('l') => {
+                            let backend = self.registry.select_next();
+                            self.switch_package_manager(backend)?;
+                        }
                         _ => {}
                     }
                 }
@@ -107,6 +199,7 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
                         search.source = PackageLocality::Remote;
                         self.task_manager.execute(Command::FilterPackages, true)?;
                     }
+                    KeyCode::Char('a') => self.toggle_aggregated()?,
                     _ => {}
                 },
                 InputMode::Insert => match key_event.code {
@@ -117,18 +210,136 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
                     KeyCode::Esc => *input_mode = InputMode::Normal,
                     _ => {}
                 },
-                _ => {}
             },
+            #[allow(clippy::single_match)]
             Pane::SearchResults => match *input_mode {
                 InputMode::Normal => match key_event.code {
                     KeyCode::Char('q') => self.quit()?,
                     KeyCode::Char('k') => self.select_previous_search_result()?,
                     KeyCode::Char('j') => self.select_next_search_result()?,
+                    KeyCode::Char('i') => self.request_confirmation(Command::InstallPackage),
+                    KeyCode::Char('d') => self.request_confirmation(Command::UninstallPackage),
+                    KeyCode::Char('u') => self.request_confirmation(Command::UpdatePackage),
+                    KeyCode::Char('p') => self.pin_selected_search_result(Command::PinPackage)?,
+                    KeyCode::Char('P') => self.pin_selected_search_result(Command::UnpinPackage)?,
+                    KeyCode::Char('t') => {
+                        let aggregated = self.registry.is_aggregated();
+                        let search = self.state.search.lock().unwrap();
+                        // the dependencies are shown as the backend listed
+                        // first sees them, naming that backend
+                        let package_name =
+                            search.results.get(search.selected_result).map(|package| {
+                                match (aggregated, package.sources.first()) {
+                                    (true, Some(backend)) => backend.qualify(&package.name),
+                                    _ => package.name.clone(),
+                                }
+                            });
+                        drop(search);
+                        if let Some(package_name) = package_name {
+                            self.explore_dependencies(package_name)?;
+                            *current_pane = Pane::Context;
+                        }
+                    }
                     _ => {}
                 },
                 _ => {}
             },
-            _ => {}
+            Pane::Outdated => {
+                if let InputMode::Normal = *input_mode {
+                    match key_event.code {
+                        KeyCode::Char('k') => self.select_previous_outdated_package(),
+                        KeyCode::Char('j') => self.select_next_outdated_package(),
+                        KeyCode::Char(' ') => self.toggle_outdated_package(),
+                        KeyCode::Char('u') => {
+                            let outdated = self.state.outdated.lock().unwrap();
+                            self.state.request_upgrade(outdated.selection());
+                        }
+                        KeyCode::Char('U') => {
+                            let outdated = self.state.outdated.lock().unwrap();
+                            self.state
+                                .request_upgrade(outdated.packages.iter().collect());
+                        }
+                        KeyCode::Char('p') => self.pin_highlighted_outdated(Command::PinPackage)?,
+                        KeyCode::Char('P') => {
+                            self.pin_highlighted_outdated(Command::UnpinPackage)?
+                        }
+                        KeyCode::Char('r') => {
+                            self.task_manager.execute(Command::OutdatedPackages, true)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Pane::Taps => {
+                if let InputMode::Normal = *input_mode {
+                    match key_event.code {
+                        KeyCode::Char('k') => self.select_previous_tap(),
+                        KeyCode::Char('j') => self.select_next_tap(),
+                        KeyCode::Char('a') => self.state.request_input(
+                            Command::AddTap,
+                            "Tap to add, as user/repository optionally followed by its git remote"
+                                .to_string(),
+                            String::default(),
+                        ),
+                        KeyCode::Char('d') => {
+                            let taps = self.state.taps.lock().unwrap();
+                            if let Some(tap) = taps.highlighted() {
+                                self.state.request_confirmation(
+                                    Command::RemoveTap,
+                                    vec![tap.name.clone()],
+                                );
+                            }
+                        }
+                        KeyCode::Char('r') => {
+                            self.task_manager.execute(Command::Taps, true)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Pane::Services => {
+                if let InputMode::Normal = *input_mode {
+                    match key_event.code {
+                        KeyCode::Char('k') => self.select_previous_service(),
+                        KeyCode::Char('j') => self.select_next_service(),
+                        KeyCode::Char('s') => self.control_service(Command::StartService)?,
+                        KeyCode::Char('S') => self.control_service(Command::StopService)?,
+                        KeyCode::Char('R') => self.control_service(Command::RestartService)?,
+                        KeyCode::Char('o') => self.control_service(Command::RunService)?,
+                        KeyCode::Char('c') => self
+                            .state
+                            .request_confirmation(Command::CleanupServices, Vec::new()),
+                        KeyCode::Char('r') => {
+                            self.task_manager.execute(Command::Services, true)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Pane::Context => {
+                if let InputMode::Normal = *input_mode {
+                    match key_event.code {
+                        KeyCode::Char('k') => self.select_previous_tree_node(),
+                        KeyCode::Char('j') => self.select_next_tree_node(),
+                        KeyCode::Char('l') | KeyCode::Char(' ') => self.expand_tree_node()?,
+                        KeyCode::Char('h') => self.collapse_tree_node(),
+                        KeyCode::Enter => {
+                            let tree = self.state.dependency_tree.lock().unwrap();
+                            let package_name = tree
+                                .selected_path()
+                                .and_then(|path| tree.node(&path))
+                                .filter(|node| node.kind != NodeKind::Heading)
+                                .map(|node| tree.qualify(&node.name));
+                            drop(tree);
+                            if let Some(package_name) = package_name {
+                                self.explore_dependencies(package_name)?;
+                            }
+                        }
+                        KeyCode::Esc => *current_pane = Pane::SearchResults,
+                        _ => {}
+                    }
+                }
+            }
         };
         Ok(())
     }
@@ -140,6 +351,398 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
         Ok(())
     }
 
+    fn cancel_operation(&self) -> color_eyre::Result<()> {
+        let operation = self.state.operation.lock().unwrap().clone();
+        if let Some(operation) = operation {
+            self.task_manager.cancel(operation.command)?;
+        }
+        Ok(())
+    }
+
+    /// Ask the user to confirm running `command` on the selected search
+    /// result before anything is changed
+    fn request_confirmation(&self, command: Command) {
+        let search = self.state.search.lock().unwrap();
+        let Some(package) = search.results.get(search.selected_result) else {
+            return;
+        };
+        self.state.request_package_confirmation(command, package);
+    }
+
+    /// Show the dependencies and dependents of `package_name` in place of
+    /// the context pane
+    fn explore_dependencies(&mut self, package_name: String) -> color_eyre::Result<()> {
+        let mut tree = self.state.dependency_tree.lock().unwrap();
+        tree.roots = vec![TreeNode::heading(
+            format!("Looking up the dependencies of {package_name}"),
+            Vec::new(),
+        )];
+        tree.package_name = package_name;
+        tree.selected = 0;
+        tree.pending = None;
+        drop(tree);
+        self.task_manager.execute(Command::Dependencies, false)
+    }
+
+    fn select_previous_tree_node(&self) {
+        let mut tree = self.state.dependency_tree.lock().unwrap();
+        tree.selected = tree.selected.saturating_sub(1);
+    }
+
+    fn select_next_tree_node(&self) {
+        let mut tree = self.state.dependency_tree.lock().unwrap();
+        let rows = tree.rows().len();
+        tree.selected = (tree.selected + 1).min(rows.saturating_sub(1));
+    }
+
+    /// Expand the highlighted node, querying the dependents of a dependent
+    /// the first time it is expanded
+    fn expand_tree_node(&mut self) -> color_eyre::Result<()> {
+        let mut tree = self.state.dependency_tree.lock().unwrap();
+        let Some(path) = tree.selected_path() else {
+            return Ok(());
+        };
+        let Some(node) = tree.node_mut(&path) else {
+            return Ok(());
+        };
+        if node.expanded || !node.is_expandable() {
+            return Ok(());
+        }
+        if node.loaded {
+            node.expanded = true;
+            return Ok(());
+        }
+        tree.pending = Some(path);
+        drop(tree);
+        self.task_manager.execute(Command::Dependents, false)
+    }
+
+    /// Collapse the highlighted node, or move to its parent when there is
+    /// nothing to collapse
+    fn collapse_tree_node(&self) {
+        let mut tree = self.state.dependency_tree.lock().unwrap();
+        let Some(mut path) = tree.selected_path() else {
+            return;
+        };
+        if let Some(node) = tree.node_mut(&path)
+            && node.expanded
+        {
+            node.expanded = false;
+            return;
+        }
+        path.pop();
+        if let Some(parent) = tree.rows().iter().position(|(row, _node)| *row == path) {
+            tree.selected = parent;
+        }
+    }
+
+    /// Pin or unpin the selected search result, which is undone as easily
+    /// as it is done so it is not confirmed first
+    fn pin_selected_search_result(&mut self, command: Command) -> color_eyre::Result<()> {
+        let search = self.state.search.lock().unwrap();
+        let Some(package) = search.results.get(search.selected_result).cloned() else {
+            return Ok(());
+        };
+        drop(search);
+        self.pin(command, package)
+    }
+
+    fn pin_highlighted_outdated(&mut self, command: Command) -> color_eyre::Result<()> {
+        let outdated = self.state.outdated.lock().unwrap();
+        let Some(package) = outdated.highlighted().cloned() else {
+            return Ok(());
+        };
+        drop(outdated);
+        self.pin(command, package)
+    }
+
+    /// Pin or unpin `package` right away, unless several aggregated
+    /// backends offer it and the user has to pick one of them first
+    fn pin(&mut self, command: Command, package: Package) -> color_eyre::Result<()> {
+        match (self.registry.is_aggregated(), &package.sources[..]) {
+            (true, [backend]) => self
+                .task_manager
+                .execute_on(command, vec![backend.qualify(&package.name)]),
+            (true, [_, _, ..]) => {
+                self.state.request_package_confirmation(command, &package);
+                Ok(())
+            }
+            _ => self.task_manager.execute_on(command, vec![package.name]),
+        }
+    }
+
+    fn handle_confirmation(&mut self, key_event: event::KeyEvent) -> color_eyre::Result<()> {
+        match key_event.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                let confirmation = self.state.confirmation.lock().unwrap().take();
+                match confirmation {
+                    // the manifest is applied with every backend it lists,
+                    // not only with the active one
+                    Some(confirmation) if confirmation.command == Command::ApplyManifest => {
+                        let path = confirmation.package_names.concat();
+                        self.task_manager.manifest(
+                            Command::ApplyManifest,
+                            path,
+                            self.registry.available(),
+                            confirmation.removals,
+                        )?;
+                    }
+                    Some(confirmation) => {
+                        self.task_manager
+                            .execute_on(confirmation.command, confirmation.targets())?;
+                    }
+                    None => {}
+                }
+            }
+            KeyCode::Tab => {
+                if let Some(confirmation) = self.state.confirmation.lock().unwrap().as_mut() {
+                    confirmation.next_source();
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                *self.state.confirmation.lock().unwrap() = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_prompt(&mut self, key_event: event::KeyEvent) -> color_eyre::Result<()> {
+        let mut prompt = self.state.prompt.lock().unwrap();
+        match key_event.code {
+            KeyCode::Enter => {
+                let Some(prompt) = prompt.take() else {
+                    return Ok(());
+                };
+                match prompt.command {
+                    Command::ExportGraph => self.task_manager.export_graph(prompt.input)?,
+                    Command::ExportBrewfile | Command::PlanBrewfile | Command::BrewfileDrift => {
+                        self.task_manager.brewfile(prompt.command, prompt.input)?;
+                    }
+                    Command::SnapshotManifest | Command::DiffManifest | Command::PlanManifest => {
+                        self.task_manager.manifest(
+                            prompt.command,
+                            prompt.input,
+                            self.registry.available(),
+                            Vec::new(),
+                        )?;
+                    }
+                    Command::AddTap if !prompt.input.trim().is_empty() => {
+                        self.task_manager
+                            .execute_on(Command::AddTap, vec![prompt.input.trim().to_string()])?;
+                    }
+                    _ => {}
+                }
+            }
+            KeyCode::Esc => *prompt = None,
+            KeyCode::Backspace => {
+                if let Some(prompt) = prompt.as_mut() {
+                    prompt.input.pop();
+                }
+            }
+            KeyCode::Char(ch) => {
+                if let Some(prompt) = prompt.as_mut() {
+                    prompt.input.push(ch);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn switch_package_manager(&mut self, backend: Backend) -> color_eyre::Result<()> {
+        info!("switched package manager to {backend}");
+        self.state
+            .update_package_manager(backend, self.registry.available());
+        self.state.update_context(String::default());
+        self.task_manager.execute(Command::Config, true)?;
+        self.task_manager.execute(Command::FilterPackages, false)?;
+        Ok(())
+    }
+
+    fn toggle_aggregated(&mut self) -> color_eyre::Result<()> {
+        let aggregated = !self.registry.is_aggregated();
+        info!("aggregated search across all backends: {aggregated}");
+        self.registry.set_aggregated(aggregated);
+        self.state.update_aggregated(aggregated);
+        self.task_manager.execute(Command::FilterPackages, true)?;
+        Ok(())
+    }
+
+    /// Query the outdated packages, the taps or the services again while
+    /// they are being shown
+    fn refresh_pane(&mut self) -> color_eyre::Result<()> {
+        let current_pane = self.state.current_pane().clone();
+        match current_pane {
+            Pane::Outdated => self
+                .task_manager
+                .execute(Command::OutdatedPackages, false)?,
+            Pane::Taps => self.task_manager.execute(Command::Taps, false)?,
+            Pane::Services => self.task_manager.execute(Command::Services, false)?,
+            // a fix may have taken care of the highlighted warning
+            Pane::Info => self.task_manager.execute(Command::RecheckHealth, false)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Start, stop, restart or run the highlighted service, which is
+    /// undone as easily as it is done so it is not confirmed first
+    fn control_service(&mut self, command: Command) -> color_eyre::Result<()> {
+        let services = self.state.services.lock().unwrap();
+        let Some(service) = services.highlighted() else {
+            return Ok(());
+        };
+        let service_name = service.name.clone();
+        drop(services);
+        self.task_manager.execute_on(command, vec![service_name])
+    }
+
+    fn select_previous_health_warning(&self) {
+        let mut health = self.state.health.lock().unwrap();
+        health.list_state.select_previous();
+        health.clamp_selection();
+        self.state.update_context(health.summary());
+    }
+
+    fn select_next_health_warning(&self) {
+        let mut health = self.state.health.lock().unwrap();
+        let count = health.visible().len();
+        if count == 0 {
+            return;
+        }
+        let next = health
+            .list_state
+            .selected()
+            .map_or(0, |selected| (selected + 1) % count);
+        health.list_state.select(Some(next));
+        self.state.update_context(health.summary());
+    }
+
+    /// Confirm running the fixes suggested for the highlighted warning,
+    /// listing the exact commands. Commands the backend cannot run for the
+    /// user, such as the ones needing sudo, stay in the explanation of the
+    /// warning to be run in a terminal
+    fn fix_health_warning(&self) {
+        let health = self.state.health.lock().unwrap();
+        let Some(warning) = health.highlighted() else {
+            return;
+        };
+        let title = warning.title.clone();
+        let fixes = warning.fixes.clone();
+        drop(health);
+        match fixes.is_empty() {
+            false => self
+                .state
+                .request_confirmation(Command::RunHealthFix, fixes),
+            true => self
+                .state
+                .update_context(format!("==> No fix is suggested for: {title}")),
+        }
+    }
+
+    /// Hide the highlighted warning from now on
+    fn ignore_health_warning(&self) {
+        let mut health = self.state.health.lock().unwrap();
+        let Some(title) = health.highlighted().map(|warning| warning.title.clone()) else {
+            return;
+        };
+        health.ignored.insert(title);
+        health.clamp_selection();
+        self.save_ignored_health_warnings(&health);
+    }
+
+    /// Show every ignored warning again
+    fn unignore_health_warnings(&self) {
+        let mut health = self.state.health.lock().unwrap();
+        health.ignored.clear();
+        health.clamp_selection();
+        self.save_ignored_health_warnings(&health);
+    }
+
+    fn save_ignored_health_warnings(&self, health: &HealthState) {
+        match health.save_ignored() {
+            Ok(()) => self.state.update_context(health.summary()),
+            Err(e) => self
+                .state
+                .update_context(format!("==> Could not save the ignored warnings: {e}")),
+        }
+    }
+
+    fn select_previous_service(&self) {
+        self.state
+            .services
+            .lock()
+            .unwrap()
+            .list_state
+            .select_previous();
+    }
+
+    fn select_next_service(&self) {
+        let mut services = self.state.services.lock().unwrap();
+        if services.services.is_empty() {
+            return;
+        }
+        let next = services
+            .list_state
+            .selected()
+            .map_or(0, |selected| (selected + 1) % services.services.len());
+        services.list_state.select(Some(next));
+    }
+
+    fn select_previous_tap(&self) {
+        self.state.taps.lock().unwrap().list_state.select_previous();
+    }
+
+    fn select_next_tap(&self) {
+        let mut taps = self.state.taps.lock().unwrap();
+        if taps.taps.is_empty() {
+            return;
+        }
+        let next = taps
+            .list_state
+            .selected()
+            .map_or(0, |selected| (selected + 1) % taps.taps.len());
+        taps.list_state.select(Some(next));
+    }
+
+    fn select_previous_outdated_package(&self) {
+        self.state
+            .outdated
+            .lock()
+            .unwrap()
+            .list_state
+            .select_previous();
+    }
+
+    fn select_next_outdated_package(&self) {
+        let mut outdated = self.state.outdated.lock().unwrap();
+        if outdated.packages.is_empty() {
+            return;
+        }
+        let next = outdated
+            .list_state
+            .selected()
+            .map_or(0, |selected| (selected + 1) % outdated.packages.len());
+        outdated.list_state.select(Some(next));
+    }
+
+    /// Mark or unmark the highlighted package for upgrading
+    fn toggle_outdated_package(&self) {
+        let mut outdated = self.state.outdated.lock().unwrap();
+        let Some(name) = outdated
+            .list_state
+            .selected()
+            .and_then(|index| outdated.packages.get(index))
+            .map(|package| package.name.clone())
+        else {
+            return;
+        };
+        if !outdated.selected.remove(&name) {
+            outdated.selected.insert(name);
+        }
+    }
+
     fn append_search_query(&mut self, ch: char) -> color_eyre::Result<()> {
         if let Ok(mut search) = self.state.search.lock() {
             search.query.push(ch);
@@ -176,7 +779,7 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
 
     fn select_next_search_result(&mut self) -> color_eyre::Result<()> {
         if let Ok(mut search) = self.state.search.lock() {
-            if search.results.len() == 0 {
+            if search.results.is_empty() {
                 return Ok(());
             }
             search.selected_result =
@@ -187,8 +790,23 @@ impl<T: PackageManager + Send + Sync + 'static> InputHandler<T> {
         Ok(())
     }
 
+    /// Query the search results again after a package operation changed
+    /// what is installed
+    fn refresh_search(&mut self) -> color_eyre::Result<()> {
+        let mut refresh = self.state.refresh.lock().unwrap();
+        if !*refresh {
+            return Ok(());
+        }
+        *refresh = false;
+        drop(refresh);
+        self.task_manager.execute(Command::FilterPackages, false)?;
+        self.task_manager.execute(Command::PackageInfo, false)?;
+        self.refresh_pane()?;
+        Ok(())
+    }
+
     fn update_search(&mut self) -> color_eyre::Result<()> {
-        if self.update == false {
+        if !self.update {
             return Ok(());
         };
         self.update = false;
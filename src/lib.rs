@@ -2,23 +2,214 @@ use std::cmp::min;
 
 pub mod package_manager;
 
-pub fn fuzz<I>(word_list: I, query: String, threshold: usize) -> Vec<String>
+/// Points for every character of the query that matched
+const SCORE_MATCH: i64 = 16;
+/// Penalty for the first character skipped between two matched characters
+const SCORE_GAP_START: i64 = -3;
+/// Penalty for every further character skipped
+const SCORE_GAP_EXTENSION: i64 = -1;
+/// Bonus for matching the first character of a word, such as the `r` of
+/// `ripgrep` or the `g` of `git-delta`
+const BONUS_BOUNDARY: i64 = SCORE_MATCH / 2;
+/// Bonus for matching a separator, so that `-` prefers `git-delta` over
+/// `git delta`
+const BONUS_NON_WORD: i64 = SCORE_MATCH / 2;
+/// Bonus for matching the start of a camelCase word or of a number
+const BONUS_CAMEL: i64 = BONUS_BOUNDARY + SCORE_GAP_EXTENSION;
+/// Bonus for matching right after the previous matched character, as much
+/// as a gap would have cost
+const BONUS_CONSECUTIVE: i64 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+/// The bonus of the first character of the query counts this many times,
+/// which favours matches starting at a word over matches within one
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// How well a word matched a query
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Positions of the characters of the word that matched, in characters
+    /// rather than bytes
+    pub positions: Vec<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Separator,
+    NonWord,
+    Lower,
+    Upper,
+    Number,
+}
+
+impl CharClass {
+    fn of(c: char) -> Self {
+        match c {
+            c if c.is_lowercase() => Self::Lower,
+            c if c.is_uppercase() => Self::Upper,
+            c if c.is_numeric() => Self::Number,
+            c if c.is_alphabetic() => Self::Lower,
+            ' ' | '/' | ',' | ':' | ';' | '|' | '-' | '_' | '.' | '@' => Self::Separator,
+            _ => Self::NonWord,
+        }
+    }
+
+    /// Bonus for matching a character of this class following a character
+    /// of class `previous`
+    fn bonus(self, previous: CharClass) -> i64 {
+        match (previous, self) {
+            (_, Self::Separator | Self::NonWord) => BONUS_NON_WORD,
+            (Self::Separator | Self::NonWord, _) => BONUS_BOUNDARY,
+            (Self::Lower, Self::Upper) => BONUS_CAMEL,
+            (Self::Lower | Self::Upper, Self::Number) => BONUS_CAMEL,
+            _ => 0,
+        }
+    }
+}
+
+/// Score `word` against `query` when every character of the query appears
+/// in the word in order, ignoring case. Matches at the start of words, at
+/// the start of the word itself and in contiguous runs score higher, gaps
+/// between matched characters lower, the way fzf ranks its results
+pub fn fuzzy_match(word: &str, query: &str) -> Option<FuzzyMatch> {
+    let word: Vec<char> = word.chars().collect();
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    let lowercase: Vec<char> = word
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    // the beginning of the word counts as a word boundary
+    let mut previous = CharClass::Separator;
+    let bonuses: Vec<i64> = word
+        .iter()
+        .map(|c| {
+            let class = CharClass::of(*c);
+            let bonus = class.bonus(previous);
+            previous = class;
+            bonus
+        })
+        .collect();
+
+    // scores[i][j] is the best score of the query up to its i-th character
+    // with that character matched at the j-th character of the word, and
+    // from[i][j] where the previous character of the query was matched
+    let (rows, columns) = (query.len(), word.len());
+    let mut scores: Vec<Vec<Option<i64>>> = vec![vec![None; columns]; rows];
+    let mut from: Vec<Vec<usize>> = vec![vec![0; columns]; rows];
+    for (i, q) in query.iter().enumerate() {
+        // best score of the previous row ending at least two characters
+        // back, along with where it ended, penalised for the gap so far
+        let mut gap: Option<(i64, usize)> = None;
+        for j in 0..columns {
+            if i > 0 && j >= 2 {
+                let extended = gap.map(|(score, k)| (score + SCORE_GAP_EXTENSION, k));
+                let started = scores[i - 1][j - 2].map(|score| (score + SCORE_GAP_START, j - 2));
+                gap = match (extended, started) {
+                    (Some(extended), Some(started)) if extended.0 > started.0 => Some(extended),
+                    (extended, None) => extended,
+                    (_, started) => started,
+                };
+            }
+            if lowercase[j] != *q {
+                continue;
+            }
+            if i == 0 {
+                // characters skipped before the first match cost nothing
+                scores[i][j] = Some(SCORE_MATCH + bonuses[j] * BONUS_FIRST_CHAR_MULTIPLIER);
+                continue;
+            }
+            let consecutive = match j {
+                0 => None,
+                _ => scores[i - 1][j - 1].map(|score| {
+                    (
+                        score + SCORE_MATCH + bonuses[j].max(BONUS_CONSECUTIVE),
+                        j - 1,
+                    )
+                }),
+            };
+            let gapped = gap.map(|(score, k)| (score + SCORE_MATCH + bonuses[j], k));
+            let best = match (consecutive, gapped) {
+                (Some(consecutive), Some(gapped)) if gapped.0 > consecutive.0 => Some(gapped),
+                (None, gapped) => gapped,
+                (consecutive, _) => consecutive,
+            };
+            if let Some((score, k)) = best {
+                scores[i][j] = Some(score);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    // the earliest of the best scoring ends, characters after it cost nothing
+    let (mut end, score) = scores[rows - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| Some((j, (*score)?)))
+        .fold(None, |best: Option<(usize, i64)>, (j, score)| match best {
+            Some((_, best_score)) if best_score >= score => best,
+            _ => Some((j, score)),
+        })?;
+    let mut positions = vec![0; rows];
+    for i in (0..rows).rev() {
+        positions[i] = end;
+        end = from[i][end];
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+/// Words matching the query, best match first. Words the query is not a
+/// subsequence of still match when they are fewer than `threshold` edits
+/// away from it, to forgive typos, ranking after every other match. Words
+/// can be anything standing for a word, such as a package for its name. An
+/// empty query matches every word, in the order of the word list
+pub fn fuzz<W, I>(word_list: I, query: String, threshold: usize) -> Vec<(W, FuzzyMatch)>
 where
-    I: IntoIterator<Item = String>,
+    W: AsRef<str>,
+    I: IntoIterator<Item = W>,
 {
+    if query.is_empty() {
+        return word_list
+            .into_iter()
+            .map(|word| {
+                let fuzzy_match = FuzzyMatch {
+                    score: 0,
+                    positions: Vec::new(),
+                };
+                (word, fuzzy_match)
+            })
+            .collect();
+    }
     let query_lowercase = query.to_lowercase();
-    word_list
+    let mut matches: Vec<(W, FuzzyMatch)> = word_list
         .into_iter()
-        .filter(|word| {
-            let word_lowercase = word.to_lowercase();
-
-            if word_lowercase.contains(&query_lowercase) {
-                return true;
+        .filter_map(|word| {
+            if let Some(fuzzy_match) = fuzzy_match(word.as_ref(), &query_lowercase) {
+                return Some((word, fuzzy_match));
             }
-
-            levenshtein_distance(word, &query_lowercase) < threshold
+            let distance = levenshtein_distance(&word.as_ref().to_lowercase(), &query_lowercase);
+            (distance < threshold).then(|| {
+                let fuzzy_match = FuzzyMatch {
+                    score: i64::MIN,
+                    positions: Vec::new(),
+                };
+                (word, fuzzy_match)
+            })
+        })
+        .collect();
+    // ties go to the shorter word, then keep the order of the word list
+    matches.sort_by(|(a_word, a), (b_word, b)| {
+        b.score.cmp(&a.score).then_with(|| {
+            let (a_word, b_word) = (a_word.as_ref(), b_word.as_ref());
+            a_word.chars().count().cmp(&b_word.chars().count())
         })
-        .collect()
+    });
+    matches
 }
 
 fn levenshtein_distance(s: &str, t: &str) -> usize {
@@ -52,3 +243,101 @@ fn levenshtein_distance(s: &str, t: &str) -> usize {
 
     dp[s_len]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(word: &str, query: &str) -> i64 {
+        fuzzy_match(word, query).unwrap().score
+    }
+
+    fn positions(word: &str, query: &str) -> Vec<usize> {
+        fuzzy_match(word, query).unwrap().positions
+    }
+
+    #[test]
+    fn word_boundary_bonus() {
+        assert!(score("git-delta", "gd") > score("gitdelta", "gd"));
+        // `d` is matched at the start of `delta` rather than at the first
+        // `d` of the word
+        assert_eq!(positions("gadget-delta", "gd"), [0, 7]);
+    }
+
+    #[test]
+    fn consecutive_runs() {
+        assert!(score("abc", "abc") > score("axbxc", "abc"));
+        assert_eq!(positions("ripgrep", "rip"), [0, 1, 2]);
+        assert_eq!(positions("rxipgrep-rip", "rip"), [9, 10, 11]);
+    }
+
+    #[test]
+    fn camel_case() {
+        assert!(score("PackageState", "ps") > score("Packagestate", "ps"));
+        assert_eq!(positions("PackageState", "ps"), [0, 7]);
+        assert_eq!(positions("Package2State", "p2s"), [0, 7, 8]);
+    }
+
+    #[test]
+    fn matched_positions() {
+        assert_eq!(positions("git-delta", "delta"), [4, 5, 6, 7, 8]);
+        assert_eq!(fuzzy_match("wget", "tw"), None);
+        assert_eq!(fuzzy_match("wget", "wgets"), None);
+    }
+
+    #[test]
+    fn non_ascii() {
+        // positions are counted in characters rather than bytes
+        assert_eq!(positions("größe", "öß"), [2, 3]);
+        assert_eq!(positions("Ärger", "är"), [0, 1]);
+        assert_eq!(positions("Ärger", "ÄR"), [0, 1]);
+    }
+
+    #[test]
+    fn empty_query() {
+        assert_eq!(
+            fuzzy_match("wget", ""),
+            Some(FuzzyMatch {
+                score: 0,
+                positions: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn typos_rank_last() {
+        let words = ["ripgrep", "ripgerp-cli", "fd"];
+        let matches = fuzz(words, "ripgerp".to_string(), 3);
+        assert_eq!(
+            matches.iter().map(|(word, _)| *word).collect::<Vec<&str>>(),
+            ["ripgerp-cli", "ripgrep"]
+        );
+        assert_eq!(matches[1].1.score, i64::MIN);
+        assert!(matches[1].1.positions.is_empty());
+
+        // `ripgrep` is two edits away from `ripgerp`
+        let matches = fuzz(words, "ripgerp".to_string(), 2);
+        assert_eq!(
+            matches.iter().map(|(word, _)| *word).collect::<Vec<&str>>(),
+            ["ripgerp-cli"]
+        );
+    }
+
+    #[test]
+    fn empty_query_keeps_the_order() {
+        let matches = fuzz(["ripgrep", "fd", "bat"], String::new(), 0);
+        assert_eq!(
+            matches.iter().map(|(word, _)| *word).collect::<Vec<&str>>(),
+            ["ripgrep", "fd", "bat"]
+        );
+    }
+
+    #[test]
+    fn ties_go_to_the_shorter_word() {
+        let matches = fuzz(["fd-find", "fd"], "fd".to_string(), 0);
+        assert_eq!(
+            matches.iter().map(|(word, _)| *word).collect::<Vec<&str>>(),
+            ["fd", "fd-find"]
+        );
+    }
+}
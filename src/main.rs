@@ -1,13 +1,11 @@
-use commands::CommandType;
 use input::InputHandler;
 use logging::initialize_logging;
 use state::State;
 use std::{sync::Arc, thread};
 use task_manager::TaskManager;
 use tracing::info;
-use wherehouse::package_manager::{Command, homebrew::Homebrew};
+use wherehouse::package_manager::{Command, registry::Registry};
 
-mod commands;
 mod input;
 mod logging;
 mod state;
@@ -20,11 +18,13 @@ fn main() -> color_eyre::Result<()> {
     initialize_logging()?;
     info!("initialized logging");
     let state = Arc::new(State::new());
-    let package_manager = Arc::new(Homebrew);
-    let mut task_manager = TaskManager::new(state.clone(), package_manager);
+    let registry = Arc::new(Registry::detect());
+    state.update_package_manager(registry.active(), registry.available());
+    info!("using {} as the package manager", registry.active());
+    let mut task_manager = TaskManager::new(state.clone(), registry.clone());
     task_manager.execute(Command::Config, false)?;
 
-    let mut input_handler = InputHandler::new(state.clone(), task_manager);
+    let mut input_handler = InputHandler::new(state.clone(), task_manager, registry);
     let _input_thread = thread::spawn(move || input_handler.run());
     info!("Input handler thread initiated");
 
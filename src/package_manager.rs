@@ -1,13 +1,40 @@
 use std::{
+    collections::BTreeMap,
+    env,
     ffi::OsStr,
     fmt::Display,
     io::{BufRead, BufReader},
-    process::{Child, Output, Stdio},
-    sync::mpsc::{Receiver, channel},
+    path::PathBuf,
+    process::{Child, ExitStatus, Stdio},
+    sync::{
+        Arc, Mutex,
+        mpsc::{Receiver, Sender, channel},
+    },
     thread,
 };
 
+use serde::{Deserialize, Serialize};
+
+use crate::{fuzz, fuzzy_match};
+use error::Error;
+use graph::DependencyGraph;
+
+pub mod aggregate;
+pub mod apt;
+pub mod cargo;
+pub mod dnf;
+pub mod error;
+pub mod flatpak;
+pub mod graph;
 pub mod homebrew;
+pub mod manifest;
+pub mod nix;
+pub mod npm;
+pub mod pacman;
+pub mod pipx;
+pub mod registry;
+#[cfg(test)]
+mod stub;
 
 pub type SpawnCommandResult = Result<std::process::Child, std::io::Error>;
 pub type CommandResult = std::io::Result<std::process::Output>;
@@ -28,70 +55,158 @@ where
 pub struct SpawnedCommandOutput {
     pub out: Option<String>,
     pub err: Option<String>,
+    pub status: ExitStatus,
+}
+
+impl SpawnedCommandOutput {
+    /// Fail with the captured stderr when the command exited
+    /// unsuccessfully
+    pub fn success(self, command: &str) -> Result<Self, Error> {
+        if self.status.success() {
+            return Ok(self);
+        }
+        Err(Error::from_exit(
+            command,
+            self.status.code(),
+            self.err.unwrap_or_default(),
+        ))
+    }
+
+    /// Everything the command printed like `combined`, failing with all of
+    /// it when the command exited unsuccessfully, for checks that report
+    /// the problems they find on stdout
+    pub fn report(self, command: &str) -> Result<String, Error> {
+        let status = self.status;
+        let report = self.combined();
+        if status.success() {
+            return Ok(report);
+        }
+        Err(Error::from_exit(command, status.code(), report))
+    }
+
+    /// Everything the command printed, stdout followed by stderr
+    pub fn combined(self) -> String {
+        format!(
+            "{}{}",
+            self.out.unwrap_or_default(),
+            self.err.unwrap_or_default()
+        )
+    }
+}
+
+/// Cancellation requests for an operation running several commands in a
+/// row, forwarded to whichever of them is currently running
+pub struct Cancellation {
+    cancelled: Arc<Mutex<bool>>,
+    current: Arc<Mutex<Option<Sender<bool>>>>,
+}
+
+impl Cancellation {
+    pub fn forward(rx: Receiver<bool>) -> Self {
+        let cancelled = Arc::new(Mutex::new(false));
+        let current: Arc<Mutex<Option<Sender<bool>>>> = Arc::new(Mutex::new(None));
+        let (forward_cancelled, forward_current) = (cancelled.clone(), current.clone());
+        thread::spawn(move || {
+            for _ in rx {
+                *forward_cancelled.lock().unwrap() = true;
+                if let Some(tx) = forward_current.lock().unwrap().as_ref() {
+                    let _ = tx.send(true);
+                }
+            }
+        });
+        Self { cancelled, current }
+    }
+
+    /// Receiver for the next command, None once the operation was cancelled
+    pub fn receiver(&self) -> Option<Receiver<bool>> {
+        let mut current = self.current.lock().unwrap();
+        if *self.cancelled.lock().unwrap() {
+            return None;
+        }
+        let (tx, rx) = channel::<bool>();
+        *current = Some(tx);
+        Some(rx)
+    }
 }
 
+/// Wait for a spawned command to exit, killing it as soon as a
+/// cancellation is received on `rx`
 pub fn handle_spawned_command(
+    rx: Receiver<bool>,
+    child: Child,
+) -> Result<SpawnedCommandOutput, Error> {
+    wait_for_child(rx, child, None)
+}
+
+/// Wait for a spawned command like `handle_spawned_command`, additionally
+/// forwarding every line it prints on stdout or stderr to `tx` as soon as
+/// it is printed
+pub fn stream_spawned_command(
+    rx: Receiver<bool>,
+    tx: Sender<String>,
+    child: Child,
+) -> Result<SpawnedCommandOutput, Error> {
+    wait_for_child(rx, child, Some(tx))
+}
+
+fn wait_for_child(
     rx: Receiver<bool>,
     mut child: Child,
-) -> Option<SpawnedCommandOutput> {
+    tx_lines: Option<Sender<String>>,
+) -> Result<SpawnedCommandOutput, Error> {
     // handle the stdout stream in another thread
     let stdout = child.stdout.take().expect("no stdout");
     let (tx_stdout, rx_stdout) = channel::<String>();
+    let tx_stdout_lines = tx_lines.clone();
     let stdout_handle = thread::spawn(move || {
         let mut out = String::new();
         let reader = BufReader::new(stdout);
-        for line in reader.lines() {
-            if let Ok(content) = line {
-                out.push_str(&content);
-                out.push('\n');
+        for content in reader.lines().map_while(Result::ok) {
+            if let Some(tx) = &tx_stdout_lines {
+                let _ = tx.send(content.clone());
             }
+            out.push_str(&content);
+            out.push('\n');
         }
-        tx_stdout.send(out).unwrap();
+        let _ = tx_stdout.send(out);
     });
 
     // handle the stderr stream in another thread
-    let stderr = child.stderr.take().expect("no stdout");
+    let stderr = child.stderr.take().expect("no stderr");
     let (tx_stderr, rx_stderr) = channel::<String>();
     let stderr_handle = thread::spawn(move || {
         let mut err = String::new();
         let reader = BufReader::new(stderr);
-        for line in reader.lines() {
-            if let Ok(content) = line {
-                err.push_str(&content);
-                err.push('\n');
+        for content in reader.lines().map_while(Result::ok) {
+            if let Some(tx) = &tx_lines {
+                let _ = tx.send(content.clone());
             }
+            err.push_str(&content);
+            err.push('\n');
         }
-        tx_stderr.send(err).unwrap();
+        let _ = tx_stderr.send(err);
     });
 
     loop {
         // check if the spawned command has exited
-        if let Ok(Some(_)) = child.try_wait() {
-            // collect the stdout stream as a string via the channel
-            let out = match rx_stdout.recv() {
-                Ok(out) => Some(out),
-                Err(_) => None,
-            };
-            // collect the stderr stream as a string via the channel
-            let err = match rx_stderr.recv() {
-                Ok(err) => Some(err),
-                Err(_) => None,
-            };
-            return Some(SpawnedCommandOutput { out, err });
-        }
-        // check if spawned command is stale and terminal if it is stale
-        if let Ok(is_stale) = rx.try_recv() {
-            if is_stale {
-                child.kill().unwrap(); // kill the spawned command
-
-                // join the stream handling threads
-                stdout_handle.join().expect("Failed to join stdout thread");
-                stderr_handle.join().expect("Failed to join stderr thread");
-                break;
-            }
+        if let Ok(Some(status)) = child.try_wait() {
+            // collect the stdout and stderr streams as strings via the channels
+            let out = rx_stdout.recv().ok();
+            let err = rx_stderr.recv().ok();
+            return Ok(SpawnedCommandOutput { out, err, status });
+        }
+        // check if spawned command is stale and terminate it if it is stale
+        if let Ok(true) = rx.try_recv() {
+            // the command may have exited in the meantime
+            let _ = child.kill();
+            let _ = child.wait();
+
+            // join the stream handling threads
+            stdout_handle.join().expect("Failed to join stdout thread");
+            stderr_handle.join().expect("Failed to join stderr thread");
+            return Err(Error::Cancelled);
         }
     }
-    None
 }
 
 /// create a blocking command and run until completion returning the output wrapped in a Result
@@ -118,16 +233,450 @@ impl Display for PackageLocality {
     }
 }
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum PackageKind {
+    #[default]
+    Package,
+    Formula,
+    Cask,
+    Application,
+}
+
+impl Display for PackageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Package => write!(f, "package"),
+            Self::Formula => write!(f, "formula"),
+            Self::Cask => write!(f, "cask"),
+            Self::Application => write!(f, "app"),
+        }
+    }
+}
+
+/// A package reported by `PackageManager::filter_packages`, carrying
+/// whatever metadata the backend reports alongside the name
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Package {
+    pub name: String,
+    /// Latest version offered by the backend
+    pub version: Option<String>,
+    /// Version currently installed, None when the package is not installed
+    pub installed_version: Option<String>,
+    pub description: Option<String>,
+    pub kind: PackageKind,
+    /// Backends offering the package, more than one when the results of
+    /// several backends have been merged
+    pub sources: Vec<Backend>,
+    /// Tap, repository or remote the package is published in
+    pub repository: Option<String>,
+    /// Whether the package is held at its installed version
+    pub pinned: bool,
+    /// Positions of the characters of the name that matched the search
+    /// pattern, in characters rather than bytes
+    pub matched: Vec<usize>,
+}
+
+impl Package {
+    pub fn new(name: String, source: Backend) -> Self {
+        Self {
+            name,
+            version: None,
+            installed_version: None,
+            description: None,
+            kind: PackageKind::default(),
+            sources: vec![source],
+            repository: None,
+            pinned: false,
+            matched: Vec::new(),
+        }
+    }
+
+    pub fn is_installed(&self) -> bool {
+        self.installed_version.is_some()
+    }
+
+    /// Fill in the metadata missing from this package with the metadata
+    /// reported for the same package by another backend
+    pub fn merge(&mut self, other: Package) {
+        for source in other.sources {
+            if !self.sources.contains(&source) {
+                self.sources.push(source);
+            }
+        }
+        self.version = self.version.take().or(other.version);
+        self.installed_version = self.installed_version.take().or(other.installed_version);
+        self.description = self.description.take().or(other.description);
+        self.repository = self.repository.take().or(other.repository);
+        self.pinned |= other.pinned;
+    }
+}
+
+/// Packages are searched for by name
+impl AsRef<str> for Package {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Edits a package name may be away from `pattern` to still match it, so
+/// that longer patterns forgive more typos
+fn typo_threshold(pattern: &str) -> usize {
+    (pattern.chars().count() + 1) / 4
+}
+
+/// Keep the packages whose name fuzzily matches the pattern, best match
+/// first, recording which characters of the name matched
+pub fn fuzz_packages(packages: Vec<Package>, pattern: String) -> Vec<Package> {
+    let threshold = typo_threshold(&pattern);
+    fuzz(packages, pattern, threshold)
+        .into_iter()
+        .map(|(package, fuzzy_match)| Package {
+            matched: fuzzy_match.positions,
+            ..package
+        })
+        .collect()
+}
+
+/// Order the packages a backend found for the pattern by how well their
+/// name matches it, best match first, recording which characters of the
+/// name matched. Packages found by something else than their name, such as
+/// their description, are kept after the others in the order they came in
+pub fn rank_packages(packages: Vec<Package>, pattern: &str) -> Vec<Package> {
+    if pattern.is_empty() {
+        return packages;
+    }
+    let mut ranked = packages
+        .into_iter()
+        .map(|package| match fuzzy_match(&package.name, pattern) {
+            Some(fuzzy_match) => (
+                fuzzy_match.score,
+                Package {
+                    matched: fuzzy_match.positions,
+                    ..package
+                },
+            ),
+            None => (
+                i64::MIN,
+                Package {
+                    matched: Vec::new(),
+                    ..package
+                },
+            ),
+        })
+        .collect::<Vec<(i64, Package)>>();
+    // ties go to the shorter name, then keep the order they came in
+    ranked.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.name.chars().count().cmp(&b.name.chars().count()))
+    });
+    ranked.into_iter().map(|(_, package)| package).collect()
+}
+
+/// What a backend reports about itself and the system it runs on
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct SystemInfo {
+    pub version: Option<String>,
+    /// Where the backend installs packages
+    pub prefix: Option<String>,
+    pub cpu: Option<String>,
+    pub os: Option<String>,
+    pub git: Option<String>,
+    pub curl: Option<String>,
+    /// Environment variables set to change how the backend behaves
+    pub env_overrides: Vec<(String, String)>,
+    /// Every other field reported, as reported
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Display for SystemInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // pad every key to the widest one of its section so that the values
+        // line up
+        fn table(f: &mut std::fmt::Formatter<'_>, rows: &[(String, String)]) -> std::fmt::Result {
+            let width = rows
+                .iter()
+                .map(|(key, _)| key.chars().count())
+                .max()
+                .unwrap_or_default();
+            for (key, value) in rows {
+                writeln!(f, "{key:width$}  {value}")?;
+            }
+            Ok(())
+        }
+
+        let summary = [
+            ("Version", &self.version),
+            ("Prefix", &self.prefix),
+            ("CPU", &self.cpu),
+            ("OS", &self.os),
+            ("Git", &self.git),
+            ("Curl", &self.curl),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value.clone()?)))
+        .collect::<Vec<(String, String)>>();
+        table(f, &summary)?;
+        if !self.env_overrides.is_empty() {
+            writeln!(f, "==> Environment overrides")?;
+            table(f, &self.env_overrides)?;
+        }
+        if !self.extra.is_empty() {
+            writeln!(f, "==> Also reported")?;
+            let extra = self
+                .extra
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<Vec<(String, String)>>();
+            table(f, &extra)?;
+        }
+        Ok(())
+    }
+}
+
+/// What `PackageManager::clean` would remove, as reported by a dry run
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct CleanupPreview {
+    /// Files, cached downloads or store paths that would be deleted
+    pub files: Vec<String>,
+    /// Packages that would be uninstalled since nothing depends on them
+    pub packages: Vec<String>,
+    /// Disk space that would be freed, as reported by the backend
+    pub freed: Option<String>,
+}
+
+impl CleanupPreview {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.packages.is_empty()
+    }
+}
+
+impl Display for CleanupPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Nothing to clean up");
+        }
+        if !self.files.is_empty() {
+            writeln!(f, "==> Files to remove ({})", self.files.len())?;
+            for file in &self.files {
+                writeln!(f, "{file}")?;
+            }
+        }
+        if !self.packages.is_empty() {
+            writeln!(
+                f,
+                "==> Unneeded packages to uninstall ({})",
+                self.packages.len()
+            )?;
+            for package in &self.packages {
+                writeln!(f, "{package}")?;
+            }
+        }
+        if let Some(freed) = &self.freed {
+            writeln!(f, "==> Disk space to free")?;
+            writeln!(f, "{freed}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Difference between a Brewfile and what is installed, where every entry
+/// is qualified with its kind such as `formula wget` or `tap user/repo`
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct BrewfilePlan {
+    /// Entries of the Brewfile that are not installed, or services that
+    /// are not running
+    pub install: Vec<String>,
+    /// Installed entries the Brewfile leaves out
+    pub uninstall: Vec<String>,
+}
+
+impl BrewfilePlan {
+    pub fn is_empty(&self) -> bool {
+        self.install.is_empty() && self.uninstall.is_empty()
+    }
+}
+
+impl Display for BrewfilePlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Everything installed matches the Brewfile");
+        }
+        if !self.install.is_empty() {
+            writeln!(
+                f,
+                "==> In the Brewfile but not installed ({})",
+                self.install.len()
+            )?;
+            for entry in &self.install {
+                writeln!(f, "{entry}")?;
+            }
+        }
+        if !self.uninstall.is_empty() {
+            writeln!(
+                f,
+                "==> Installed but not in the Brewfile ({})",
+                self.uninstall.len()
+            )?;
+            for entry in &self.uninstall {
+                writeln!(f, "{entry}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "Warning"),
+            Self::Error => write!(f, "Error"),
+        }
+    }
+}
+
+/// A problem reported by one of the health checks of a backend
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HealthWarning {
+    /// Name of the check that reported the warning, to run it on its own
+    /// once the problem has been fixed
+    pub check: String,
+    pub title: String,
+    pub severity: Severity,
+    /// Explanation following the title
+    pub details: String,
+    /// Commands suggested to fix the problem
+    pub fixes: Vec<String>,
+}
+
+impl Display for HealthWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "==> {}: {}", self.severity, self.title)?;
+        if !self.details.is_empty() {
+            writeln!(f, "{}", self.details)?;
+        }
+        if !self.fixes.is_empty() {
+            writeln!(f, "==> Suggested fixes")?;
+            for fix in &self.fixes {
+                writeln!(f, "{fix}")?;
+            }
+        }
+        writeln!(f, "==> Reported by")?;
+        writeln!(f, "{}", self.check)
+    }
+}
+
+/// Run a `brew` command suggested by a health check as a fix, forwarding
+/// each line of output to `tx`. The fix is split on whitespace and run
+/// without a shell, so nothing in it is expanded or interpreted, and any
+/// other command is refused
+pub fn run_fix(rx: Receiver<bool>, tx: Sender<String>, fix: String) -> Result<String, Error> {
+    let mut words = fix.split_whitespace();
+    if words.next() != Some("brew") {
+        return Err(Error::unsupported(format!("running `{fix}` as a fix")));
+    }
+    let child = spawn_command("brew", words).map_err(|e| Error::from_io("brew", e))?;
+    Ok(stream_spawned_command(rx, tx, child)?
+        .success("brew")?
+        .combined())
+}
+
+/// A package along with the packages it depends on
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Dependency {
+    pub name: String,
+    /// Left empty by backends that only resolve direct dependencies
+    pub dependencies: Vec<Dependency>,
+}
+
+impl Dependency {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            dependencies: Vec::new(),
+        }
+    }
+}
+
+/// A third-party repository of packages added to a backend, such as a
+/// Homebrew tap
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Tap {
+    /// Name of the tap, as `<user>/<repository>`
+    pub name: String,
+    /// Git remote the tap is cloned from
+    pub remote: Option<String>,
+    pub formulae: usize,
+    pub casks: usize,
+    /// When the tap was last updated, as reported by the backend
+    pub last_updated: Option<String>,
+}
+
+/// A background service managed by a backend, such as a database started
+/// with `brew services`
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Service {
+    pub name: String,
+    /// Status as reported by the backend, such as `started` or `error`
+    pub status: String,
+    /// User the service runs as
+    pub user: Option<String>,
+    /// Launchd plist or systemd unit file the service is defined in
+    pub file: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ServiceAction {
+    /// Start the service and have it started at login
+    Start,
+    Stop,
+    Restart,
+    /// Start the service without having it started at login
+    Run,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Command {
     FilterPackages,
     Config,
     PackageInfo,
     GeneralInfo,
     CheckHealth,
+    RecheckHealth,
+    RunHealthFix,
+    OutdatedPackages,
+    Taps,
+    Services,
+    Dependencies,
+    Dependents,
+    PreviewClean,
     InstallPackage,
     UninstallPackage,
     UpdatePackage,
+    PinPackage,
+    UnpinPackage,
+    AddTap,
+    RemoveTap,
+    StartService,
+    StopService,
+    RestartService,
+    RunService,
+    CleanupServices,
+    ExportGraph,
+    ExportBrewfile,
+    PlanBrewfile,
+    BrewfileDrift,
+    ImportBrewfile,
+    SnapshotManifest,
+    DiffManifest,
+    PlanManifest,
+    ApplyManifest,
     Clean,
 }
 
@@ -138,34 +687,284 @@ pub trait PackageManager: Send + Sync + 'static {
         rx: Receiver<bool>,
         source: PackageLocality,
         pattern: String,
-    ) -> Result<Vec<String>, String>;
-    fn package_manager_config(&self, rx: Receiver<bool>) -> Result<String, String>;
-    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, String>;
-    fn check_health(&self, rx: Receiver<bool>) -> Result<String, String>;
-    fn clean(&self, rx: Receiver<bool>) -> Result<String, String>;
-    fn install_package(&self, rx: Receiver<bool>, package_name: String) -> Result<(), String>;
-    fn update_package(&self, rx: Receiver<bool>, package_name: String) -> Result<(), String>;
-    fn uninstall_package(&self, rx: Receiver<bool>, package_name: String) -> Result<(), String>;
-}
-
-// enum PackageManager {
-//     Homebrew,
-//     Pacman,
-//     Apt,
-//     Winget,
-// }
-//
-// type Alias = &'static str;
-//
-// impl From<PackageManager> for Alias {
-//     fn from(value: PackageManager) -> Self {
-//         match value {
-//             PackageManager::Homebrew => "brew",
-//             PackageManager::Pacman => "pacman",
-//             PackageManager::Apt => "apt",
-//             PackageManager::Winget => "winget",
-//         }
-//     }
-// }
-//
-//
+    ) -> Result<Vec<Package>, Error>;
+    /// Installed packages for which a newer version is available, with
+    /// both the installed and the latest version filled in
+    fn outdated_packages(&self, rx: Receiver<bool>) -> Result<Vec<Package>, Error>;
+    /// Names of the installed packages the user asked for, as opposed to
+    /// the ones installed as dependencies, as the backend records them.
+    /// Backends keeping no such record leave this unsupported, since
+    /// guessing would make manifests uninstall packages still in use
+    fn requested_packages(&self, _rx: Receiver<bool>) -> Result<Vec<String>, Error> {
+        Err(Error::unsupported(format!(
+            "telling the packages installed on request with {}",
+            self.alias()
+        )))
+    }
+    fn package_manager_config(&self, rx: Receiver<bool>) -> Result<String, Error>;
+    /// Configuration of the backend split into fields, the raw form of
+    /// which is `package_manager_config`
+    fn system_info(&self, rx: Receiver<bool>) -> Result<SystemInfo, Error>;
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error>;
+    /// Packages `package_name` depends on, each along with its own
+    /// dependencies as far as the backend resolves them
+    fn dependencies(
+        &self,
+        rx: Receiver<bool>,
+        package_name: String,
+    ) -> Result<Vec<Dependency>, Error>;
+    /// Installed packages that directly depend on `package_name`
+    fn dependents(&self, rx: Receiver<bool>, package_name: String) -> Result<Vec<String>, Error>;
+    /// Every installed package along with the installed packages it
+    /// directly depends on
+    fn dependency_graph(&self, rx: Receiver<bool>) -> Result<DependencyGraph, Error>;
+    /// Long running operations forward each line of output to `tx` while
+    /// they run, and return the whole output once they are done
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error>;
+    /// Run every health check, splitting what they report into individual
+    /// warnings
+    fn health_warnings(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error>;
+    /// Run the health check named `check` on its own
+    fn run_health_check(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        check: String,
+    ) -> Result<Vec<HealthWarning>, Error>;
+    /// Dry run of `clean`, listing what it would remove without removing
+    /// anything
+    fn clean_preview(&self, rx: Receiver<bool>) -> Result<CleanupPreview, Error>;
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error>;
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error>;
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error>;
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error>;
+    /// Hold the package at its installed version, so that it is left
+    /// alone when everything else is upgraded
+    fn pin_package(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error>;
+    fn unpin_package(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error>;
+
+    // taps, services and Brewfiles only exist in Homebrew, every other
+    // backend keeps these defaults
+
+    fn taps(&self, _rx: Receiver<bool>) -> Result<Vec<Tap>, Error> {
+        Err(Error::unsupported(format!(
+            "listing taps with {}",
+            self.alias()
+        )))
+    }
+    /// Add the tap `tap`, given as its name optionally followed by the git
+    /// remote to clone it from
+    fn add_tap(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _tap: String,
+    ) -> Result<String, Error> {
+        Err(Error::unsupported(format!(
+            "adding taps with {}",
+            self.alias()
+        )))
+    }
+    fn remove_tap(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _tap: String,
+    ) -> Result<String, Error> {
+        Err(Error::unsupported(format!(
+            "removing taps with {}",
+            self.alias()
+        )))
+    }
+    fn services(&self, _rx: Receiver<bool>) -> Result<Vec<Service>, Error> {
+        Err(Error::unsupported(format!(
+            "listing services with {}",
+            self.alias()
+        )))
+    }
+    fn control_service(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _action: ServiceAction,
+        _service: String,
+    ) -> Result<String, Error> {
+        Err(Error::unsupported(format!(
+            "controlling services with {}",
+            self.alias()
+        )))
+    }
+    /// Remove the services that are no longer needed, such as the services
+    /// of packages that have been uninstalled
+    fn cleanup_services(&self, _rx: Receiver<bool>, _tx: Sender<String>) -> Result<String, Error> {
+        Err(Error::unsupported(format!(
+            "cleaning up services with {}",
+            self.alias()
+        )))
+    }
+    /// Write the taps, packages and running services to the Brewfile at
+    /// `path`, replacing it if it exists
+    fn export_brewfile(&self, _rx: Receiver<bool>, _path: String) -> Result<String, Error> {
+        Err(Error::unsupported(format!(
+            "exporting a Brewfile with {}",
+            self.alias()
+        )))
+    }
+    /// What importing the Brewfile at `path` would change
+    fn brewfile_plan(&self, _rx: Receiver<bool>, _path: String) -> Result<BrewfilePlan, Error> {
+        Err(Error::unsupported(format!(
+            "importing a Brewfile with {}",
+            self.alias()
+        )))
+    }
+    /// Install everything the Brewfile at `path` lists, and uninstall
+    /// everything it leaves out
+    fn import_brewfile(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _path: String,
+    ) -> Result<String, Error> {
+        Err(Error::unsupported(format!(
+            "importing a Brewfile with {}",
+            self.alias()
+        )))
+    }
+}
+
+/// Backends are keyed by their lowercase name in manifests and lockfiles
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Apt,
+    Dnf,
+    Pacman,
+    Homebrew,
+    Nix,
+    Flatpak,
+    Cargo,
+    Pipx,
+    Npm,
+}
+
+impl Backend {
+    /// Every supported backend, in the order of preference used when
+    /// picking the default backend: system package managers come first
+    /// and language ecosystem package managers last
+    pub const ALL: [Backend; 9] = [
+        Self::Apt,
+        Self::Dnf,
+        Self::Pacman,
+        Self::Homebrew,
+        Self::Nix,
+        Self::Flatpak,
+        Self::Cargo,
+        Self::Pipx,
+        Self::Npm,
+    ];
+
+    /// Name of the executable used to detect the backend
+    pub fn alias(&self) -> &'static str {
+        match self {
+            Self::Apt => "apt-get",
+            Self::Dnf => "dnf",
+            Self::Pacman => "pacman",
+            Self::Homebrew => "brew",
+            Self::Nix => "nix",
+            Self::Flatpak => "flatpak",
+            Self::Cargo => "cargo",
+            Self::Pipx => "pipx",
+            Self::Npm => "npm",
+        }
+    }
+
+    /// Lowercase name of the backend, as used in manifests
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Apt => "apt",
+            Self::Dnf => "dnf",
+            Self::Pacman => "pacman",
+            Self::Homebrew => "homebrew",
+            Self::Nix => "nix",
+            Self::Flatpak => "flatpak",
+            Self::Cargo => "cargo",
+            Self::Pipx => "pipx",
+            Self::Npm => "npm",
+        }
+    }
+
+    /// `package_name` prefixed with the backend, such as `homebrew:wget`,
+    /// so that an aggregated operation on it runs with this backend
+    pub fn qualify(&self, package_name: &str) -> String {
+        format!("{}:{package_name}", self.key())
+    }
+
+    /// Backend and package name of a name prefixed by `qualify`
+    pub fn unqualify(name: &str) -> Option<(Backend, &str)> {
+        let (key, package_name) = name.split_once(':')?;
+        let backend = Self::ALL.into_iter().find(|backend| backend.key() == key)?;
+        Some((backend, package_name))
+    }
+
+    /// Check whether the executable of the backend is on PATH
+    pub fn is_available(&self) -> bool {
+        find_executable(self.alias()).is_some()
+    }
+
+    pub fn package_manager(&self) -> Arc<dyn PackageManager> {
+        match self {
+            Self::Apt => Arc::new(apt::Apt),
+            Self::Dnf => Arc::new(dnf::Dnf),
+            Self::Pacman => Arc::new(pacman::Pacman),
+            Self::Homebrew => Arc::new(homebrew::Homebrew),
+            Self::Nix => Arc::new(nix::Nix),
+            Self::Flatpak => Arc::new(flatpak::Flatpak),
+            Self::Cargo => Arc::new(cargo::Cargo),
+            Self::Pipx => Arc::new(pipx::Pipx),
+            Self::Npm => Arc::new(npm::Npm),
+        }
+    }
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Apt => write!(f, "APT"),
+            Self::Dnf => write!(f, "DNF"),
+            Self::Pacman => write!(f, "pacman"),
+            Self::Homebrew => write!(f, "Homebrew"),
+            Self::Nix => write!(f, "Nix"),
+            Self::Flatpak => write!(f, "Flatpak"),
+            Self::Cargo => write!(f, "Cargo"),
+            Self::Pipx => write!(f, "pipx"),
+            Self::Npm => write!(f, "npm"),
+        }
+    }
+}
+
+/// find the first executable with the given name in the directories on PATH
+pub fn find_executable(alias: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|directory| directory.join(alias))
+        .find(|path| path.is_file())
+}
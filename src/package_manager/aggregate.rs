@@ -0,0 +1,788 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        mpsc::{Receiver, RecvTimeoutError, Sender, channel},
+    },
+    thread,
+    time::Duration,
+};
+
+use tracing::warn;
+
+use super::{
+    Backend, BrewfilePlan, CleanupPreview, Dependency, HealthWarning, Package, PackageLocality,
+    PackageManager, Service, ServiceAction, SystemInfo, Tap,
+    error::Error,
+    graph::{DependencyGraph, GraphEdge, GraphNode},
+    rank_packages,
+};
+
+/// How often the fan-out checks whether it has been cancelled while
+/// waiting on the backends
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// `PackageManager` that fans every query out to several backends in
+/// parallel and merges their results. Package operations are routed to the
+/// backend the package name is qualified with, or to the only backend that
+/// offered the package in the latest search.
+pub struct Aggregate {
+    backends: Vec<(Backend, Arc<dyn PackageManager>)>,
+    sources: Mutex<HashMap<String, Vec<Backend>>>,
+    /// Backends that listed their taps in the latest query, along with the
+    /// names of those taps
+    taps: Mutex<Vec<(Backend, Vec<String>)>>,
+    /// Backends that listed their services in the latest query, along with
+    /// the names of those services
+    services: Mutex<Vec<(Backend, Vec<String>)>>,
+}
+
+impl Aggregate {
+    pub fn new(backends: Vec<(Backend, Arc<dyn PackageManager>)>) -> Self {
+        Self {
+            backends,
+            sources: Mutex::new(HashMap::default()),
+            taps: Mutex::new(Vec::default()),
+            services: Mutex::new(Vec::default()),
+        }
+    }
+
+    /// Run `f` against every backend on its own thread, forwarding a
+    /// cancellation received on `rx` to all of them. Returns None when
+    /// cancelled, otherwise the result of each backend in registration
+    /// order.
+    fn fan_out<R, F>(&self, rx: Receiver<bool>, f: F) -> Option<Vec<(Backend, R)>>
+    where
+        R: Send + 'static,
+        F: Fn(&dyn PackageManager, Receiver<bool>) -> R + Send + Sync + 'static,
+    {
+        Self::fan_out_to(&self.backends, rx, f)
+    }
+
+    /// `fan_out` restricted to `backends`
+    fn fan_out_to<R, F>(
+        backends: &[(Backend, Arc<dyn PackageManager>)],
+        rx: Receiver<bool>,
+        f: F,
+    ) -> Option<Vec<(Backend, R)>>
+    where
+        R: Send + 'static,
+        F: Fn(&dyn PackageManager, Receiver<bool>) -> R + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let (tx_result, rx_result) = channel::<(usize, R)>();
+        let mut tx_cancel: Vec<Sender<bool>> = Vec::new();
+        for (index, (_backend, package_manager)) in backends.iter().enumerate() {
+            let (tx, rx_backend) = channel::<bool>();
+            tx_cancel.push(tx);
+            let package_manager = package_manager.clone();
+            let tx_result = tx_result.clone();
+            let f = f.clone();
+            thread::spawn(move || {
+                let result = f(package_manager.as_ref(), rx_backend);
+                let _ = tx_result.send((index, result));
+            });
+        }
+        drop(tx_result);
+
+        let mut results: Vec<Option<R>> = backends.iter().map(|_| None).collect();
+        let mut pending = backends.len();
+        while pending > 0 {
+            if let Ok(true) = rx.try_recv() {
+                for tx in tx_cancel {
+                    let _ = tx.send(true);
+                }
+                return None;
+            }
+            match rx_result.recv_timeout(POLL_INTERVAL) {
+                Ok((index, result)) => {
+                    results[index] = Some(result);
+                    pending -= 1;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        Some(
+            backends
+                .iter()
+                .zip(results)
+                .filter_map(|((backend, _), result)| Some((*backend, result?)))
+                .collect(),
+        )
+    }
+
+    /// Backends that offered `package_name` in the latest search
+    fn sources_of(&self, package_name: &str) -> Vec<Backend> {
+        self.sources
+            .lock()
+            .unwrap()
+            .get(package_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Backend to run an operation on `package_name` against, along with
+    /// the unqualified name to pass it. Nothing is guessed: a name that is
+    /// not qualified has to have been offered by exactly one backend
+    fn route(&self, package_name: &str) -> Result<(Arc<dyn PackageManager>, String), Error> {
+        let (backend, package_name) = match Backend::unqualify(package_name) {
+            Some((backend, package_name)) => (backend, package_name.to_string()),
+            None => match self.sources_of(package_name)[..] {
+                [backend] => (backend, package_name.to_string()),
+                ref sources => {
+                    return Err(Error::UnknownSource {
+                        package: package_name.to_string(),
+                        sources: sources.to_vec(),
+                    });
+                }
+            },
+        };
+        let package_manager = self
+            .backends
+            .iter()
+            .find(|(b, _)| *b == backend)
+            .map(|(_, package_manager)| package_manager.clone())
+            .ok_or_else(|| Error::BinaryMissing {
+                binary: backend.alias().to_string(),
+            })?;
+        Ok((package_manager, package_name))
+    }
+
+    /// Backend to run tap or service operations against, preferring the
+    /// backend that listed `name` in the latest query
+    fn listed_by(
+        &self,
+        listings: &Mutex<Vec<(Backend, Vec<String>)>>,
+        name: &str,
+    ) -> Option<Arc<dyn PackageManager>> {
+        let listings = listings.lock().unwrap();
+        let backend = listings
+            .iter()
+            .find(|(_, names)| names.iter().any(|listed| listed == name))
+            .or(listings.first())
+            .map(|(backend, _)| *backend)?;
+        drop(listings);
+
+        self.backends
+            .iter()
+            .find(|(b, _)| *b == backend)
+            .map(|(_, package_manager)| package_manager.clone())
+    }
+
+    /// Result of the backend supporting an operation only one backend
+    /// supports, such as anything involving a Brewfile
+    fn supported<R>(results: Option<Vec<(Backend, Result<R, Error>)>>) -> Result<R, Error> {
+        let Some(results) = results else {
+            return Err(Error::Cancelled);
+        };
+        let mut unsupported = None;
+        for (_backend, result) in results {
+            match result {
+                Err(e @ Error::Unsupported { .. }) => unsupported = unsupported.or(Some(e)),
+                result => return result,
+            }
+        }
+        Err(unsupported.unwrap_or_else(|| Error::unsupported("running without a package manager")))
+    }
+
+    /// Concatenate the text reported by each backend under a heading
+    fn join_reports(reports: Vec<(Backend, Result<String, Error>)>) -> String {
+        reports
+            .into_iter()
+            .map(|(backend, report)| match report {
+                Ok(report) => format!("{backend}\n{report}\n"),
+                Err(e) => format!("{backend}\n{e}\n"),
+            })
+            .collect()
+    }
+}
+
+impl PackageManager for Aggregate {
+    fn alias(&self) -> &'static str {
+        "all"
+    }
+
+    fn filter_packages(
+        &self,
+        rx: Receiver<bool>,
+        source: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        let query = pattern.clone();
+        let Some(results) = self.fan_out(rx, move |package_manager, rx| {
+            package_manager.filter_packages(rx, source, query.clone())
+        }) else {
+            return Err(Error::Cancelled);
+        };
+
+        // merge packages offered by several backends into a single result,
+        // ranked across backends below
+        let mut packages: Vec<Package> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let mut errors: Vec<Error> = Vec::new();
+        for (backend, result) in results {
+            match result {
+                Ok(found) => {
+                    for package in found {
+                        match positions.get(&package.name) {
+                            Some(&position) => packages[position].merge(package),
+                            None => {
+                                positions.insert(package.name.clone(), packages.len());
+                                packages.push(package);
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("{backend} failed to filter packages: {e}");
+                    errors.push(e);
+                }
+            }
+        }
+        // only fail when no backend had anything to offer, reporting the
+        // first failure since the rest have been logged
+        if packages.is_empty() && !errors.is_empty() {
+            return Err(errors.swap_remove(0));
+        }
+
+        *self.sources.lock().unwrap() = packages
+            .iter()
+            .map(|package| (package.name.clone(), package.sources.clone()))
+            .collect();
+        Ok(rank_packages(packages, &pattern))
+    }
+
+    fn outdated_packages(&self, rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        let Some(results) = self.fan_out(rx, |package_manager, rx| {
+            package_manager.outdated_packages(rx)
+        }) else {
+            return Err(Error::Cancelled);
+        };
+
+        // outdated packages are kept apart per backend since each one has
+        // to be upgraded by the backend that reported it
+        let mut packages: Vec<Package> = Vec::new();
+        let mut errors: Vec<Error> = Vec::new();
+        for (backend, result) in results {
+            match result {
+                Ok(outdated) => packages.extend(outdated),
+                Err(Error::Unsupported { .. }) => {}
+                Err(e) => {
+                    warn!("{backend} failed to list outdated packages: {e}");
+                    errors.push(e);
+                }
+            }
+        }
+        if packages.is_empty() && !errors.is_empty() {
+            return Err(errors.swap_remove(0));
+        }
+
+        // a name several backends report as outdated is offered by each
+        // of them, so an upgrade of it has to name its backend
+        let mut sources = self.sources.lock().unwrap();
+        for package in &packages {
+            let offered = sources.entry(package.name.clone()).or_default();
+            for backend in &package.sources {
+                if !offered.contains(backend) {
+                    offered.push(*backend);
+                }
+            }
+        }
+        drop(sources);
+        Ok(packages)
+    }
+
+    fn package_manager_config(&self, rx: Receiver<bool>) -> Result<String, Error> {
+        match self.fan_out(rx, |package_manager, rx| {
+            package_manager.package_manager_config(rx)
+        }) {
+            Some(reports) => Ok(Self::join_reports(reports)),
+            None => Err(Error::Cancelled),
+        }
+    }
+
+    fn system_info(&self, rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        Self::supported(self.fan_out(rx, |package_manager, rx| package_manager.system_info(rx)))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        // a package offered by several backends is described by each of them
+        let sources = self.sources_of(&package_name);
+        if Backend::unqualify(&package_name).is_some() || sources.len() < 2 {
+            let (package_manager, package_name) = self.route(&package_name)?;
+            return package_manager.package_info(rx, package_name);
+        }
+        let backends: Vec<(Backend, Arc<dyn PackageManager>)> = self
+            .backends
+            .iter()
+            .filter(|(backend, _)| sources.contains(backend))
+            .cloned()
+            .collect();
+        match Self::fan_out_to(&backends, rx, move |package_manager, rx| {
+            package_manager.package_info(rx, package_name.clone())
+        }) {
+            Some(reports) => Ok(Self::join_reports(reports)),
+            None => Err(Error::Cancelled),
+        }
+    }
+
+    fn dependencies(
+        &self,
+        rx: Receiver<bool>,
+        package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        let (package_manager, package_name) = self.route(&package_name)?;
+        package_manager.dependencies(rx, package_name)
+    }
+
+    fn dependents(&self, rx: Receiver<bool>, package_name: String) -> Result<Vec<String>, Error> {
+        let (package_manager, package_name) = self.route(&package_name)?;
+        package_manager.dependents(rx, package_name)
+    }
+
+    fn dependency_graph(&self, rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        let Some(graphs) = self.fan_out(rx, |package_manager, rx| {
+            package_manager.dependency_graph(rx)
+        }) else {
+            return Err(Error::Cancelled);
+        };
+
+        // packages are qualified with their backend, since the same name
+        // may stand for different packages in different backends
+        let mut merged = DependencyGraph::default();
+        let mut errors: Vec<Error> = Vec::new();
+        for (backend, graph) in graphs {
+            match graph {
+                Ok(graph) => {
+                    merged
+                        .nodes
+                        .extend(graph.nodes.into_iter().map(|node| GraphNode {
+                            name: format!("{backend}:{}", node.name),
+                            ..node
+                        }));
+                    merged
+                        .edges
+                        .extend(graph.edges.into_iter().map(|edge| GraphEdge {
+                            from: format!("{backend}:{}", edge.from),
+                            to: format!("{backend}:{}", edge.to),
+                            ..edge
+                        }));
+                }
+                Err(Error::Unsupported { .. }) => {}
+                Err(e) => {
+                    warn!("{backend} failed to query the dependency graph: {e}");
+                    errors.push(e);
+                }
+            }
+        }
+        if merged.nodes.is_empty() && !errors.is_empty() {
+            return Err(errors.swap_remove(0));
+        }
+        Ok(merged)
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        match self.fan_out(rx, move |package_manager, rx| {
+            package_manager.check_health(rx, tx.clone())
+        }) {
+            Some(reports) => Ok(Self::join_reports(reports)),
+            None => Err(Error::Cancelled),
+        }
+    }
+
+    fn health_warnings(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        let Some(results) = self.fan_out(rx, move |package_manager, rx| {
+            package_manager.health_warnings(rx, tx.clone())
+        }) else {
+            return Err(Error::Cancelled);
+        };
+
+        let mut warnings: Vec<HealthWarning> = Vec::new();
+        let mut supported = false;
+        let mut errors: Vec<Error> = Vec::new();
+        for (backend, result) in results {
+            match result {
+                Ok(found) => {
+                    supported = true;
+                    warnings.extend(found);
+                }
+                Err(Error::Unsupported { .. }) => {}
+                Err(e) => {
+                    warn!("{backend} failed to check health: {e}");
+                    errors.push(e);
+                }
+            }
+        }
+        if !supported {
+            return Err(if errors.is_empty() {
+                Error::unsupported("splitting the health checks into warnings")
+            } else {
+                errors.swap_remove(0)
+            });
+        }
+        Ok(warnings)
+    }
+
+    fn run_health_check(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Self::supported(self.fan_out(rx, move |package_manager, rx| {
+            package_manager.run_health_check(rx, tx.clone(), check.clone())
+        }))
+    }
+
+    fn clean_preview(&self, rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        let Some(previews) =
+            self.fan_out(rx, |package_manager, rx| package_manager.clean_preview(rx))
+        else {
+            return Err(Error::Cancelled);
+        };
+
+        // entries are qualified with the backend that would remove them,
+        // backends without a dry run are left out of the preview
+        let mut merged = CleanupPreview::default();
+        let mut freed: Vec<String> = Vec::new();
+        let mut errors: Vec<Error> = Vec::new();
+        for (backend, preview) in previews {
+            match preview {
+                Ok(preview) => {
+                    merged.files.extend(
+                        preview
+                            .files
+                            .into_iter()
+                            .map(|file| format!("{backend}: {file}")),
+                    );
+                    merged.packages.extend(
+                        preview
+                            .packages
+                            .into_iter()
+                            .map(|package| format!("{backend}: {package}")),
+                    );
+                    if let Some(size) = preview.freed {
+                        freed.push(format!("{size} ({backend})"));
+                    }
+                }
+                Err(Error::Unsupported { .. }) => {}
+                Err(e) => {
+                    warn!("{backend} failed to preview the cleanup: {e}");
+                    errors.push(e);
+                }
+            }
+        }
+        if merged.is_empty() && !errors.is_empty() {
+            return Err(errors.swap_remove(0));
+        }
+        if !freed.is_empty() {
+            merged.freed = Some(freed.join(", "));
+        }
+        Ok(merged)
+    }
+
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        match self.fan_out(rx, move |package_manager, rx| {
+            package_manager.clean(rx, tx.clone())
+        }) {
+            Some(reports) => Ok(Self::join_reports(reports)),
+            None => Err(Error::Cancelled),
+        }
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let (package_manager, package_name) = self.route(&package_name)?;
+        package_manager.install_package(rx, tx, package_name)
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let (package_manager, package_name) = self.route(&package_name)?;
+        package_manager.update_package(rx, tx, package_name)
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let (package_manager, package_name) = self.route(&package_name)?;
+        package_manager.uninstall_package(rx, tx, package_name)
+    }
+
+    fn pin_package(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let (package_manager, package_name) = self.route(&package_name)?;
+        package_manager.pin_package(rx, package_name)
+    }
+
+    fn unpin_package(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let (package_manager, package_name) = self.route(&package_name)?;
+        package_manager.unpin_package(rx, package_name)
+    }
+
+    fn taps(&self, rx: Receiver<bool>) -> Result<Vec<Tap>, Error> {
+        let Some(results) = self.fan_out(rx, |package_manager, rx| package_manager.taps(rx)) else {
+            return Err(Error::Cancelled);
+        };
+
+        let mut taps: Vec<Tap> = Vec::new();
+        let mut sources: Vec<(Backend, Vec<String>)> = Vec::new();
+        let mut errors: Vec<Error> = Vec::new();
+        for (backend, result) in results {
+            match result {
+                Ok(found) => {
+                    sources.push((backend, found.iter().map(|tap| tap.name.clone()).collect()));
+                    taps.extend(found);
+                }
+                Err(Error::Unsupported { .. }) => {}
+                Err(e) => {
+                    warn!("{backend} failed to list taps: {e}");
+                    errors.push(e);
+                }
+            }
+        }
+        if sources.is_empty() && !errors.is_empty() {
+            return Err(errors.swap_remove(0));
+        }
+
+        *self.taps.lock().unwrap() = sources;
+        Ok(taps)
+    }
+
+    fn add_tap(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        tap: String,
+    ) -> Result<String, Error> {
+        self.listed_by(&self.taps, &tap)
+            .ok_or_else(|| Error::unsupported("adding taps with these backends"))?
+            .add_tap(rx, tx, tap)
+    }
+
+    fn remove_tap(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        tap: String,
+    ) -> Result<String, Error> {
+        self.listed_by(&self.taps, &tap)
+            .ok_or_else(|| Error::unsupported("removing taps with these backends"))?
+            .remove_tap(rx, tx, tap)
+    }
+
+    fn services(&self, rx: Receiver<bool>) -> Result<Vec<Service>, Error> {
+        let Some(results) = self.fan_out(rx, |package_manager, rx| package_manager.services(rx))
+        else {
+            return Err(Error::Cancelled);
+        };
+
+        let mut services: Vec<Service> = Vec::new();
+        let mut sources: Vec<(Backend, Vec<String>)> = Vec::new();
+        let mut errors: Vec<Error> = Vec::new();
+        for (backend, result) in results {
+            match result {
+                Ok(found) => {
+                    sources.push((
+                        backend,
+                        found.iter().map(|service| service.name.clone()).collect(),
+                    ));
+                    services.extend(found);
+                }
+                Err(Error::Unsupported { .. }) => {}
+                Err(e) => {
+                    warn!("{backend} failed to list services: {e}");
+                    errors.push(e);
+                }
+            }
+        }
+        if sources.is_empty() && !errors.is_empty() {
+            return Err(errors.swap_remove(0));
+        }
+
+        *self.services.lock().unwrap() = sources;
+        Ok(services)
+    }
+
+    fn control_service(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        action: ServiceAction,
+        service: String,
+    ) -> Result<String, Error> {
+        self.listed_by(&self.services, &service)
+            .ok_or_else(|| Error::unsupported("controlling services with these backends"))?
+            .control_service(rx, tx, action, service)
+    }
+
+    fn cleanup_services(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        self.listed_by(&self.services, "")
+            .ok_or_else(|| Error::unsupported("cleaning up services with these backends"))?
+            .cleanup_services(rx, tx)
+    }
+
+    fn export_brewfile(&self, rx: Receiver<bool>, path: String) -> Result<String, Error> {
+        Self::supported(self.fan_out(rx, move |package_manager, rx| {
+            package_manager.export_brewfile(rx, path.clone())
+        }))
+    }
+
+    fn brewfile_plan(&self, rx: Receiver<bool>, path: String) -> Result<BrewfilePlan, Error> {
+        Self::supported(self.fan_out(rx, move |package_manager, rx| {
+            package_manager.brewfile_plan(rx, path.clone())
+        }))
+    }
+
+    fn import_brewfile(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        path: String,
+    ) -> Result<String, Error> {
+        Self::supported(self.fan_out(rx, move |package_manager, rx| {
+            package_manager.import_brewfile(rx, tx.clone(), path.clone())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    fn aggregate() -> Aggregate {
+        Aggregate::new(vec![
+            (Backend::Apt, Backend::Apt.package_manager()),
+            (Backend::Homebrew, Backend::Homebrew.package_manager()),
+        ])
+    }
+
+    #[test]
+    fn route_refuses_a_package_no_search_offered() {
+        let result = aggregate().route("wget").map(|(_, name)| name);
+        assert_eq!(
+            result,
+            Err(Error::UnknownSource {
+                package: "wget".to_string(),
+                sources: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn route_refuses_a_package_offered_by_several_backends() {
+        let aggregate = aggregate();
+        aggregate
+            .sources
+            .lock()
+            .unwrap()
+            .insert("wget".to_string(), vec![Backend::Apt, Backend::Homebrew]);
+        let result = aggregate.route("wget").map(|(_, name)| name);
+        assert_eq!(
+            result,
+            Err(Error::UnknownSource {
+                package: "wget".to_string(),
+                sources: vec![Backend::Apt, Backend::Homebrew],
+            })
+        );
+    }
+
+    #[test]
+    fn route_follows_the_qualified_backend() {
+        let aggregate = aggregate();
+        aggregate
+            .sources
+            .lock()
+            .unwrap()
+            .insert("wget".to_string(), vec![Backend::Apt, Backend::Homebrew]);
+        let (package_manager, name) = aggregate.route("homebrew:wget").unwrap();
+        assert_eq!(package_manager.alias(), Backend::Homebrew.alias());
+        assert_eq!(name, "wget");
+
+        let result = aggregate.route("npm:wget").map(|(_, name)| name);
+        assert_eq!(
+            result,
+            Err(Error::BinaryMissing {
+                binary: "npm".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn filter_ranks_across_backends() {
+        let path = StubPath::isolated();
+        path.stub("apt-mark", "exit 0")
+            .stub(
+                "dpkg-query",
+                "printf 'rgb\\tinstalled\\t1.0\\tcolours\\n'\n\
+                 printf 'ripgrep-all\\tinstalled\\t0.10\\tsearch anything\\n'",
+            )
+            .stub("cargo", "echo 'ripgrep v14.1.0:'\necho '    rg'");
+        let aggregate = Aggregate::new(vec![
+            (Backend::Apt, Backend::Apt.package_manager()),
+            (Backend::Cargo, Backend::Cargo.package_manager()),
+        ]);
+        let (_tx, rx) = mpsc::channel();
+        let packages = aggregate
+            .filter_packages(rx, PackageLocality::Local, "rg".to_string())
+            .unwrap();
+        let names = packages
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, ["rgb", "ripgrep", "ripgrep-all"]);
+        assert_eq!(packages[1].sources, [Backend::Cargo]);
+        assert_eq!(packages[1].matched, [0, 3]);
+    }
+
+    #[test]
+    fn outdated_sources_merge_across_backends() {
+        let path = StubPath::isolated();
+        path.stub("pacman", "echo 'typescript 5.4.5-1 -> 5.5.2-1'")
+            .stub(
+                "npm",
+                "echo '{\"typescript\":{\"current\":\"5.4.5\",\"latest\":\"5.5.2\"}}'\nexit 1",
+            );
+        let aggregate = Aggregate::new(vec![
+            (Backend::Pacman, Backend::Pacman.package_manager()),
+            (Backend::Npm, Backend::Npm.package_manager()),
+        ]);
+        let (_tx, rx) = mpsc::channel();
+        let packages = aggregate.outdated_packages(rx).unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(
+            aggregate.sources_of("typescript"),
+            [Backend::Pacman, Backend::Npm]
+        );
+        let result = aggregate.route("typescript").map(|(_, name)| name);
+        assert_eq!(
+            result,
+            Err(Error::UnknownSource {
+                package: "typescript".to_string(),
+                sources: vec![Backend::Pacman, Backend::Npm],
+            })
+        );
+        let (package_manager, name) = aggregate.route("npm:typescript").unwrap();
+        assert_eq!(package_manager.alias(), Backend::Npm.alias());
+        assert_eq!(name, "typescript");
+    }
+}
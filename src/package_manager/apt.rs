@@ -0,0 +1,751 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::mpsc::{Receiver, Sender},
+};
+
+use super::{
+    Backend, CleanupPreview, CommandResult, Dependency, HealthWarning, Package, PackageLocality,
+    PackageManager, SpawnCommandResult, SystemInfo, command,
+    error::{Error, checked},
+    fuzz_packages,
+    graph::{DependencyGraph, DependencyKind, GraphEdge, GraphNode},
+    handle_spawned_command, rank_packages, spawn_command, stream_spawned_command,
+};
+
+pub struct Apt;
+
+const APT_GET_ALIAS: &str = "apt-get";
+const APT_CACHE_ALIAS: &str = "apt-cache";
+const APT_CONFIG_ALIAS: &str = "apt-config";
+const APT_MARK_ALIAS: &str = "apt-mark";
+const DPKG_QUERY_ALIAS: &str = "dpkg-query";
+
+impl Apt {
+    /// Install specified packages, or upgrade them when used
+    /// together with `InstallOption::OnlyUpgrade`
+    fn apt_get_install<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["install".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+
+        spawn_command(APT_GET_ALIAS, args)
+    }
+
+    /// Remove specified packages
+    fn apt_get_remove<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = RemoveOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["remove".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: RemoveOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+
+        spawn_command(APT_GET_ALIAS, args)
+    }
+
+    /// Upgrade every installed package that has a newer version
+    /// available, without installing or removing other packages
+    fn apt_get_upgrade<I>(options: Option<I>) -> CommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+    {
+        let mut args = vec!["upgrade".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        command(APT_GET_ALIAS, args)
+    }
+
+    /// Update the package cache and diagnose broken dependencies
+    fn apt_get_check() -> SpawnCommandResult {
+        spawn_command(APT_GET_ALIAS, ["check"])
+    }
+
+    /// Clear out the local repository of retrieved package
+    /// files that can no longer be downloaded
+    fn apt_get_autoclean<I>(options: Option<I>) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = CleanOption>,
+    {
+        let mut args = vec!["autoclean".to_string()];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: CleanOption| option.into()));
+        }
+        spawn_command(APT_GET_ALIAS, args)
+    }
+
+    /// Search the package cache for the specified pattern
+    fn apt_cache_search<I>(options: Option<I>, pattern: String) -> CommandResult
+    where
+        I: IntoIterator<Item = SearchOption>,
+    {
+        let mut args = vec!["search".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: SearchOption| option.into()),
+            );
+        }
+        args.push(pattern);
+        command(APT_CACHE_ALIAS, args)
+    }
+
+    /// Display the package records of the specified packages
+    fn apt_cache_show<J>(package_list: J) -> SpawnCommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["show".to_string()];
+        args.extend(package_list);
+        spawn_command(APT_CACHE_ALIAS, args)
+    }
+
+    /// Show the packages the specified package depends on, or the
+    /// packages depending on it when `reverse` is set
+    fn apt_cache_depends(package_name: String, reverse: bool) -> CommandResult {
+        let args = match reverse {
+            true => vec![
+                "rdepends".to_string(),
+                "--installed".to_string(),
+                package_name,
+            ],
+            false => vec!["depends".to_string(), package_name],
+        };
+        command(APT_CACHE_ALIAS, args)
+    }
+
+    /// Dump the apt configuration space
+    fn apt_config_dump() -> CommandResult {
+        command(APT_CONFIG_ALIAS, ["dump"])
+    }
+
+    /// Change or show whether packages are held at their
+    /// installed version
+    fn apt_mark<J>(subcommand: MarkSubcommand, package_list: J) -> CommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec![String::from(subcommand)];
+        args.extend(package_list);
+        command(APT_MARK_ALIAS, args)
+    }
+
+    /// List the packages marked as installed manually, as opposed to the
+    /// ones installed automatically as dependencies
+    fn apt_mark_showmanual() -> SpawnCommandResult {
+        spawn_command(APT_MARK_ALIAS, [String::from(MarkSubcommand::ShowManual)])
+    }
+
+    /// List the packages known to dpkg along with their installation
+    /// status and the packages they depend on
+    fn dpkg_query_depends() -> CommandResult {
+        command(
+            DPKG_QUERY_ALIAS,
+            [
+                "--show",
+                "--showformat=${Package}\t${db:Status-Status}\t${Version}\t${Pre-Depends}, ${Depends}\n",
+            ],
+        )
+    }
+
+    /// List the packages known to dpkg along with their
+    /// installation status
+    fn dpkg_query_show() -> CommandResult {
+        command(
+            DPKG_QUERY_ALIAS,
+            [
+                "--show",
+                "--showformat=${Package}\t${db:Status-Status}\t${Version}\t${binary:Summary}\n",
+            ],
+        )
+    }
+
+    /// Packages that are fully installed, as opposed to removed packages
+    /// whose configuration files are still around
+    fn installed_packages() -> Result<Vec<Package>, Error> {
+        let output = checked(
+            APT_MARK_ALIAS,
+            Self::apt_mark(MarkSubcommand::ShowHold, Vec::new()),
+        )?;
+        let held = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect::<HashSet<String>>();
+        let output = checked(DPKG_QUERY_ALIAS, Self::dpkg_query_show())?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(
+                |line| match line.splitn(4, '\t').collect::<Vec<&str>>()[..] {
+                    [name, "installed", version, summary] => Some(Package {
+                        version: Some(version.to_string()),
+                        installed_version: Some(version.to_string()),
+                        description: Some(summary.to_string()),
+                        pinned: held.contains(name),
+                        ..Package::new(name.to_string(), Backend::Apt)
+                    }),
+                    _ => None,
+                },
+            )
+            .collect())
+    }
+}
+
+impl PackageManager for Apt {
+    fn alias(&self) -> &'static str {
+        APT_GET_ALIAS
+    }
+
+    fn filter_packages(
+        &self,
+        _rx: Receiver<bool>,
+        package_locality: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        match package_locality {
+            PackageLocality::Local => Ok(fuzz_packages(Self::installed_packages()?, pattern)),
+            PackageLocality::Remote => {
+                let installed = Self::installed_packages()?
+                    .into_iter()
+                    .map(|package| (package.name.clone(), package))
+                    .collect::<HashMap<String, Package>>();
+                let output = checked(
+                    APT_CACHE_ALIAS,
+                    Self::apt_cache_search(Some([SearchOption::NamesOnly]), pattern.clone()),
+                )?;
+                // each result is formatted as `<package> - <short description>`
+                let packages = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| line.split_once(" - "))
+                    .map(|(package, description)| {
+                        let installed = installed.get(package.trim());
+                        Package {
+                            installed_version: installed
+                                .and_then(|package| package.installed_version.clone()),
+                            pinned: installed.is_some_and(|package| package.pinned),
+                            description: Some(description.trim().to_string()),
+                            ..Package::new(package.trim().to_string(), Backend::Apt)
+                        }
+                    })
+                    .collect::<Vec<Package>>();
+                Ok(rank_packages(packages, &pattern))
+            }
+        }
+    }
+
+    fn outdated_packages(&self, _rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        let output = checked(
+            APT_GET_ALIAS,
+            Self::apt_get_upgrade(Some([InstallOption::DryRun])),
+        )?;
+        // each upgrade is simulated as
+        // `Inst <package> [<installed version>] (<version> <release> [<arch>])`
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (package, rest) = line.strip_prefix("Inst ")?.split_once(" [")?;
+                let (installed_version, rest) = rest.split_once("] (")?;
+                let version = rest.split_whitespace().next()?;
+                Some(Package {
+                    version: Some(version.to_string()),
+                    installed_version: Some(installed_version.to_string()),
+                    ..Package::new(package.to_string(), Backend::Apt)
+                })
+            })
+            .collect())
+    }
+
+    fn requested_packages(&self, rx: Receiver<bool>) -> Result<Vec<String>, Error> {
+        let child = Self::apt_mark_showmanual().map_err(|e| Error::from_io(APT_MARK_ALIAS, e))?;
+        let out = handle_spawned_command(rx, child)?
+            .success(APT_MARK_ALIAS)?
+            .out
+            .unwrap_or_default();
+        Ok(out.lines().map(str::to_string).collect())
+    }
+
+    fn package_manager_config(&self, _rx: Receiver<bool>) -> Result<String, Error> {
+        let output = checked(APT_CONFIG_ALIAS, Self::apt_config_dump())?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn system_info(&self, _rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        Err(Error::unsupported("reading the system information of apt"))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let child =
+            Self::apt_cache_show([package_name]).map_err(|e| Error::from_io(APT_CACHE_ALIAS, e))?;
+        Ok(handle_spawned_command(rx, child)?
+            .success(APT_CACHE_ALIAS)?
+            .out
+            .unwrap_or_default())
+    }
+
+    fn dependencies(
+        &self,
+        _rx: Receiver<bool>,
+        package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        let output = checked(
+            APT_CACHE_ALIAS,
+            Self::apt_cache_depends(package_name, false),
+        )?;
+        // dependencies are listed as `  Depends: <package>`, virtual packages
+        // as `<package>` and alternatives prefixed with `|`
+        let mut seen = HashSet::new();
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim().trim_start_matches('|');
+                line.strip_prefix("Depends:")
+                    .or_else(|| line.strip_prefix("PreDepends:"))
+            })
+            .map(|name| name.trim().trim_matches(['<', '>']).to_string())
+            .filter(|name| seen.insert(name.clone()))
+            .map(Dependency::new)
+            .collect())
+    }
+
+    fn dependents(&self, _rx: Receiver<bool>, package_name: String) -> Result<Vec<String>, Error> {
+        let output = checked(APT_CACHE_ALIAS, Self::apt_cache_depends(package_name, true))?;
+        // dependents are listed below a `Reverse Depends:` heading
+        let mut seen = HashSet::new();
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .skip_while(|line| !line.starts_with("Reverse Depends:"))
+            .skip(1)
+            .map(|line| line.trim().trim_start_matches('|').to_string())
+            .filter(|name| !name.is_empty() && seen.insert(name.clone()))
+            .collect())
+    }
+
+    fn dependency_graph(&self, _rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        let output = checked(DPKG_QUERY_ALIAS, Self::dpkg_query_depends())?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let installed = stdout
+            .lines()
+            .filter_map(
+                |line| match line.splitn(4, '\t').collect::<Vec<&str>>()[..] {
+                    [name, "installed", version, depends] => Some((name, version, depends)),
+                    _ => None,
+                },
+            )
+            .collect::<Vec<(&str, &str, &str)>>();
+        let names = installed
+            .iter()
+            .map(|(name, _version, _depends)| *name)
+            .collect::<HashSet<&str>>();
+
+        let mut graph = DependencyGraph::default();
+        for (name, version, depends) in installed {
+            // dependencies are listed as `<package>[:<arch>] [(<constraint>)]`,
+            // separated by commas, with alternatives separated by `|` of which
+            // the first installed one is followed
+            let edges = depends
+                .split(',')
+                .filter_map(|alternatives| {
+                    alternatives
+                        .split('|')
+                        .filter_map(|dependency| dependency.split_whitespace().next())
+                        .map(|dependency| dependency.split(':').next().unwrap_or(dependency))
+                        .find(|dependency| names.contains(dependency))
+                })
+                .collect::<HashSet<&str>>();
+            graph.edges.extend(edges.into_iter().map(|to| GraphEdge {
+                from: name.to_string(),
+                to: to.to_string(),
+                kind: DependencyKind::Runtime,
+            }));
+            graph.nodes.push(GraphNode {
+                name: name.to_string(),
+                version: Some(version.to_string()),
+            });
+        }
+        Ok(graph)
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::apt_get_check().map_err(|e| Error::from_io(APT_GET_ALIAS, e))?;
+        // apt-get reports progress on stdout and problems on stderr
+        stream_spawned_command(rx, tx, child)?.report(APT_GET_ALIAS)
+    }
+
+    fn health_warnings(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "splitting the health check of apt into warnings",
+        ))
+    }
+
+    fn run_health_check(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "running individual health checks with apt",
+        ))
+    }
+
+    fn clean_preview(&self, rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        let child = Self::apt_get_autoclean(Some([CleanOption::DryRun]))
+            .map_err(|e| Error::from_io(APT_GET_ALIAS, e))?;
+        let out = handle_spawned_command(rx, child)?
+            .success(APT_GET_ALIAS)?
+            .out
+            .unwrap_or_default();
+        // each archive is reported as `Del <package> <version> [<size>]`
+        Ok(CleanupPreview {
+            files: out
+                .lines()
+                .filter_map(|line| line.strip_prefix("Del "))
+                .map(|archive| archive.trim().to_string())
+                .collect(),
+            ..CleanupPreview::default()
+        })
+    }
+
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::apt_get_autoclean::<Vec<CleanOption>>(None)
+            .map_err(|e| Error::from_io(APT_GET_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(APT_GET_ALIAS)?
+            .combined())
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::apt_get_install(Some([InstallOption::AssumeYes]), [package_name])
+            .map_err(|e| Error::from_io(APT_GET_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(APT_GET_ALIAS)?
+            .combined())
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::apt_get_install(
+            Some([InstallOption::OnlyUpgrade, InstallOption::AssumeYes]),
+            [package_name],
+        )
+        .map_err(|e| Error::from_io(APT_GET_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(APT_GET_ALIAS)?
+            .combined())
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::apt_get_remove(Some([RemoveOption::AssumeYes]), [package_name])
+            .map_err(|e| Error::from_io(APT_GET_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(APT_GET_ALIAS)?
+            .combined())
+    }
+
+    fn pin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let output = checked(
+            APT_MARK_ALIAS,
+            Self::apt_mark(MarkSubcommand::Hold, [package_name]),
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn unpin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let output = checked(
+            APT_MARK_ALIAS,
+            Self::apt_mark(MarkSubcommand::Unhold, [package_name]),
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+pub enum CleanOption {
+    DryRun,
+    Quiet,
+}
+
+impl From<CleanOption> for String {
+    fn from(value: CleanOption) -> Self {
+        match value {
+            CleanOption::DryRun => "--simulate".to_string(),
+            CleanOption::Quiet => "--quiet".to_string(),
+        }
+    }
+}
+
+pub enum InstallOption {
+    AssumeYes,
+    DryRun,
+    DownloadOnly,
+    FixBroken,
+    FixMissing,
+    NoInstallRecommends,
+    InstallSuggests,
+    Reinstall,
+    OnlyUpgrade,
+    NoUpgrade,
+    AllowDowngrades,
+    AutoRemove,
+    Quiet,
+}
+
+impl From<InstallOption> for String {
+    fn from(value: InstallOption) -> Self {
+        match value {
+            InstallOption::AssumeYes => "--assume-yes".to_string(),
+            InstallOption::DryRun => "--simulate".to_string(),
+            InstallOption::DownloadOnly => "--download-only".to_string(),
+            InstallOption::FixBroken => "--fix-broken".to_string(),
+            InstallOption::FixMissing => "--fix-missing".to_string(),
+            InstallOption::NoInstallRecommends => "--no-install-recommends".to_string(),
+            InstallOption::InstallSuggests => "--install-suggests".to_string(),
+            InstallOption::Reinstall => "--reinstall".to_string(),
+            InstallOption::OnlyUpgrade => "--only-upgrade".to_string(),
+            InstallOption::NoUpgrade => "--no-upgrade".to_string(),
+            InstallOption::AllowDowngrades => "--allow-downgrades".to_string(),
+            InstallOption::AutoRemove => "--auto-remove".to_string(),
+            InstallOption::Quiet => "--quiet".to_string(),
+        }
+    }
+}
+
+pub enum MarkSubcommand {
+    Hold,
+    Unhold,
+    ShowHold,
+    ShowManual,
+}
+
+impl From<MarkSubcommand> for String {
+    fn from(value: MarkSubcommand) -> Self {
+        match value {
+            MarkSubcommand::Hold => "hold".to_string(),
+            MarkSubcommand::Unhold => "unhold".to_string(),
+            MarkSubcommand::ShowHold => "showhold".to_string(),
+            MarkSubcommand::ShowManual => "showmanual".to_string(),
+        }
+    }
+}
+
+pub enum RemoveOption {
+    AssumeYes,
+    DryRun,
+    Purge,
+    AutoRemove,
+    Quiet,
+}
+
+impl From<RemoveOption> for String {
+    fn from(value: RemoveOption) -> Self {
+        match value {
+            RemoveOption::AssumeYes => "--assume-yes".to_string(),
+            RemoveOption::DryRun => "--simulate".to_string(),
+            RemoveOption::Purge => "--purge".to_string(),
+            RemoveOption::AutoRemove => "--auto-remove".to_string(),
+            RemoveOption::Quiet => "--quiet".to_string(),
+        }
+    }
+}
+
+pub enum SearchOption {
+    NamesOnly,
+    Full,
+}
+
+impl From<SearchOption> for String {
+    fn from(value: SearchOption) -> Self {
+        match value {
+            SearchOption::NamesOnly => "--names-only".to_string(),
+            SearchOption::Full => "--full".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    #[test]
+    fn missing_binary() {
+        let _path = StubPath::isolated();
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Apt.package_info(rx, "curl".to_string()),
+            Err(Error::BinaryMissing {
+                binary: APT_CACHE_ALIAS.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn non_zero_exit_of_spawned_command() {
+        let path = StubPath::isolated();
+        path.stub("apt-cache", "echo 'E: No packages found' >&2\nexit 100");
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Apt.package_info(rx, "curl".to_string()),
+            Err(Error::NonZeroExit {
+                command: APT_CACHE_ALIAS.to_string(),
+                code: Some(100),
+                stderr: "E: No packages found".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn non_zero_exit_of_blocking_command() {
+        let path = StubPath::isolated();
+        path.stub("apt-mark", "exit 0")
+            .stub("dpkg-query", "echo 'dpkg-query: error' >&2\nexit 2");
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Apt.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::NonZeroExit {
+                command: DPKG_QUERY_ALIAS.to_string(),
+                code: Some(2),
+                stderr: "dpkg-query: error".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn failed_health_check() {
+        let path = StubPath::isolated();
+        path.stub(
+            "apt-get",
+            "echo 'Reading package lists...'\necho 'E: Unmet dependencies' >&2\nexit 100",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let (tx_lines, _rx_lines) = mpsc::channel();
+        assert_eq!(
+            Apt.check_health(rx, tx_lines),
+            Err(Error::NonZeroExit {
+                command: APT_GET_ALIAS.to_string(),
+                code: Some(100),
+                stderr: "Reading package lists...\nE: Unmet dependencies".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn streamed_and_cancelled_clean() {
+        let path = StubPath::prepended();
+        path.stub("apt-get", "echo 'Reading package lists...'\nexec sleep 10");
+        let (tx, rx) = mpsc::channel();
+        let (tx_lines, rx_lines) = mpsc::channel();
+        let clean = std::thread::spawn(move || Apt.clean(rx, tx_lines));
+        assert_eq!(rx_lines.recv().as_deref(), Ok("Reading package lists..."));
+        tx.send(true).unwrap();
+        assert_eq!(clean.join().unwrap(), Err(Error::Cancelled));
+    }
+
+    #[test]
+    fn cancelled() {
+        let path = StubPath::prepended();
+        path.stub("apt-cache", "exec sleep 10");
+        let (tx, rx) = mpsc::channel();
+        tx.send(true).unwrap();
+        assert_eq!(
+            Apt.package_info(rx, "curl".to_string()),
+            Err(Error::Cancelled)
+        );
+    }
+
+    #[test]
+    fn installed_packages() {
+        let path = StubPath::isolated();
+        path.stub("apt-mark", "echo curl").stub(
+            "dpkg-query",
+            "printf 'curl\\tinstalled\\t7.88.1\\tcommand line tool\\n'\n\
+             printf 'vim\\tinstalled\\t9.0\\teditor\\n'\n\
+             printf 'old\\tconfig-files\\t1.0\\tremoved\\n'",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Apt
+            .filter_packages(rx, PackageLocality::Local, "curl".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "curl");
+        assert_eq!(packages[0].installed_version.as_deref(), Some("7.88.1"));
+        assert!(packages[0].pinned);
+    }
+
+    #[test]
+    fn remote_results_ranked() {
+        let path = StubPath::isolated();
+        path.stub("apt-mark", "exit 0")
+            .stub("dpkg-query", "exit 0")
+            .stub(
+                "apt-cache",
+                "echo 'ripgrep-all - search anything'\necho 'rg - ripgrep'",
+            );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Apt
+            .filter_packages(rx, PackageLocality::Remote, "rg".to_string())
+            .unwrap();
+        assert_eq!(packages[0].name, "rg");
+        assert_eq!(packages[0].matched, [0, 1]);
+        assert_eq!(packages[1].name, "ripgrep-all");
+        assert_eq!(packages[1].matched, [0, 3]);
+    }
+
+    #[test]
+    fn requested_packages() {
+        let path = StubPath::isolated();
+        path.stub(
+            "apt-mark",
+            "[ \"$1\" = showmanual ] || exit 1\necho curl\necho vim",
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Apt.requested_packages(rx),
+            Ok(vec!["curl".to_string(), "vim".to_string()])
+        );
+    }
+}
@@ -0,0 +1,467 @@
+use std::{
+    collections::HashMap,
+    env,
+    path::PathBuf,
+    sync::mpsc::{Receiver, Sender},
+};
+
+use super::{
+    Backend, CleanupPreview, CommandResult, Dependency, HealthWarning, Package, PackageLocality,
+    PackageManager, SpawnCommandResult, SystemInfo, command,
+    error::{Error, checked},
+    fuzz_packages,
+    graph::DependencyGraph,
+    handle_spawned_command, rank_packages, spawn_command, stream_spawned_command,
+};
+
+pub struct Cargo;
+
+/// Entry of `cargo install --list`
+struct InstalledCrate {
+    name: String,
+    version: String,
+    binaries: Vec<String>,
+}
+
+const CARGO_ALIAS: &str = "cargo";
+
+/// Maximum number of crates returned by a registry search
+const SEARCH_LIMIT: &str = "100";
+
+impl Cargo {
+    /// Build and install a Rust binary
+    fn cargo_install<I, J>(options: Option<I>, crate_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["install".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        args.extend(crate_list);
+
+        spawn_command(CARGO_ALIAS, args)
+    }
+
+    /// List installed crates along with their binaries
+    fn cargo_install_list() -> SpawnCommandResult {
+        spawn_command(CARGO_ALIAS, ["install", "--list"])
+    }
+
+    /// Remove a Rust binary
+    fn cargo_uninstall<J>(crate_list: J) -> SpawnCommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["uninstall".to_string()];
+        args.extend(crate_list);
+
+        spawn_command(CARGO_ALIAS, args)
+    }
+
+    /// Search the registry for crates matching the specified pattern
+    fn cargo_search(pattern: String) -> CommandResult {
+        command(
+            CARGO_ALIAS,
+            [
+                "search".to_string(),
+                "--limit".to_string(),
+                SEARCH_LIMIT.to_string(),
+                pattern,
+            ],
+        )
+    }
+
+    /// Display information about a crate from the registry
+    fn cargo_info(crate_name: String) -> SpawnCommandResult {
+        spawn_command(CARGO_ALIAS, ["info".to_string(), crate_name])
+    }
+
+    /// Display the cargo version along with its build and host
+    /// information
+    fn cargo_version() -> CommandResult {
+        command(CARGO_ALIAS, ["--version", "--verbose"])
+    }
+
+    /// Installed crates along with the binaries they provide, parsed
+    /// from `<crate> v<version>:` lines each followed by indented
+    /// binary names
+    fn installed_crates(rx: Receiver<bool>) -> Result<Vec<InstalledCrate>, Error> {
+        let child = Self::cargo_install_list().map_err(|e| Error::from_io(CARGO_ALIAS, e))?;
+        let output = handle_spawned_command(rx, child)?.success(CARGO_ALIAS)?;
+        let mut crates: Vec<InstalledCrate> = Vec::new();
+        for line in output.out.unwrap_or_default().lines() {
+            if line.starts_with(char::is_whitespace) {
+                if let Some(installed_crate) = crates.last_mut() {
+                    installed_crate.binaries.push(line.trim().to_string());
+                }
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            if let Some(name) = fields.next() {
+                // crates installed from a path or git repository have the
+                // source in parentheses after the version
+                let version = fields.next().unwrap_or_default();
+                crates.push(InstalledCrate {
+                    name: name.to_string(),
+                    version: version
+                        .trim_start_matches('v')
+                        .trim_end_matches(':')
+                        .to_string(),
+                    binaries: Vec::new(),
+                });
+            }
+        }
+        Ok(crates)
+    }
+
+    /// Directory that `cargo install` places binaries in
+    fn bin_directory() -> Option<PathBuf> {
+        match env::var_os("CARGO_HOME") {
+            Some(cargo_home) => Some(PathBuf::from(cargo_home).join("bin")),
+            None => env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo").join("bin")),
+        }
+    }
+}
+
+impl PackageManager for Cargo {
+    fn alias(&self) -> &'static str {
+        CARGO_ALIAS
+    }
+
+    fn filter_packages(
+        &self,
+        rx: Receiver<bool>,
+        package_locality: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        match package_locality {
+            PackageLocality::Local => {
+                let installed_crates = Self::installed_crates(rx)?
+                    .into_iter()
+                    .map(|installed_crate| Package {
+                        version: Some(installed_crate.version.clone()),
+                        installed_version: Some(installed_crate.version),
+                        ..Package::new(installed_crate.name, Backend::Cargo)
+                    })
+                    .collect::<Vec<Package>>();
+                Ok(fuzz_packages(installed_crates, pattern))
+            }
+            PackageLocality::Remote => {
+                let installed_versions = Self::installed_crates(rx)?
+                    .into_iter()
+                    .map(|installed_crate| (installed_crate.name, installed_crate.version))
+                    .collect::<HashMap<String, String>>();
+                let output = checked(CARGO_ALIAS, Self::cargo_search(pattern.clone()))?;
+                // each result is formatted as `<crate> = "<version>"    # <description>`
+                let packages = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| line.split_once(" = "))
+                    .map(|(crate_name, rest)| {
+                        let (version, description) = match rest.split_once('#') {
+                            Some((version, description)) => {
+                                (version, Some(description.trim().to_string()))
+                            }
+                            None => (rest, None),
+                        };
+                        Package {
+                            version: Some(version.trim().trim_matches('"').to_string()),
+                            installed_version: installed_versions.get(crate_name).cloned(),
+                            description,
+                            repository: Some("crates.io".to_string()),
+                            ..Package::new(crate_name.to_string(), Backend::Cargo)
+                        }
+                    })
+                    .collect::<Vec<Package>>();
+                Ok(rank_packages(packages, &pattern))
+            }
+        }
+    }
+
+    fn outdated_packages(&self, _rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        Err(Error::unsupported("listing outdated crates with cargo"))
+    }
+
+    fn package_manager_config(&self, _rx: Receiver<bool>) -> Result<String, Error> {
+        let output = checked(CARGO_ALIAS, Self::cargo_version())?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn system_info(&self, _rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        Err(Error::unsupported(
+            "reading the system information of cargo",
+        ))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let child = Self::cargo_info(package_name).map_err(|e| Error::from_io(CARGO_ALIAS, e))?;
+        Ok(handle_spawned_command(rx, child)?
+            .success(CARGO_ALIAS)?
+            .out
+            .unwrap_or_default())
+    }
+
+    fn dependencies(
+        &self,
+        _rx: Receiver<bool>,
+        _package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        Err(Error::unsupported(
+            "listing the dependencies of installed crates",
+        ))
+    }
+
+    fn dependents(&self, _rx: Receiver<bool>, _package_name: String) -> Result<Vec<String>, Error> {
+        Err(Error::unsupported(
+            "listing the dependents of installed crates",
+        ))
+    }
+
+    fn dependency_graph(&self, _rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        Err(Error::unsupported(
+            "exporting the dependency graph of installed crates",
+        ))
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, _tx: Sender<String>) -> Result<String, Error> {
+        // cargo has no health check of its own, so verify that every binary
+        // it claims to have installed is still present
+        let Some(bin_directory) = Self::bin_directory() else {
+            return Err(Error::Io {
+                command: CARGO_ALIAS.to_string(),
+                reason: "neither CARGO_HOME nor HOME is set".to_string(),
+            });
+        };
+        let mut report = String::new();
+        for installed_crate in Self::installed_crates(rx)? {
+            for binary in installed_crate.binaries {
+                if !bin_directory.join(&binary).exists() {
+                    report.push_str(&format!(
+                        "{}: binary {binary} is missing from {}\n",
+                        installed_crate.name,
+                        bin_directory.display()
+                    ));
+                }
+            }
+        }
+        if report.is_empty() {
+            report.push_str("all installed binaries are present\n");
+        }
+        Ok(report)
+    }
+
+    fn health_warnings(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "splitting the health check of cargo into warnings",
+        ))
+    }
+
+    fn run_health_check(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "running individual health checks with cargo",
+        ))
+    }
+
+    fn clean_preview(&self, _rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        Err(Error::unsupported("cleaning installed crates with cargo"))
+    }
+
+    fn clean(&self, _rx: Receiver<bool>, _tx: Sender<String>) -> Result<String, Error> {
+        Err(Error::unsupported("cleaning installed crates with cargo"))
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::cargo_install(Some([InstallOption::Locked]), [package_name])
+            .map_err(|e| Error::from_io(CARGO_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(CARGO_ALIAS)?
+            .combined())
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        // installing an already installed crate upgrades it when a newer
+        // version has been published
+        let child = Self::cargo_install(Some([InstallOption::Locked]), [package_name])
+            .map_err(|e| Error::from_io(CARGO_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(CARGO_ALIAS)?
+            .combined())
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child =
+            Self::cargo_uninstall([package_name]).map_err(|e| Error::from_io(CARGO_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(CARGO_ALIAS)?
+            .combined())
+    }
+
+    fn pin_package(&self, _rx: Receiver<bool>, _package_name: String) -> Result<String, Error> {
+        Err(Error::unsupported("pinning crates with cargo"))
+    }
+
+    fn unpin_package(&self, _rx: Receiver<bool>, _package_name: String) -> Result<String, Error> {
+        Err(Error::unsupported("unpinning crates with cargo"))
+    }
+}
+
+pub enum InstallOption {
+    Force,
+    Locked,
+    Offline,
+    AllFeatures,
+    NoDefaultFeatures,
+    Debug,
+    Quiet,
+}
+
+impl From<InstallOption> for String {
+    fn from(value: InstallOption) -> Self {
+        match value {
+            InstallOption::Force => "--force".to_string(),
+            InstallOption::Locked => "--locked".to_string(),
+            InstallOption::Offline => "--offline".to_string(),
+            InstallOption::AllFeatures => "--all-features".to_string(),
+            InstallOption::NoDefaultFeatures => "--no-default-features".to_string(),
+            InstallOption::Debug => "--debug".to_string(),
+            InstallOption::Quiet => "--quiet".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    /// cargo listing ripgrep from crates.io and a crate built from a path
+    const INSTALL_LIST: &str = "echo 'ripgrep v14.1.0:'\n\
+        echo '    rg'\n\
+        echo 'wherehouse v0.1.0 (/home/user/wherehouse):'\n\
+        echo '    wherehouse'";
+
+    #[test]
+    fn missing_binary() {
+        let _path = StubPath::isolated();
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Cargo.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::BinaryMissing {
+                binary: CARGO_ALIAS.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn installed_crates() {
+        let path = StubPath::isolated();
+        path.stub(
+            "cargo",
+            &format!("[ \"$1 $2\" = 'install --list' ] || exit 1\n{INSTALL_LIST}"),
+        );
+        let (_tx, rx) = mpsc::channel();
+        let crates = Cargo::installed_crates(rx).unwrap();
+        assert_eq!(crates.len(), 2);
+        assert_eq!(crates[0].name, "ripgrep");
+        assert_eq!(crates[0].version, "14.1.0");
+        assert_eq!(crates[0].binaries, ["rg"]);
+        assert_eq!(crates[1].name, "wherehouse");
+        assert_eq!(crates[1].version, "0.1.0");
+        assert_eq!(crates[1].binaries, ["wherehouse"]);
+    }
+
+    #[test]
+    fn remote_crates() {
+        let path = StubPath::isolated();
+        path.stub(
+            "cargo",
+            &format!(
+                "case \"$1\" in\n\
+                 install) {INSTALL_LIST} ;;\n\
+                 search) echo 'ripgrep_all = \"0.10.6\"    # rga: ripgrep, but also search in PDFs'\n\
+                 echo 'ripgrep = \"14.1.1\"    # ripgrep is a line-oriented search tool'\n\
+                 echo '... and 42 crates more (use --limit N to see more)' ;;\n\
+                 *) exit 1 ;;\n\
+                 esac"
+            ),
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Cargo
+            .filter_packages(rx, PackageLocality::Remote, "ripgrep".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "ripgrep");
+        assert_eq!(packages[0].version.as_deref(), Some("14.1.1"));
+        assert_eq!(packages[0].installed_version.as_deref(), Some("14.1.0"));
+        assert_eq!(
+            packages[0].description.as_deref(),
+            Some("ripgrep is a line-oriented search tool")
+        );
+        assert_eq!(packages[1].name, "ripgrep_all");
+        assert_eq!(packages[1].installed_version, None);
+    }
+
+    #[test]
+    fn failed_search() {
+        let path = StubPath::isolated();
+        path.stub(
+            "cargo",
+            &format!(
+                "[ \"$1\" = search ] || {{\n{INSTALL_LIST}\nexit 0\n}}\n\
+                 echo 'error: failed to retrieve search results from the registry' >&2\nexit 101"
+            ),
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Cargo.filter_packages(rx, PackageLocality::Remote, "ripgrep".to_string()),
+            Err(Error::NonZeroExit {
+                command: CARGO_ALIAS.to_string(),
+                code: Some(101),
+                stderr: "error: failed to retrieve search results from the registry".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn outdated_crates_unsupported() {
+        let (_tx, rx) = mpsc::channel();
+        assert!(matches!(
+            Cargo.outdated_packages(rx),
+            Err(Error::Unsupported { .. })
+        ));
+    }
+}
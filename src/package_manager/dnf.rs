@@ -0,0 +1,876 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::mpsc::{Receiver, Sender},
+};
+
+use super::{
+    Backend, Cancellation, CleanupPreview, CommandResult, Dependency, HealthWarning, Package,
+    PackageLocality, PackageManager, SpawnCommandResult, SystemInfo, command,
+    error::{Error, checked, checked_search},
+    fuzz_packages,
+    graph::DependencyGraph,
+    handle_spawned_command, rank_packages, spawn_command, stream_spawned_command,
+};
+
+pub struct Dnf;
+
+const DNF_ALIAS: &str = "dnf";
+const RPM_ALIAS: &str = "rpm";
+
+/// Name of a package given as `<name>-<epoch>:<version>-<release>.<arch>`
+fn nevra_name(nevra: &str) -> Option<String> {
+    let mut fields = nevra.rsplitn(3, '-');
+    let (_release, _version) = (fields.next()?, fields.next()?);
+    fields.next().map(|name| name.to_string())
+}
+
+impl Dnf {
+    /// Install specified packages and their dependencies
+    fn dnf_install<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["install".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+
+        spawn_command(DNF_ALIAS, args)
+    }
+
+    /// Upgrade specified packages to the latest available version
+    fn dnf_upgrade<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["upgrade".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+
+        spawn_command(DNF_ALIAS, args)
+    }
+
+    /// List packages in the specified state
+    fn dnf_list<I>(options: Option<I>) -> CommandResult
+    where
+        I: IntoIterator<Item = ListOption>,
+    {
+        let mut args = vec!["list".to_string()];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: ListOption| option.into()));
+        }
+        command(DNF_ALIAS, args)
+    }
+
+    /// Remove specified packages along with the packages
+    /// depending on them
+    fn dnf_remove<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = RemoveOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["remove".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: RemoveOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+
+        spawn_command(DNF_ALIAS, args)
+    }
+
+    /// Search package metadata for the specified pattern
+    fn dnf_search<I>(options: Option<I>, pattern: String) -> CommandResult
+    where
+        I: IntoIterator<Item = SearchOption>,
+    {
+        let mut args = vec!["search".to_string(), "--quiet".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: SearchOption| option.into()),
+            );
+        }
+        args.push(pattern);
+        command(DNF_ALIAS, args)
+    }
+
+    /// Display detailed information about the specified packages
+    fn dnf_info<J>(package_list: J) -> SpawnCommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["info".to_string(), "--quiet".to_string()];
+        args.extend(package_list);
+        spawn_command(DNF_ALIAS, args)
+    }
+
+    /// Check the local package database for problems such as
+    /// missing dependencies, duplicates and obsoleted packages
+    fn dnf_check() -> SpawnCommandResult {
+        spawn_command(DNF_ALIAS, ["check"])
+    }
+
+    /// Remove cached data
+    fn dnf_clean(subcommand: CleanSubcommand) -> SpawnCommandResult {
+        spawn_command(DNF_ALIAS, ["clean", subcommand.into()])
+    }
+
+    /// Print the repository and main configuration
+    fn dnf_config_dump() -> CommandResult {
+        command(DNF_ALIAS, ["config-manager", "--dump"])
+    }
+
+    /// List the names, versions and summaries of all installed packages
+    fn rpm_query_all() -> CommandResult {
+        command(
+            RPM_ALIAS,
+            [
+                "--query",
+                "--all",
+                "--queryformat",
+                "%{NAME}\t%{VERSION}-%{RELEASE}\t%{SUMMARY}\n",
+            ],
+        )
+    }
+
+    /// Add, remove or list the version locks of the versionlock plugin
+    fn dnf_versionlock<J>(subcommand: VersionlockSubcommand, package_list: J) -> CommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["versionlock".to_string(), subcommand.into()];
+        args.extend(package_list);
+        command(DNF_ALIAS, args)
+    }
+
+    /// Query the repositories, or the installed packages when used together
+    /// with `RepoqueryOption::Installed`, printing one package per line
+    fn dnf_repoquery<I, J>(options: Option<I>, package_list: J) -> CommandResult
+    where
+        I: IntoIterator<Item = RepoqueryOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["repoquery".to_string(), "--quiet".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: RepoqueryOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+        command(DNF_ALIAS, args)
+    }
+
+    /// List the names of the packages installed by the user, as opposed
+    /// to the ones installed as dependencies
+    fn dnf_repoquery_userinstalled() -> SpawnCommandResult {
+        spawn_command(
+            DNF_ALIAS,
+            [
+                "repoquery".to_string(),
+                "--quiet".to_string(),
+                RepoqueryOption::UserInstalled.into(),
+                "--queryformat".to_string(),
+                "%{name}\n".to_string(),
+            ],
+        )
+    }
+
+    /// Names of the packages locked at their installed version, locks are
+    /// listed as `<name>-<epoch>:<version>-<release>.*`. Nothing is locked
+    /// when the versionlock plugin is not installed
+    fn locked_packages() -> HashSet<String> {
+        let Ok(output) = checked(
+            DNF_ALIAS,
+            Self::dnf_versionlock(VersionlockSubcommand::List, Vec::new()),
+        ) else {
+            return HashSet::new();
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| nevra_name(line.trim()))
+            .collect()
+    }
+
+    /// Installed packages along with their versions and summaries
+    fn installed_packages() -> Result<Vec<Package>, Error> {
+        let locked = Self::locked_packages();
+        let output = checked(RPM_ALIAS, Self::rpm_query_all())?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(
+                |line| match line.splitn(3, '\t').collect::<Vec<&str>>()[..] {
+                    [name, version, summary] => Some(Package {
+                        version: Some(version.to_string()),
+                        installed_version: Some(version.to_string()),
+                        description: Some(summary.to_string()),
+                        pinned: locked.contains(name),
+                        ..Package::new(name.to_string(), Backend::Dnf)
+                    }),
+                    _ => None,
+                },
+            )
+            .collect())
+    }
+
+    /// Verify all installed packages against the rpm database
+    fn rpm_verify_all<I>(options: Option<I>) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = VerifyOption>,
+    {
+        let mut args = vec!["--verify".to_string(), "--all".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: VerifyOption| option.into()),
+            );
+        }
+        spawn_command(RPM_ALIAS, args)
+    }
+}
+
+impl PackageManager for Dnf {
+    fn alias(&self) -> &'static str {
+        DNF_ALIAS
+    }
+
+    fn filter_packages(
+        &self,
+        _rx: Receiver<bool>,
+        package_locality: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        match package_locality {
+            PackageLocality::Local => Ok(fuzz_packages(Self::installed_packages()?, pattern)),
+            PackageLocality::Remote => {
+                let installed = Self::installed_packages()?
+                    .into_iter()
+                    .map(|package| (package.name.clone(), package))
+                    .collect::<HashMap<String, Package>>();
+                let output = checked_search(
+                    DNF_ALIAS,
+                    Self::dnf_search::<Vec<SearchOption>>(None, pattern.clone()),
+                    &["No matches found"],
+                )?;
+                // each result is formatted as `<package>.<arch> : <summary>`, grouped
+                // under `=== ... ===` or `Matched fields: ...` headings
+                let mut seen = HashSet::new();
+                let packages = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter(|line| !line.starts_with('=') && !line.starts_with("Matched"))
+                    .filter_map(|line| {
+                        let (package, summary) =
+                            line.split_once(" : ").or_else(|| line.split_once('\t'))?;
+                        let (package, _arch) = package.trim().rsplit_once('.')?;
+                        Some((package.to_string(), summary.trim().to_string()))
+                    })
+                    .filter(|(package, _summary)| seen.insert(package.clone()))
+                    .map(|(package, summary)| {
+                        let installed = installed.get(&package);
+                        Package {
+                            installed_version: installed
+                                .and_then(|package| package.installed_version.clone()),
+                            pinned: installed.is_some_and(|package| package.pinned),
+                            description: Some(summary),
+                            ..Package::new(package, Backend::Dnf)
+                        }
+                    })
+                    .collect::<Vec<Package>>();
+                Ok(rank_packages(packages, &pattern))
+            }
+        }
+    }
+
+    fn outdated_packages(&self, _rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        let installed_versions = Self::installed_packages()?
+            .into_iter()
+            .map(|package| (package.name, package.installed_version))
+            .collect::<HashMap<String, Option<String>>>();
+        let output = checked(DNF_ALIAS, Self::dnf_list(Some([ListOption::Upgrades])))?;
+        // each upgrade is listed as `<package>.<arch> <version> <repository>`
+        // below a heading, the same package may be listed once per arch
+        let mut seen = HashSet::new();
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let [package, version, repository] =
+                    line.split_whitespace().collect::<Vec<&str>>()[..]
+                else {
+                    return None;
+                };
+                let (name, _arch) = package.rsplit_once('.')?;
+                if !seen.insert(name.to_string()) {
+                    return None;
+                }
+                Some(Package {
+                    version: Some(version.to_string()),
+                    installed_version: installed_versions.get(name).cloned().flatten(),
+                    repository: Some(repository.to_string()),
+                    ..Package::new(name.to_string(), Backend::Dnf)
+                })
+            })
+            .collect())
+    }
+
+    fn requested_packages(&self, rx: Receiver<bool>) -> Result<Vec<String>, Error> {
+        let child =
+            Self::dnf_repoquery_userinstalled().map_err(|e| Error::from_io(DNF_ALIAS, e))?;
+        let out = handle_spawned_command(rx, child)?
+            .success(DNF_ALIAS)?
+            .out
+            .unwrap_or_default();
+        Ok(out
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn package_manager_config(&self, _rx: Receiver<bool>) -> Result<String, Error> {
+        let output = checked(DNF_ALIAS, Self::dnf_config_dump())?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn system_info(&self, _rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        Err(Error::unsupported("reading the system information of dnf"))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let child = Self::dnf_info([package_name]).map_err(|e| Error::from_io(DNF_ALIAS, e))?;
+        Ok(handle_spawned_command(rx, child)?
+            .success(DNF_ALIAS)?
+            .out
+            .unwrap_or_default())
+    }
+
+    fn dependencies(
+        &self,
+        _rx: Receiver<bool>,
+        package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        let output = checked(
+            DNF_ALIAS,
+            Self::dnf_repoquery(
+                Some([RepoqueryOption::Requires, RepoqueryOption::Resolve]),
+                [package_name],
+            ),
+        )?;
+        let mut seen = HashSet::new();
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| nevra_name(line.trim()))
+            .filter(|name| seen.insert(name.clone()))
+            .map(Dependency::new)
+            .collect())
+    }
+
+    fn dependents(&self, _rx: Receiver<bool>, package_name: String) -> Result<Vec<String>, Error> {
+        let output = checked(
+            DNF_ALIAS,
+            Self::dnf_repoquery(
+                Some([
+                    RepoqueryOption::Installed,
+                    RepoqueryOption::WhatRequires(package_name),
+                ]),
+                Vec::new(),
+            ),
+        )?;
+        let mut seen = HashSet::new();
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| nevra_name(line.trim()))
+            .filter(|name| seen.insert(name.clone()))
+            .collect())
+    }
+
+    fn dependency_graph(&self, _rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        Err(Error::unsupported(
+            "exporting the dependency graph with dnf",
+        ))
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        // the package database is checked before every installed file is
+        // verified, both commands exit unsuccessfully when they find problems
+        let cancellation = Cancellation::forward(rx);
+        let Some(rx_check) = cancellation.receiver() else {
+            return Err(Error::Cancelled);
+        };
+        let child = Self::dnf_check().map_err(|e| Error::from_io(DNF_ALIAS, e))?;
+        let database_check =
+            stream_spawned_command(rx_check, tx.clone(), child)?.report(DNF_ALIAS)?;
+        let Some(rx_verify) = cancellation.receiver() else {
+            return Err(Error::Cancelled);
+        };
+        let child = Self::rpm_verify_all::<Vec<VerifyOption>>(None)
+            .map_err(|e| Error::from_io(RPM_ALIAS, e))?;
+        let verification = stream_spawned_command(rx_verify, tx, child)?.report(RPM_ALIAS)?;
+        Ok(format!(
+            "dnf check\n{database_check}\nrpm --verify --all\n{verification}"
+        ))
+    }
+
+    fn health_warnings(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "splitting the health check of dnf into warnings",
+        ))
+    }
+
+    fn run_health_check(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "running individual health checks with dnf",
+        ))
+    }
+
+    fn clean_preview(&self, _rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        Err(Error::unsupported("previewing dnf clean"))
+    }
+
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child =
+            Self::dnf_clean(CleanSubcommand::All).map_err(|e| Error::from_io(DNF_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(DNF_ALIAS)?
+            .combined())
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::dnf_install(Some([InstallOption::AssumeYes]), [package_name])
+            .map_err(|e| Error::from_io(DNF_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(DNF_ALIAS)?
+            .combined())
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::dnf_upgrade(Some([InstallOption::AssumeYes]), [package_name])
+            .map_err(|e| Error::from_io(DNF_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(DNF_ALIAS)?
+            .combined())
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::dnf_remove(Some([RemoveOption::AssumeYes]), [package_name])
+            .map_err(|e| Error::from_io(DNF_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(DNF_ALIAS)?
+            .combined())
+    }
+
+    fn pin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let output = checked(
+            DNF_ALIAS,
+            Self::dnf_versionlock(VersionlockSubcommand::Add, [package_name]),
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn unpin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let output = checked(
+            DNF_ALIAS,
+            Self::dnf_versionlock(VersionlockSubcommand::Delete, [package_name]),
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+pub enum CleanSubcommand {
+    All,
+    DbCache,
+    ExpireCache,
+    Metadata,
+    Packages,
+}
+
+impl From<CleanSubcommand> for &'static str {
+    fn from(value: CleanSubcommand) -> Self {
+        match value {
+            CleanSubcommand::All => "all",
+            CleanSubcommand::DbCache => "dbcache",
+            CleanSubcommand::ExpireCache => "expire-cache",
+            CleanSubcommand::Metadata => "metadata",
+            CleanSubcommand::Packages => "packages",
+        }
+    }
+}
+
+pub enum InstallOption {
+    AssumeYes,
+    AllowErasing,
+    Best,
+    NoBest,
+    SkipBroken,
+    NoDocs,
+    NoWeakDeps,
+    DownloadOnly,
+    Refresh,
+    CacheOnly,
+    EnableRepo(String),
+    DisableRepo(String),
+    SetOpt(String),
+}
+
+impl From<InstallOption> for String {
+    fn from(value: InstallOption) -> Self {
+        match value {
+            InstallOption::AssumeYes => "--assumeyes".to_string(),
+            InstallOption::AllowErasing => "--allowerasing".to_string(),
+            InstallOption::Best => "--best".to_string(),
+            InstallOption::NoBest => "--nobest".to_string(),
+            InstallOption::SkipBroken => "--skip-broken".to_string(),
+            InstallOption::NoDocs => "--nodocs".to_string(),
+            InstallOption::NoWeakDeps => "--setopt=install_weak_deps=False".to_string(),
+            InstallOption::DownloadOnly => "--downloadonly".to_string(),
+            InstallOption::Refresh => "--refresh".to_string(),
+            InstallOption::CacheOnly => "--cacheonly".to_string(),
+            InstallOption::EnableRepo(repo) => format!("--enablerepo={repo}"),
+            InstallOption::DisableRepo(repo) => format!("--disablerepo={repo}"),
+            InstallOption::SetOpt(option) => format!("--setopt={option}"),
+        }
+    }
+}
+
+pub enum ListOption {
+    Installed,
+    Upgrades,
+    Autoremove,
+}
+
+impl From<ListOption> for String {
+    fn from(value: ListOption) -> Self {
+        match value {
+            ListOption::Installed => "--installed".to_string(),
+            ListOption::Upgrades => "--upgrades".to_string(),
+            ListOption::Autoremove => "--autoremove".to_string(),
+        }
+    }
+}
+
+pub enum RemoveOption {
+    AssumeYes,
+    NoAutoremove,
+    SetOpt(String),
+}
+
+impl From<RemoveOption> for String {
+    fn from(value: RemoveOption) -> Self {
+        match value {
+            RemoveOption::AssumeYes => "--assumeyes".to_string(),
+            RemoveOption::NoAutoremove => "--noautoremove".to_string(),
+            RemoveOption::SetOpt(option) => format!("--setopt={option}"),
+        }
+    }
+}
+
+pub enum RepoqueryOption {
+    Installed,
+    /// Only list packages installed by the user rather than as dependencies
+    UserInstalled,
+    /// List the capabilities the packages require
+    Requires,
+    /// Resolve required capabilities to the packages providing them
+    Resolve,
+    /// Only list packages requiring the specified capability
+    WhatRequires(String),
+}
+
+impl From<RepoqueryOption> for String {
+    fn from(value: RepoqueryOption) -> Self {
+        match value {
+            RepoqueryOption::Installed => "--installed".to_string(),
+            RepoqueryOption::UserInstalled => "--userinstalled".to_string(),
+            RepoqueryOption::Requires => "--requires".to_string(),
+            RepoqueryOption::Resolve => "--resolve".to_string(),
+            RepoqueryOption::WhatRequires(capability) => format!("--whatrequires={capability}"),
+        }
+    }
+}
+
+pub enum SearchOption {
+    All,
+    Refresh,
+    CacheOnly,
+}
+
+impl From<SearchOption> for String {
+    fn from(value: SearchOption) -> Self {
+        match value {
+            SearchOption::All => "--all".to_string(),
+            SearchOption::Refresh => "--refresh".to_string(),
+            SearchOption::CacheOnly => "--cacheonly".to_string(),
+        }
+    }
+}
+
+pub enum VersionlockSubcommand {
+    Add,
+    Delete,
+    List,
+}
+
+impl From<VersionlockSubcommand> for String {
+    fn from(value: VersionlockSubcommand) -> Self {
+        match value {
+            VersionlockSubcommand::Add => "add".to_string(),
+            VersionlockSubcommand::Delete => "delete".to_string(),
+            VersionlockSubcommand::List => "list".to_string(),
+        }
+    }
+}
+
+pub enum VerifyOption {
+    NoDeps,
+    NoFiles,
+    NoScripts,
+    NoDigest,
+    NoSignature,
+}
+
+impl From<VerifyOption> for String {
+    fn from(value: VerifyOption) -> Self {
+        match value {
+            VerifyOption::NoDeps => "--nodeps".to_string(),
+            VerifyOption::NoFiles => "--nofiles".to_string(),
+            VerifyOption::NoScripts => "--noscripts".to_string(),
+            VerifyOption::NoDigest => "--nodigest".to_string(),
+            VerifyOption::NoSignature => "--nosignature".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    /// rpm listing curl and vim-enhanced as installed
+    fn stub_installed(path: &StubPath) -> &StubPath {
+        path.stub(
+            "rpm",
+            "[ \"$1 $2\" = '--query --all' ] || exit 1\n\
+             printf 'curl\\t8.6.0-7.fc40\\tA utility for getting files from remote servers\\n'\n\
+             printf 'vim-enhanced\\t9.1.158-1.fc40\\tA version of the VIM editor\\n'",
+        )
+    }
+
+    #[test]
+    fn missing_binary() {
+        let _path = StubPath::isolated();
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Dnf.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::BinaryMissing {
+                binary: RPM_ALIAS.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn installed_packages() {
+        let path = StubPath::isolated();
+        stub_installed(&path).stub(
+            "dnf",
+            "[ \"$1 $2\" = 'versionlock list' ] || exit 1\necho 'vim-enhanced-2:9.1.158-1.fc40.*'",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Dnf
+            .filter_packages(rx, PackageLocality::Local, "vim".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "vim-enhanced");
+        assert_eq!(
+            packages[0].installed_version.as_deref(),
+            Some("9.1.158-1.fc40")
+        );
+        assert_eq!(
+            packages[0].description.as_deref(),
+            Some("A version of the VIM editor")
+        );
+        assert!(packages[0].pinned);
+    }
+
+    #[test]
+    fn remote_packages() {
+        let path = StubPath::isolated();
+        stub_installed(&path).stub(
+            "dnf",
+            "[ \"$1\" = search ] || exit 1\n\
+             echo 'Matched fields: name (exact)'\n\
+             echo ' curl.x86_64 : A utility for getting files from remote servers'\n\
+             echo 'Matched fields: name'\n\
+             echo ' curlpp.i686 : A C++ wrapper for libcURL'\n\
+             echo ' curlpp.x86_64 : A C++ wrapper for libcURL'",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Dnf
+            .filter_packages(rx, PackageLocality::Remote, "curl".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "curl");
+        assert_eq!(
+            packages[0].installed_version.as_deref(),
+            Some("8.6.0-7.fc40")
+        );
+        assert_eq!(packages[1].name, "curlpp");
+        assert_eq!(packages[1].installed_version, None);
+        assert_eq!(
+            packages[1].description.as_deref(),
+            Some("A C++ wrapper for libcURL")
+        );
+    }
+
+    #[test]
+    fn nothing_found() {
+        let path = StubPath::isolated();
+        stub_installed(&path).stub("dnf", "echo 'No matches found.' >&2\nexit 1");
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Dnf.filter_packages(rx, PackageLocality::Remote, "nothing".to_string()),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn outdated_packages() {
+        let path = StubPath::isolated();
+        stub_installed(&path).stub(
+            "dnf",
+            "case \"$1\" in\n\
+             list) [ \"$2\" = --upgrades ] || exit 1\n\
+             echo 'Available upgrades'\n\
+             echo 'vim-enhanced.x86_64 2:9.1.393-1.fc40 updates'\n\
+             echo 'vim-enhanced.i686 2:9.1.393-1.fc40 updates' ;;\n\
+             *) exit 1 ;;\n\
+             esac",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Dnf.outdated_packages(rx).unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "vim-enhanced");
+        assert_eq!(packages[0].version.as_deref(), Some("2:9.1.393-1.fc40"));
+        assert_eq!(
+            packages[0].installed_version.as_deref(),
+            Some("9.1.158-1.fc40")
+        );
+        assert_eq!(packages[0].repository.as_deref(), Some("updates"));
+    }
+
+    #[test]
+    fn failed_outdated_packages() {
+        let path = StubPath::isolated();
+        stub_installed(&path).stub(
+            "dnf",
+            "echo 'Error: Failed to download metadata for repo' >&2\nexit 1",
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Dnf.outdated_packages(rx),
+            Err(Error::NonZeroExit {
+                command: DNF_ALIAS.to_string(),
+                code: Some(1),
+                stderr: "Error: Failed to download metadata for repo".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn requested_packages() {
+        let path = StubPath::isolated();
+        path.stub(
+            "dnf",
+            "[ \"$1 $3\" = 'repoquery --userinstalled' ] || exit 1\necho curl\necho\necho vim-enhanced",
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Dnf.requested_packages(rx),
+            Ok(vec!["curl".to_string(), "vim-enhanced".to_string()])
+        );
+    }
+
+    #[test]
+    fn failed_requested_packages() {
+        let path = StubPath::isolated();
+        path.stub("dnf", "echo 'Error: rpmdb open failed' >&2\nexit 1");
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Dnf.requested_packages(rx),
+            Err(Error::NonZeroExit {
+                command: DNF_ALIAS.to_string(),
+                code: Some(1),
+                stderr: "Error: rpmdb open failed".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn dependencies() {
+        let path = StubPath::isolated();
+        path.stub(
+            "dnf",
+            "[ \"$1 $3 $4 $5\" = 'repoquery --requires --resolve curl' ] || exit 1\n\
+             echo 'glibc-0:2.39-15.fc40.x86_64'\n\
+             echo 'glibc-0:2.39-15.fc40.i686'\n\
+             echo 'libcurl-0:8.6.0-7.fc40.x86_64'",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let dependencies = Dnf.dependencies(rx, "curl".to_string()).unwrap();
+        assert_eq!(
+            dependencies
+                .iter()
+                .map(|dependency| dependency.name.as_str())
+                .collect::<Vec<_>>(),
+            ["glibc", "libcurl"]
+        );
+    }
+}
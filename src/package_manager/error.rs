@@ -0,0 +1,186 @@
+use std::{fmt::Display, io, process::Output};
+
+use super::Backend;
+
+/// Reasons a `PackageManager` operation can fail
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// The executable could not be found on PATH
+    BinaryMissing { binary: String },
+    /// The command ran but exited unsuccessfully, `code` is None when it
+    /// was terminated by a signal
+    NonZeroExit {
+        command: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+    /// The operation was stopped before it completed
+    Cancelled,
+    /// The command succeeded but its output could not be understood
+    Parse { command: String, reason: String },
+    /// The command requires privileges the current user does not have
+    PermissionDenied { command: String, reason: String },
+    /// The backend has no way of performing the operation
+    Unsupported { operation: String },
+    /// The package cannot be routed to a backend, since none or several
+    /// of the aggregated backends offer it
+    UnknownSource {
+        package: String,
+        sources: Vec<Backend>,
+    },
+    /// The command could not be run for any other reason
+    Io { command: String, reason: String },
+}
+
+impl Error {
+    /// Classify a failure to run `command`
+    pub fn from_io(command: &str, error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Self::BinaryMissing {
+                binary: command
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or(command)
+                    .to_string(),
+            },
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied {
+                command: command.to_string(),
+                reason: error.to_string(),
+            },
+            _ => Self::Io {
+                command: command.to_string(),
+                reason: error.to_string(),
+            },
+        }
+    }
+
+    /// Classify an unsuccessful exit of `command` from what it printed
+    /// on stderr
+    pub fn from_exit(command: &str, code: Option<i32>, stderr: String) -> Self {
+        let stderr = stderr.trim().to_string();
+        let lowercase = stderr.to_lowercase();
+        let permission_denied = [
+            "permission denied",
+            "operation not permitted",
+            "are you root",
+            "must be root",
+            "unless you are root",
+            "requires root",
+            "eacces",
+        ]
+        .iter()
+        .any(|message| lowercase.contains(message));
+        if permission_denied {
+            return Self::PermissionDenied {
+                command: command.to_string(),
+                reason: stderr,
+            };
+        }
+        Self::NonZeroExit {
+            command: command.to_string(),
+            code,
+            stderr,
+        }
+    }
+
+    pub fn parse(command: &str, reason: impl Display) -> Self {
+        Self::Parse {
+            command: command.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn unsupported(operation: impl Display) -> Self {
+        Self::Unsupported {
+            operation: operation.to_string(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BinaryMissing { binary } => write!(f, "{binary} was not found on PATH"),
+            Self::NonZeroExit {
+                command,
+                code: Some(code),
+                stderr,
+            } => write!(f, "{command} exited with code {code}\n{stderr}"),
+            Self::NonZeroExit {
+                command,
+                code: None,
+                stderr,
+            } => write!(f, "{command} was terminated by a signal\n{stderr}"),
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::Parse { command, reason } => {
+                write!(f, "failed to parse the output of {command}: {reason}")
+            }
+            Self::PermissionDenied { command, reason } => {
+                write!(f, "permission denied running {command}\n{reason}")
+            }
+            Self::Unsupported { operation } => write!(f, "{operation} is not supported"),
+            Self::UnknownSource { package, sources } if sources.is_empty() => {
+                write!(
+                    f,
+                    "no backend is known to offer {package}, search for it first"
+                )
+            }
+            Self::UnknownSource { package, sources } => {
+                let sources = sources
+                    .iter()
+                    .map(|backend| backend.to_string())
+                    .collect::<Vec<String>>();
+                write!(
+                    f,
+                    "{package} is offered by {}, pick one of them",
+                    sources.join(", ")
+                )
+            }
+            Self::Io { command, reason } => write!(f, "failed to execute {command}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Output of a blocking command that ran to completion, failing when it
+/// could not be run or exited unsuccessfully
+pub fn checked(command: &str, result: io::Result<Output>) -> Result<Output, Error> {
+    let output = result.map_err(|e| Error::from_io(command, e))?;
+    if output.status.success() {
+        return Ok(output);
+    }
+    Err(Error::from_exit(
+        command,
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    ))
+}
+
+/// Output of a blocking command that ran to completion, regardless of
+/// its exit status, for commands that report problems through it
+pub fn unchecked(command: &str, result: io::Result<Output>) -> Result<Output, Error> {
+    result.map_err(|e| Error::from_io(command, e))
+}
+
+/// Output of a search command, where an unsuccessful exit counts as
+/// nothing having matched when stderr is empty or contains one of the
+/// `no_match` notices the command prints in that case
+pub fn checked_search(
+    command: &str,
+    result: io::Result<Output>,
+    no_match: &[&str],
+) -> Result<Output, Error> {
+    let output = result.map_err(|e| Error::from_io(command, e))?;
+    if output.status.success() {
+        return Ok(output);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    if stderr.trim().is_empty() || no_match.iter().any(|notice| stderr.contains(notice)) {
+        return Ok(Output {
+            stdout: Vec::new(),
+            ..output
+        });
+    }
+    Err(Error::from_exit(command, output.status.code(), stderr))
+}
@@ -0,0 +1,786 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::mpsc::{Receiver, Sender},
+};
+
+use super::{
+    Backend, Cancellation, CleanupPreview, CommandResult, Dependency, HealthWarning, Package,
+    PackageKind, PackageLocality, PackageManager, SpawnCommandResult, SystemInfo, command,
+    error::{Error, checked, checked_search},
+    fuzz_packages,
+    graph::DependencyGraph,
+    handle_spawned_command, rank_packages, spawn_command, stream_spawned_command,
+};
+
+pub struct Flatpak;
+
+const FLATPAK_ALIAS: &str = "flatpak";
+
+/// Applications are listed as `<installation>/<application id>` so that the
+/// same application installed per-user and system-wide can be told apart.
+fn split_installation(package_name: &str) -> (Option<Installation>, String) {
+    match package_name.split_once('/') {
+        Some(("user", application)) => (Some(Installation::User), application.to_string()),
+        Some(("system", application)) => (Some(Installation::System), application.to_string()),
+        _ => (None, package_name.to_string()),
+    }
+}
+
+impl Flatpak {
+    /// List installed applications, one tab separated
+    /// `<application> <installation> <version> <origin> <description>`
+    /// row per line
+    fn flatpak_list<I>(options: Option<I>) -> CommandResult
+    where
+        I: IntoIterator<Item = ListOption>,
+    {
+        let mut args = vec![
+            "list".to_string(),
+            "--columns=application,installation,version,origin,description".to_string(),
+        ];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: ListOption| option.into()));
+        }
+        command(FLATPAK_ALIAS, args)
+    }
+
+    /// Search the remotes configured for an installation for
+    /// applications matching the specified pattern
+    fn flatpak_search(installation: Installation, pattern: String) -> CommandResult {
+        command(
+            FLATPAK_ALIAS,
+            [
+                "search".to_string(),
+                installation.into(),
+                "--columns=application,version,remotes,description".to_string(),
+                pattern,
+            ],
+        )
+    }
+
+    /// List the applications of an installation that have an update
+    /// available, one tab separated `<application> <version>` row per line
+    fn flatpak_remote_ls_updates(installation: Installation) -> CommandResult {
+        command(
+            FLATPAK_ALIAS,
+            [
+                "remote-ls".to_string(),
+                installation.into(),
+                "--updates".to_string(),
+                "--app".to_string(),
+                "--columns=application,version".to_string(),
+            ],
+        )
+    }
+
+    /// Show information about an installed application or runtime
+    fn flatpak_info(installation: Option<Installation>, application: String) -> CommandResult {
+        let mut args = vec!["info".to_string()];
+        if let Some(installation) = installation {
+            args.push(installation.into());
+        }
+        args.push(application);
+        command(FLATPAK_ALIAS, args)
+    }
+
+    /// Show the remote metadata of applications matching the
+    /// specified application id
+    fn flatpak_search_details(
+        installation: Option<Installation>,
+        application: String,
+    ) -> SpawnCommandResult {
+        let mut args = vec![
+            "search".to_string(),
+            "--columns=name,description,application,version,branch,remotes".to_string(),
+        ];
+        if let Some(installation) = installation {
+            args.push(installation.into());
+        }
+        args.push(application);
+        spawn_command(FLATPAK_ALIAS, args)
+    }
+
+    /// Install applications from the remotes of an installation
+    fn flatpak_install<I>(
+        installation: Option<Installation>,
+        options: Option<I>,
+        application: String,
+    ) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+    {
+        let mut args = vec!["install".to_string()];
+        if let Some(installation) = installation {
+            args.push(installation.into());
+        }
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        args.push(application);
+
+        spawn_command(FLATPAK_ALIAS, args)
+    }
+
+    /// Update applications to their latest version
+    fn flatpak_update<I>(
+        installation: Option<Installation>,
+        options: Option<I>,
+        application: String,
+    ) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+    {
+        let mut args = vec!["update".to_string()];
+        if let Some(installation) = installation {
+            args.push(installation.into());
+        }
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        args.push(application);
+
+        spawn_command(FLATPAK_ALIAS, args)
+    }
+
+    /// Uninstall applications, or unused runtimes and extensions
+    /// when used together with `UninstallOption::Unused`
+    fn flatpak_uninstall<I>(
+        installation: Option<Installation>,
+        options: Option<I>,
+        application: Option<String>,
+    ) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = UninstallOption>,
+    {
+        let mut args = vec!["uninstall".to_string()];
+        if let Some(installation) = installation {
+            args.push(installation.into());
+        }
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: UninstallOption| option.into()),
+            );
+        }
+        if let Some(application) = application {
+            args.push(application);
+        }
+
+        spawn_command(FLATPAK_ALIAS, args)
+    }
+
+    /// Check an installation for missing or corrupt objects
+    fn flatpak_repair<I>(installation: Installation, options: Option<I>) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = RepairOption>,
+    {
+        let mut args = vec!["repair".to_string(), installation.into()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: RepairOption| option.into()),
+            );
+        }
+
+        spawn_command(FLATPAK_ALIAS, args)
+    }
+
+    /// Mask an application so that it is not updated, or remove the mask
+    /// again. Without an application, the masked patterns are listed
+    fn flatpak_mask(
+        installation: Option<Installation>,
+        remove: bool,
+        application: Option<String>,
+    ) -> CommandResult {
+        let mut args = vec!["mask".to_string()];
+        if let Some(installation) = installation {
+            args.push(installation.into());
+        }
+        if remove {
+            args.push("--remove".to_string());
+        }
+        if let Some(application) = application {
+            args.push(application);
+        }
+        command(FLATPAK_ALIAS, args)
+    }
+
+    /// Masked applications of both installations, named
+    /// `<installation>/<application id>`
+    fn masked_applications() -> Result<HashSet<String>, Error> {
+        let mut masked = HashSet::new();
+        for installation in [Installation::User, Installation::System] {
+            let output = checked(
+                FLATPAK_ALIAS,
+                Self::flatpak_mask(Some(installation), false, None),
+            )?;
+            masked.extend(
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(|pattern| pattern.trim())
+                    .filter(|pattern| !pattern.is_empty())
+                    .map(|pattern| format!("{installation}/{pattern}")),
+            );
+        }
+        Ok(masked)
+    }
+
+    /// List the remotes configured for both installations
+    fn flatpak_remotes() -> CommandResult {
+        command(
+            FLATPAK_ALIAS,
+            [
+                "remotes",
+                "--show-details",
+                "--columns=name,title,url,options",
+            ],
+        )
+    }
+
+    /// Installed applications, named `<installation>/<application id>`
+    fn installed_applications() -> Result<Vec<Package>, Error> {
+        let masked = Self::masked_applications()?;
+        let output = checked(FLATPAK_ALIAS, Self::flatpak_list(Some([ListOption::App])))?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut columns = line.splitn(5, '\t').map(|column| column.trim());
+                let application = columns.next()?;
+                let installation = columns.next()?;
+                let version = columns.next().filter(|version| !version.is_empty());
+                let name = format!("{installation}/{application}");
+                Some(Package {
+                    version: version.map(|version| version.to_string()),
+                    // the version column is empty for applications that do
+                    // not declare one, which does not make them any less
+                    // installed
+                    installed_version: Some(version.unwrap_or_default().to_string()),
+                    repository: columns.next().map(|origin| origin.to_string()),
+                    description: columns.next().map(|description| description.to_string()),
+                    kind: PackageKind::Application,
+                    pinned: masked.contains(&name),
+                    ..Package::new(name, Backend::Flatpak)
+                })
+            })
+            .collect())
+    }
+}
+
+impl PackageManager for Flatpak {
+    fn alias(&self) -> &'static str {
+        FLATPAK_ALIAS
+    }
+
+    fn filter_packages(
+        &self,
+        _rx: Receiver<bool>,
+        package_locality: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        match package_locality {
+            PackageLocality::Local => Ok(fuzz_packages(Self::installed_applications()?, pattern)),
+            PackageLocality::Remote => {
+                let installed_versions = Self::installed_applications()?
+                    .into_iter()
+                    .map(|application| (application.name, application.installed_version))
+                    .collect::<HashMap<String, Option<String>>>();
+                let mut applications = Vec::new();
+                for installation in [Installation::User, Installation::System] {
+                    let output = checked_search(
+                        FLATPAK_ALIAS,
+                        Self::flatpak_search(installation, pattern.clone()),
+                        &["No matches found"],
+                    )?;
+                    // an informational message without any tab is printed in
+                    // place of the columns when nothing matches
+                    applications.extend(
+                        String::from_utf8_lossy(&output.stdout)
+                            .lines()
+                            .filter(|line| line.contains('\t'))
+                            .filter_map(|line| {
+                                let mut columns = line.splitn(4, '\t').map(|column| column.trim());
+                                let name = format!("{installation}/{}", columns.next()?);
+                                Some(Package {
+                                    version: columns
+                                        .next()
+                                        .filter(|version| !version.is_empty())
+                                        .map(|version| version.to_string()),
+                                    installed_version: installed_versions
+                                        .get(&name)
+                                        .cloned()
+                                        .flatten(),
+                                    repository: columns.next().map(|remotes| remotes.to_string()),
+                                    description: columns
+                                        .next()
+                                        .map(|description| description.to_string()),
+                                    kind: PackageKind::Application,
+                                    ..Package::new(name, Backend::Flatpak)
+                                })
+                            }),
+                    );
+                }
+                Ok(rank_packages(applications, &pattern))
+            }
+        }
+    }
+
+    fn outdated_packages(&self, _rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        let installed_versions = Self::installed_applications()?
+            .into_iter()
+            .map(|application| (application.name, application.installed_version))
+            .collect::<HashMap<String, Option<String>>>();
+        let mut applications = Vec::new();
+        for installation in [Installation::User, Installation::System] {
+            let output = checked(FLATPAK_ALIAS, Self::flatpak_remote_ls_updates(installation))?;
+            applications.extend(String::from_utf8_lossy(&output.stdout).lines().filter_map(
+                |line| {
+                    let mut columns = line.splitn(2, '\t').map(|column| column.trim());
+                    let name = format!("{installation}/{}", columns.next()?);
+                    Some(Package {
+                        version: columns
+                            .next()
+                            .filter(|version| !version.is_empty())
+                            .map(|version| version.to_string()),
+                        installed_version: installed_versions.get(&name).cloned().flatten(),
+                        kind: PackageKind::Application,
+                        ..Package::new(name, Backend::Flatpak)
+                    })
+                },
+            ));
+        }
+        Ok(applications)
+    }
+
+    fn package_manager_config(&self, _rx: Receiver<bool>) -> Result<String, Error> {
+        let output = checked(FLATPAK_ALIAS, Self::flatpak_remotes())?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn system_info(&self, _rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        Err(Error::unsupported(
+            "reading the system information of flatpak",
+        ))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let (installation, application) = split_installation(&package_name);
+        let heading = match installation {
+            Some(installation) => format!("Installation: {installation}\n"),
+            None => String::default(),
+        };
+
+        // installed applications carry local metadata, while the remotes
+        // only know about the metadata published in their appstream data
+        if let Ok(output) = Self::flatpak_info(installation, application.clone())
+            && output.status.success()
+        {
+            return Ok(format!(
+                "{heading}{}",
+                String::from_utf8_lossy(&output.stdout)
+            ));
+        }
+
+        let child = Self::flatpak_search_details(installation, application)
+            .map_err(|e| Error::from_io(FLATPAK_ALIAS, e))?;
+        let output = handle_spawned_command(rx, child)?;
+        Ok(format!("{heading}{}", output.out.unwrap_or_default()))
+    }
+
+    fn dependencies(
+        &self,
+        _rx: Receiver<bool>,
+        _package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        Err(Error::unsupported(
+            "listing the dependencies of flatpak applications",
+        ))
+    }
+
+    fn dependents(&self, _rx: Receiver<bool>, _package_name: String) -> Result<Vec<String>, Error> {
+        Err(Error::unsupported(
+            "listing the dependents of flatpak applications",
+        ))
+    }
+
+    fn dependency_graph(&self, _rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        Err(Error::unsupported(
+            "exporting the dependency graph of flatpak applications",
+        ))
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        // the per-user installation is checked before the system one
+        let cancellation = Cancellation::forward(rx);
+        let mut reports = Vec::new();
+        for installation in [Installation::User, Installation::System] {
+            let Some(rx_repair) = cancellation.receiver() else {
+                return Err(Error::Cancelled);
+            };
+            let child = Self::flatpak_repair(installation, Some([RepairOption::DryRun]))
+                .map_err(|e| Error::from_io(FLATPAK_ALIAS, e))?;
+            let report =
+                stream_spawned_command(rx_repair, tx.clone(), child)?.report(FLATPAK_ALIAS)?;
+            reports.push(format!("{installation} installation\n{report}"));
+        }
+        Ok(reports.join("\n"))
+    }
+
+    fn health_warnings(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "splitting the health check of flatpak into warnings",
+        ))
+    }
+
+    fn run_health_check(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "running individual health checks with flatpak",
+        ))
+    }
+
+    fn clean_preview(&self, _rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        Err(Error::unsupported(
+            "previewing the removal of unused flatpak runtimes",
+        ))
+    }
+
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let cancellation = Cancellation::forward(rx);
+        let mut out = String::new();
+        for installation in [Installation::User, Installation::System] {
+            let Some(rx_uninstall) = cancellation.receiver() else {
+                return Err(Error::Cancelled);
+            };
+            let child = Self::flatpak_uninstall(
+                Some(installation),
+                Some([
+                    UninstallOption::Unused,
+                    UninstallOption::AssumeYes,
+                    UninstallOption::NonInteractive,
+                ]),
+                None,
+            )
+            .map_err(|e| Error::from_io(FLATPAK_ALIAS, e))?;
+            let output =
+                stream_spawned_command(rx_uninstall, tx.clone(), child)?.success(FLATPAK_ALIAS)?;
+            out.push_str(&format!(
+                "{installation} installation\n{}\n",
+                output.combined()
+            ));
+        }
+        Ok(out)
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let (installation, application) = split_installation(&package_name);
+        let child = Self::flatpak_install(
+            installation,
+            Some([InstallOption::AssumeYes, InstallOption::NonInteractive]),
+            application,
+        )
+        .map_err(|e| Error::from_io(FLATPAK_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(FLATPAK_ALIAS)?
+            .combined())
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let (installation, application) = split_installation(&package_name);
+        let child = Self::flatpak_update(
+            installation,
+            Some([InstallOption::AssumeYes, InstallOption::NonInteractive]),
+            application,
+        )
+        .map_err(|e| Error::from_io(FLATPAK_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(FLATPAK_ALIAS)?
+            .combined())
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let (installation, application) = split_installation(&package_name);
+        let child = Self::flatpak_uninstall(
+            installation,
+            Some([UninstallOption::AssumeYes, UninstallOption::NonInteractive]),
+            Some(application),
+        )
+        .map_err(|e| Error::from_io(FLATPAK_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(FLATPAK_ALIAS)?
+            .combined())
+    }
+
+    fn pin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let (installation, application) = split_installation(&package_name);
+        checked(
+            FLATPAK_ALIAS,
+            Self::flatpak_mask(installation, false, Some(application)),
+        )?;
+        Ok(format!("Masked {package_name}"))
+    }
+
+    fn unpin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let (installation, application) = split_installation(&package_name);
+        checked(
+            FLATPAK_ALIAS,
+            Self::flatpak_mask(installation, true, Some(application)),
+        )?;
+        Ok(format!("Unmasked {package_name}"))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Installation {
+    User,
+    System,
+}
+
+impl Display for Installation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::User => write!(f, "user"),
+            Self::System => write!(f, "system"),
+        }
+    }
+}
+
+impl From<Installation> for String {
+    fn from(value: Installation) -> Self {
+        match value {
+            Installation::User => "--user".to_string(),
+            Installation::System => "--system".to_string(),
+        }
+    }
+}
+
+pub enum InstallOption {
+    AssumeYes,
+    NonInteractive,
+    NoDeps,
+    NoRelated,
+    NoPull,
+    NoDeploy,
+    Reinstall,
+    App,
+    Runtime,
+}
+
+impl From<InstallOption> for String {
+    fn from(value: InstallOption) -> Self {
+        match value {
+            InstallOption::AssumeYes => "--assumeyes".to_string(),
+            InstallOption::NonInteractive => "--noninteractive".to_string(),
+            InstallOption::NoDeps => "--no-deps".to_string(),
+            InstallOption::NoRelated => "--no-related".to_string(),
+            InstallOption::NoPull => "--no-pull".to_string(),
+            InstallOption::NoDeploy => "--no-deploy".to_string(),
+            InstallOption::Reinstall => "--reinstall".to_string(),
+            InstallOption::App => "--app".to_string(),
+            InstallOption::Runtime => "--runtime".to_string(),
+        }
+    }
+}
+
+pub enum ListOption {
+    App,
+    Runtime,
+}
+
+impl From<ListOption> for String {
+    fn from(value: ListOption) -> Self {
+        match value {
+            ListOption::App => "--app".to_string(),
+            ListOption::Runtime => "--runtime".to_string(),
+        }
+    }
+}
+
+pub enum RepairOption {
+    DryRun,
+    ReinstallAll,
+}
+
+impl From<RepairOption> for String {
+    fn from(value: RepairOption) -> Self {
+        match value {
+            RepairOption::DryRun => "--dry-run".to_string(),
+            RepairOption::ReinstallAll => "--reinstall-all".to_string(),
+        }
+    }
+}
+
+pub enum UninstallOption {
+    AssumeYes,
+    NonInteractive,
+    Unused,
+    DeleteData,
+    Force,
+    KeepRef,
+    NoRelated,
+    App,
+    Runtime,
+}
+
+impl From<UninstallOption> for String {
+    fn from(value: UninstallOption) -> Self {
+        match value {
+            UninstallOption::AssumeYes => "--assumeyes".to_string(),
+            UninstallOption::NonInteractive => "--noninteractive".to_string(),
+            UninstallOption::Unused => "--unused".to_string(),
+            UninstallOption::DeleteData => "--delete-data".to_string(),
+            UninstallOption::Force => "--force-remove".to_string(),
+            UninstallOption::KeepRef => "--keep-ref".to_string(),
+            UninstallOption::NoRelated => "--no-related".to_string(),
+            UninstallOption::App => "--app".to_string(),
+            UninstallOption::Runtime => "--runtime".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    /// flatpak with GIMP installed and masked per-user and Maps installed
+    /// system-wide without a version, where only GIMP has an update
+    const FLATPAK: &str = "case \"$1 $2\" in\n\
+        'mask --user') echo org.gimp.GIMP ;;\n\
+        'mask --system') ;;\n\
+        'list --columns=application,installation,version,origin,description')\n\
+        printf 'org.gimp.GIMP\\tuser\\t2.10.38\\tflathub\\tGNU Image Manipulation Program\\n'\n\
+        printf 'org.gnome.Maps\\tsystem\\t\\tfedora\\tFind places around the world\\n' ;;\n\
+        'search --user')\n\
+        printf 'org.gimp.GIMP\\t2.10.38\\tflathub\\tGNU Image Manipulation Program\\n'\n\
+        printf 'org.gimp.GIMP.Plugin.GMic\\t3.3.5\\tflathub\\tGMIC plugin for GIMP\\n' ;;\n\
+        'search --system') echo 'No matches found' ;;\n\
+        'remote-ls --user') printf 'org.gimp.GIMP\\t2.10.39\\n' ;;\n\
+        'remote-ls --system') ;;\n\
+        *) exit 1 ;;\n\
+        esac";
+
+    #[test]
+    fn missing_binary() {
+        let _path = StubPath::isolated();
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Flatpak.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::BinaryMissing {
+                binary: FLATPAK_ALIAS.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn installed_applications() {
+        let path = StubPath::isolated();
+        path.stub("flatpak", FLATPAK);
+        let (_tx, rx) = mpsc::channel();
+        let packages = Flatpak
+            .filter_packages(rx, PackageLocality::Local, String::default())
+            .unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "user/org.gimp.GIMP");
+        assert_eq!(packages[0].installed_version.as_deref(), Some("2.10.38"));
+        assert_eq!(packages[0].repository.as_deref(), Some("flathub"));
+        assert!(packages[0].pinned);
+        assert_eq!(packages[1].name, "system/org.gnome.Maps");
+        assert_eq!(packages[1].version, None);
+        assert_eq!(packages[1].installed_version.as_deref(), Some(""));
+        assert_eq!(
+            packages[1].description.as_deref(),
+            Some("Find places around the world")
+        );
+        assert!(!packages[1].pinned);
+    }
+
+    #[test]
+    fn remote_applications() {
+        let path = StubPath::isolated();
+        path.stub("flatpak", FLATPAK);
+        let (_tx, rx) = mpsc::channel();
+        let packages = Flatpak
+            .filter_packages(rx, PackageLocality::Remote, "gimp".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "user/org.gimp.GIMP");
+        assert_eq!(packages[0].installed_version.as_deref(), Some("2.10.38"));
+        assert_eq!(packages[1].name, "user/org.gimp.GIMP.Plugin.GMic");
+        assert_eq!(packages[1].version.as_deref(), Some("3.3.5"));
+        assert_eq!(packages[1].installed_version, None);
+        assert_eq!(
+            packages[1].description.as_deref(),
+            Some("GMIC plugin for GIMP")
+        );
+    }
+
+    #[test]
+    fn outdated_applications() {
+        let path = StubPath::isolated();
+        path.stub("flatpak", FLATPAK);
+        let (_tx, rx) = mpsc::channel();
+        let packages = Flatpak.outdated_packages(rx).unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "user/org.gimp.GIMP");
+        assert_eq!(packages[0].version.as_deref(), Some("2.10.39"));
+        assert_eq!(packages[0].installed_version.as_deref(), Some("2.10.38"));
+    }
+
+    #[test]
+    fn failed_outdated_applications() {
+        let path = StubPath::isolated();
+        path.stub(
+            "flatpak",
+            "[ \"$1\" = remote-ls ] || exit 0\n\
+             echo 'error: Unable to load summary from remote flathub' >&2\nexit 1",
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Flatpak.outdated_packages(rx),
+            Err(Error::NonZeroExit {
+                command: FLATPAK_ALIAS.to_string(),
+                code: Some(1),
+                stderr: "error: Unable to load summary from remote flathub".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::{collections::HashSet, fmt::Write};
+
+use serde::Serialize;
+
+/// Whether a package is needed to run or only to build its dependent
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    Runtime,
+    Build,
+}
+
+/// An installed package
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GraphNode {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// `from` depends on `to`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: DependencyKind,
+}
+
+/// Dependency graph of the installed packages
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct DependencyGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Serialize)]
+struct JsonGraph<'a> {
+    nodes: Vec<JsonNode<'a>>,
+    edges: Vec<JsonEdge<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonNode<'a> {
+    name: &'a str,
+    version: Option<&'a str>,
+    leaf: bool,
+}
+
+#[derive(Serialize)]
+struct JsonEdge<'a> {
+    from: &'a str,
+    to: &'a str,
+    kind: DependencyKind,
+}
+
+/// Quote an identifier or attribute value for DOT, so that names such as
+/// `openssl@3`, `homebrew/cask/firefox` or `g++` are taken as they are
+fn quoted(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+impl DependencyGraph {
+    /// Installed packages no other installed package depends on, in other
+    /// words the packages that were most likely installed on purpose
+    pub fn leaves(&self) -> HashSet<&str> {
+        let dependencies = self
+            .edges
+            .iter()
+            .map(|edge| edge.to.as_str())
+            .collect::<HashSet<&str>>();
+        self.nodes
+            .iter()
+            .map(|node| node.name.as_str())
+            .filter(|name| !dependencies.contains(name))
+            .collect()
+    }
+
+    /// Drop the edges to packages that are not part of the graph, such as
+    /// build dependencies that are no longer installed or virtual packages
+    pub fn retain_installed(&mut self) {
+        let installed = self
+            .nodes
+            .iter()
+            .map(|node| node.name.clone())
+            .collect::<HashSet<String>>();
+        self.edges
+            .retain(|edge| installed.contains(&edge.from) && installed.contains(&edge.to));
+    }
+
+    /// Graphviz rendering of the graph, where leaves are filled and build
+    /// dependencies are dashed
+    pub fn to_dot(&self) -> String {
+        let leaves = self.leaves();
+        let mut dot = String::from("digraph \"dependencies\" {\n");
+        dot.push_str("    \"rankdir\"=\"LR\";\n");
+        // keywords such as `node` are the only identifiers left unquoted
+        dot.push_str("    node [\"shape\"=\"box\"];\n");
+        for node in &self.nodes {
+            // versions go on a line of their own below the name
+            let label = match &node.version {
+                Some(version) => quoted(&format!("{}\n{version}", node.name)),
+                None => quoted(&node.name),
+            };
+            let _ = write!(dot, "    {} [\"label\"={label}", quoted(&node.name));
+            if leaves.contains(node.name.as_str()) {
+                dot.push_str(", \"style\"=\"filled\", \"fillcolor\"=\"palegreen\"");
+            }
+            dot.push_str("];\n");
+        }
+        for edge in &self.edges {
+            let _ = write!(dot, "    {} -> {}", quoted(&edge.from), quoted(&edge.to));
+            if let DependencyKind::Build = edge.kind {
+                dot.push_str(" [\"style\"=\"dashed\", \"label\"=\"build\"]");
+            }
+            dot.push_str(";\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// JSON rendering of the graph, as `nodes` with their `version` and
+    /// whether they are a `leaf`, and `edges` with their `kind`
+    pub fn to_json(&self) -> String {
+        let leaves = self.leaves();
+        let graph = JsonGraph {
+            nodes: self
+                .nodes
+                .iter()
+                .map(|node| JsonNode {
+                    name: &node.name,
+                    version: node.version.as_deref(),
+                    leaf: leaves.contains(node.name.as_str()),
+                })
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .map(|edge| JsonEdge {
+                    from: &edge.from,
+                    to: &edge.to,
+                    kind: edge.kind,
+                })
+                .collect(),
+        };
+        serde_json::to_string_pretty(&graph).expect("the graph only consists of strings and bools")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> DependencyGraph {
+        DependencyGraph {
+            nodes: vec![
+                GraphNode {
+                    name: "homebrew/cask/firefox".to_string(),
+                    version: None,
+                },
+                GraphNode {
+                    name: "openssl@3".to_string(),
+                    version: Some("3.3.1".to_string()),
+                },
+                GraphNode {
+                    name: "g++".to_string(),
+                    version: Some("14.1".to_string()),
+                },
+            ],
+            edges: vec![
+                GraphEdge {
+                    from: "homebrew/cask/firefox".to_string(),
+                    to: "openssl@3".to_string(),
+                    kind: DependencyKind::Runtime,
+                },
+                GraphEdge {
+                    from: "openssl@3".to_string(),
+                    to: "g++".to_string(),
+                    kind: DependencyKind::Build,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn dot() {
+        assert_eq!(
+            graph().to_dot(),
+            r#"digraph "dependencies" {
+    "rankdir"="LR";
+    node ["shape"="box"];
+    "homebrew/cask/firefox" ["label"="homebrew/cask/firefox", "style"="filled", "fillcolor"="palegreen"];
+    "openssl@3" ["label"="openssl@3\n3.3.1"];
+    "g++" ["label"="g++\n14.1"];
+    "homebrew/cask/firefox" -> "openssl@3";
+    "openssl@3" -> "g++" ["style"="dashed", "label"="build"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn dot_escapes_quotes() {
+        let graph = DependencyGraph {
+            nodes: vec![GraphNode {
+                name: r#"say "hi"\"#.to_string(),
+                version: None,
+            }],
+            edges: Vec::new(),
+        };
+        assert!(
+            graph
+                .to_dot()
+                .contains(r#"    "say \"hi\"\\" ["label"="say \"hi\"\\""#)
+        );
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            graph().to_json(),
+            r#"{
+  "nodes": [
+    {
+      "name": "homebrew/cask/firefox",
+      "version": null,
+      "leaf": true
+    },
+    {
+      "name": "openssl@3",
+      "version": "3.3.1",
+      "leaf": false
+    },
+    {
+      "name": "g++",
+      "version": "14.1",
+      "leaf": false
+    }
+  ],
+  "edges": [
+    {
+      "from": "homebrew/cask/firefox",
+      "to": "openssl@3",
+      "kind": "runtime"
+    },
+    {
+      "from": "openssl@3",
+      "to": "g++",
+      "kind": "build"
+    }
+  ]
+}"#
+        );
+    }
+}
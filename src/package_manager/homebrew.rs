@@ -1,18 +1,439 @@
-use std::sync::mpsc::Receiver;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    process::Output,
+    sync::mpsc::{Receiver, Sender},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use crate::fuzz;
+use serde::{Deserialize, de::IgnoredAny};
 
 use super::{
-    CommandResult, PackageLocality, PackageManager, SpawnCommandResult, command,
-    handle_spawned_command, spawn_command,
+    Backend, BrewfilePlan, Cancellation, CleanupPreview, CommandResult, Dependency, HealthWarning,
+    Package, PackageKind, PackageLocality, PackageManager, Service, ServiceAction, Severity,
+    SpawnCommandResult, SystemInfo, Tap, command,
+    error::{Error, checked, checked_search},
+    fuzz_packages,
+    graph::{DependencyGraph, DependencyKind, GraphEdge, GraphNode},
+    handle_spawned_command, rank_packages, spawn_command, stream_spawned_command,
 };
 
 pub struct Homebrew;
 
-const HOMEBREW_ALIAS: &'static str = "brew";
+const HOMEBREW_ALIAS: &str = "brew";
+
+/// Output of `brew info --json=v2`
+#[derive(Deserialize)]
+struct Info {
+    #[serde(default)]
+    formulae: Vec<FormulaInfo>,
+    #[serde(default)]
+    casks: Vec<CaskInfo>,
+}
+
+#[derive(Deserialize)]
+struct FormulaInfo {
+    full_name: String,
+    tap: Option<String>,
+    desc: Option<String>,
+    homepage: Option<String>,
+    license: Option<String>,
+    #[serde(default)]
+    versions: FormulaVersions,
+    #[serde(default)]
+    dependencies: Vec<String>,
+    #[serde(default)]
+    build_dependencies: Vec<String>,
+    #[serde(default)]
+    recommended_dependencies: Vec<String>,
+    #[serde(default)]
+    optional_dependencies: Vec<String>,
+    #[serde(default)]
+    conflicts_with: Vec<String>,
+    caveats: Option<String>,
+    #[serde(default)]
+    installed: Vec<InstallReceipt>,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    outdated: bool,
+    #[serde(default)]
+    deprecated: bool,
+    #[serde(default)]
+    disabled: bool,
+    /// Bottle specification keyed by spec, only `stable` is bottled
+    #[serde(default)]
+    bottle: BTreeMap<String, BottleSpec>,
+}
+
+#[derive(Deserialize, Default)]
+struct FormulaVersions {
+    stable: Option<String>,
+    head: Option<String>,
+    #[serde(default)]
+    bottle: bool,
+}
+
+#[derive(Deserialize)]
+struct BottleSpec {
+    /// Bottle files keyed by the platform they were built for
+    #[serde(default)]
+    files: BTreeMap<String, IgnoredAny>,
+}
+
+/// Receipt written by `brew install` for each installed version
+#[derive(Deserialize)]
+struct InstallReceipt {
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    used_options: Vec<String>,
+    #[serde(default)]
+    poured_from_bottle: bool,
+    /// Seconds since the unix epoch
+    time: Option<u64>,
+    #[serde(default)]
+    installed_as_dependency: bool,
+    #[serde(default)]
+    installed_on_request: bool,
+    #[serde(default)]
+    runtime_dependencies: Vec<RuntimeDependency>,
+}
+
+#[derive(Deserialize)]
+struct RuntimeDependency {
+    full_name: String,
+    #[serde(default)]
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct CaskInfo {
+    full_token: String,
+    tap: Option<String>,
+    /// Human readable names of the application
+    #[serde(default)]
+    name: Vec<String>,
+    desc: Option<String>,
+    homepage: Option<String>,
+    version: Option<String>,
+    installed: Option<String>,
+    /// Seconds since the unix epoch
+    installed_time: Option<u64>,
+    #[serde(default)]
+    outdated: bool,
+    #[serde(default)]
+    auto_updates: Option<bool>,
+    #[serde(default)]
+    deprecated: bool,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    depends_on: CaskDependencies,
+    conflicts_with: Option<CaskConflicts>,
+    caveats: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct CaskDependencies {
+    #[serde(default)]
+    formula: Vec<String>,
+    #[serde(default)]
+    cask: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CaskConflicts {
+    #[serde(default)]
+    cask: Vec<String>,
+}
+
+/// Output of `brew outdated --json=v2`
+#[derive(Deserialize)]
+struct Outdated {
+    #[serde(default)]
+    formulae: Vec<OutdatedPackage>,
+    #[serde(default)]
+    casks: Vec<OutdatedPackage>,
+}
+
+#[derive(Deserialize)]
+struct OutdatedPackage {
+    name: String,
+    #[serde(default)]
+    installed_versions: Vec<String>,
+    current_version: String,
+    #[serde(default)]
+    pinned: bool,
+}
+
+/// Entry of `brew tap-info --json`, where the formulae and casks of taps
+/// other than the official ones are qualified as `<tap>/<name>`
+#[derive(Deserialize)]
+struct TapInfo {
+    name: String,
+    remote: Option<String>,
+    #[serde(default)]
+    formula_names: Vec<String>,
+    #[serde(default)]
+    cask_tokens: Vec<String>,
+    /// Relative age of the latest commit, such as `3 days ago`
+    last_commit: Option<String>,
+}
+
+/// Entry of `brew services list --json`
+#[derive(Deserialize)]
+struct ServiceInfo {
+    name: String,
+    /// `none`, `started`, `scheduled`, `stopped`, `error` or `unknown`
+    status: String,
+    user: Option<String>,
+    file: Option<String>,
+}
+
+/// Dependencies drawn by `brew deps --tree`, where every level of the tree
+/// is indented by four characters of `│`, `├──`, `└──` or spaces. The
+/// package itself is the root of the tree
+fn dependency_tree(output: &str) -> Vec<Dependency> {
+    fn close(open: &mut Vec<Dependency>, roots: &mut Vec<Dependency>) {
+        if let Some(node) = open.pop() {
+            match open.last_mut() {
+                Some(parent) => parent.dependencies.push(node),
+                None => roots.push(node),
+            }
+        }
+    }
+
+    // nodes whose dependencies are still being listed, one per level
+    let mut open: Vec<Dependency> = Vec::new();
+    let mut roots = Vec::new();
+    for line in output.lines() {
+        let name = line.trim_start_matches(['│', '├', '└', '─', ' ']);
+        if name.trim().is_empty() {
+            continue;
+        }
+        let depth = (line.chars().count() - name.chars().count()) / 4;
+        while open.len() > depth {
+            close(&mut open, &mut roots);
+        }
+        open.push(Dependency::new(name.trim().to_string()));
+    }
+    while !open.is_empty() {
+        close(&mut open, &mut roots);
+    }
+    roots
+}
+
+/// Append a `==> <heading>` section, leaving out sections without content
+fn push_section(details: &mut String, heading: &str, body: &str) {
+    if body.trim().is_empty() {
+        return;
+    }
+    details.push_str(&format!("==> {heading}\n{}\n", body.trim_end()));
+}
+
+/// Comma separated list of the given names behind a label, or nothing
+/// when there are no names
+fn labelled_list(label: &str, names: &[String]) -> String {
+    match names.is_empty() {
+        true => String::default(),
+        false => format!("{label}: {}\n", names.join(", ")),
+    }
+}
+
+/// How long ago a unix timestamp was, rounded down to whole days
+fn age(timestamp: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    match now.saturating_sub(timestamp) / (24 * 60 * 60) {
+        0 => "today".to_string(),
+        1 => "1 day ago".to_string(),
+        days => format!("{days} days ago"),
+    }
+}
+
+impl FormulaInfo {
+    fn details(&self) -> String {
+        let mut details = format!("{}\n", self.full_name);
+        if let Some(desc) = &self.desc {
+            details.push_str(&format!("{desc}\n"));
+        }
+        if let Some(homepage) = &self.homepage {
+            details.push_str(&format!("{homepage}\n"));
+        }
+
+        let mut overview = String::new();
+        if let Some(tap) = &self.tap {
+            overview.push_str(&format!("Tap: {tap}\n"));
+        }
+        if let Some(license) = &self.license {
+            overview.push_str(&format!("License: {license}\n"));
+        }
+        if let Some(stable) = &self.versions.stable {
+            overview.push_str(&format!("Stable: {stable}"));
+            if self.versions.bottle {
+                overview.push_str(" (bottled)");
+            }
+            overview.push('\n');
+        }
+        if let Some(head) = &self.versions.head {
+            overview.push_str(&format!("HEAD: {head}\n"));
+        }
+        let status = [
+            (!self.installed.is_empty(), "installed"),
+            (self.pinned, "pinned"),
+            (self.outdated, "outdated"),
+            (self.deprecated, "deprecated"),
+            (self.disabled, "disabled"),
+        ]
+        .into_iter()
+        .filter_map(|(applies, status)| applies.then_some(status))
+        .collect::<Vec<&str>>();
+        if !status.is_empty() {
+            overview.push_str(&format!("Status: {}\n", status.join(", ")));
+        }
+        push_section(&mut details, "Overview", &overview);
+
+        let platforms = self
+            .bottle
+            .get("stable")
+            .map(|spec| spec.files.keys().cloned().collect::<Vec<String>>())
+            .unwrap_or_default();
+        let bottles = match platforms.is_empty() {
+            true => "No bottles, builds from source\n".to_string(),
+            false => format!("{}\n", platforms.join(", ")),
+        };
+        push_section(&mut details, "Bottles", &bottles);
+
+        push_section(
+            &mut details,
+            "Dependencies",
+            &[
+                labelled_list("Required", &self.dependencies),
+                labelled_list("Build", &self.build_dependencies),
+                labelled_list("Recommended", &self.recommended_dependencies),
+                labelled_list("Optional", &self.optional_dependencies),
+            ]
+            .concat(),
+        );
+        push_section(
+            &mut details,
+            "Conflicts",
+            &labelled_list("Conflicts with", &self.conflicts_with),
+        );
+
+        let mut installed = String::new();
+        for receipt in &self.installed {
+            let mut notes = vec![match receipt.poured_from_bottle {
+                true => "poured from bottle",
+                false => "built from source",
+            }];
+            if receipt.installed_on_request {
+                notes.push("installed on request");
+            }
+            if receipt.installed_as_dependency {
+                notes.push("installed as dependency");
+            }
+            installed.push_str(&format!("{} ({})", receipt.version, notes.join(", ")));
+            if let Some(time) = receipt.time {
+                installed.push_str(&format!(", {}", age(time)));
+            }
+            installed.push('\n');
+            if !receipt.used_options.is_empty() {
+                installed.push_str(&format!("  options: {}\n", receipt.used_options.join(" ")));
+            }
+            for dependency in &receipt.runtime_dependencies {
+                installed.push_str(&format!(
+                    "  {} {}\n",
+                    dependency.full_name, dependency.version
+                ));
+            }
+        }
+        push_section(&mut details, "Installed", &installed);
+        push_section(
+            &mut details,
+            "Caveats",
+            self.caveats.as_deref().unwrap_or_default(),
+        );
+        details
+    }
+}
+
+impl CaskInfo {
+    fn details(&self) -> String {
+        let mut details = self.full_token.clone();
+        if !self.name.is_empty() {
+            details.push_str(&format!(" ({})", self.name.join(", ")));
+        }
+        details.push('\n');
+        if let Some(desc) = &self.desc {
+            details.push_str(&format!("{desc}\n"));
+        }
+        if let Some(homepage) = &self.homepage {
+            details.push_str(&format!("{homepage}\n"));
+        }
+
+        let mut overview = String::new();
+        if let Some(tap) = &self.tap {
+            overview.push_str(&format!("Tap: {tap}\n"));
+        }
+        if let Some(version) = &self.version {
+            overview.push_str(&format!("Version: {version}\n"));
+        }
+        if self.auto_updates == Some(true) {
+            overview.push_str("Updates itself\n");
+        }
+        let status = [
+            (self.installed.is_some(), "installed"),
+            (self.outdated, "outdated"),
+            (self.deprecated, "deprecated"),
+            (self.disabled, "disabled"),
+        ]
+        .into_iter()
+        .filter_map(|(applies, status)| applies.then_some(status))
+        .collect::<Vec<&str>>();
+        if !status.is_empty() {
+            overview.push_str(&format!("Status: {}\n", status.join(", ")));
+        }
+        push_section(&mut details, "Overview", &overview);
+
+        push_section(
+            &mut details,
+            "Dependencies",
+            &[
+                labelled_list("Formulae", &self.depends_on.formula),
+                labelled_list("Casks", &self.depends_on.cask),
+            ]
+            .concat(),
+        );
+        if let Some(conflicts) = &self.conflicts_with {
+            push_section(
+                &mut details,
+                "Conflicts",
+                &labelled_list("Conflicts with", &conflicts.cask),
+            );
+        }
+        if let Some(installed) = &self.installed {
+            let installed = match self.installed_time {
+                Some(time) => format!("{installed}, {}", age(time)),
+                None => installed.clone(),
+            };
+            push_section(&mut details, "Installed", &installed);
+        }
+        push_section(
+            &mut details,
+            "Caveats",
+            self.caveats.as_deref().unwrap_or_default(),
+        );
+        details
+    }
+}
 
 impl Homebrew {
     /// Display homebrew version
+    #[allow(dead_code)]
     fn brew_version() -> CommandResult {
         command(HOMEBREW_ALIAS, ["--version"])
     }
@@ -76,8 +497,173 @@ impl Homebrew {
     }
 
     /// List installed packages (casks/ formulae)
-    fn brew_list() -> CommandResult {
-        command(HOMEBREW_ALIAS, ["list"])
+    fn brew_list<I>(options: Option<I>) -> CommandResult
+    where
+        I: IntoIterator<Item = ListOption>,
+    {
+        let mut args = vec!["list".to_string()];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: ListOption| option.into()));
+        }
+        command(HOMEBREW_ALIAS, args)
+    }
+
+    /// List the names of the installed casks
+    fn brew_list_casks() -> SpawnCommandResult {
+        spawn_command(
+            HOMEBREW_ALIAS,
+            ["list".to_string(), ListOption::Cask.into()],
+        )
+    }
+
+    /// Prevent the specified formulae from being upgraded
+    fn brew_pin<J>(package_list: J) -> CommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["pin".to_string()];
+        args.extend(package_list);
+        command(HOMEBREW_ALIAS, args)
+    }
+
+    /// Allow the specified formulae to be upgraded again
+    fn brew_unpin<J>(package_list: J) -> CommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["unpin".to_string()];
+        args.extend(package_list);
+        command(HOMEBREW_ALIAS, args)
+    }
+
+    /// List installed formulae that are not dependencies of another
+    /// installed formula
+    fn brew_leaves(options: Option<LeavesOption>) -> SpawnCommandResult {
+        let mut args = vec!["leaves".to_string()];
+        args.extend(options.map(String::from));
+        spawn_command(HOMEBREW_ALIAS, args)
+    }
+
+    /// Show the dependencies of the specified packages
+    fn brew_deps<I, J>(options: Option<I>, package_list: J) -> CommandResult
+    where
+        I: IntoIterator<Item = DepsOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["deps".to_string()];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: DepsOption| option.into()));
+        }
+        args.extend(package_list);
+        command(HOMEBREW_ALIAS, args)
+    }
+
+    /// Show the formulae and casks that depend on the specified packages
+    fn brew_uses<I, J>(options: Option<I>, package_list: J) -> CommandResult
+    where
+        I: IntoIterator<Item = UsesOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["uses".to_string()];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: UsesOption| option.into()));
+        }
+        args.extend(package_list);
+        command(HOMEBREW_ALIAS, args)
+    }
+
+    /// List installed formulae and casks that have a newer
+    /// version available
+    fn brew_outdated<I>(options: Option<I>) -> CommandResult
+    where
+        I: IntoIterator<Item = OutdatedOption>,
+    {
+        let mut args = vec!["outdated".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: OutdatedOption| option.into()),
+            );
+        }
+        command(HOMEBREW_ALIAS, args)
+    }
+
+    /// Show information about the specified taps, or every
+    /// installed tap
+    fn brew_tap_info<I, J>(options: Option<I>, taps: Option<J>) -> CommandResult
+    where
+        I: IntoIterator<Item = TapInfoOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["tap-info".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: TapInfoOption| option.into()),
+            );
+        }
+        if let Some(taps) = taps {
+            args.extend(taps);
+        }
+        command(HOMEBREW_ALIAS, args)
+    }
+
+    /// Tap a formula repository, cloned from GitHub unless a
+    /// remote is specified
+    fn brew_tap(tap: String, remote: Option<String>) -> SpawnCommandResult {
+        let mut args = vec!["tap".to_string(), tap];
+        if let Some(remote) = remote {
+            args.push(remote);
+        }
+        spawn_command(HOMEBREW_ALIAS, args)
+    }
+
+    /// Remove a tapped formula repository
+    fn brew_untap(tap: String) -> SpawnCommandResult {
+        spawn_command(HOMEBREW_ALIAS, ["untap".to_string(), tap])
+    }
+
+    /// Manage background services of formulae with launchctl on
+    /// macOS or systemctl on Linux
+    fn brew_services<I>(
+        subcommand: ServicesSubcommand,
+        options: Option<I>,
+        service: Option<String>,
+    ) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = ServicesOption>,
+    {
+        let mut args = vec!["services".to_string(), subcommand.into()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: ServicesOption| option.into()),
+            );
+        }
+        if let Some(service) = service {
+            args.push(service);
+        }
+        spawn_command(HOMEBREW_ALIAS, args)
+    }
+
+    /// Install, dump or check the taps, packages and services listed
+    /// in a Brewfile
+    fn brew_bundle<I>(subcommand: BundleSubcommand, options: Option<I>) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = BundleOption>,
+    {
+        let mut args = vec!["bundle".to_string(), subcommand.into()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: BundleOption| option.into()),
+            );
+        }
+        spawn_command(HOMEBREW_ALIAS, args)
     }
 
     /// Search homebrew core for specified pattern
@@ -87,20 +673,22 @@ impl Homebrew {
 
     /// Uninstall formulae that were only installed as a dependency
     /// of another formula and are now no longer needed
-    fn brew_autoremove(dry_run: Option<AutoremoveOption>) -> CommandResult {
+    fn brew_autoremove(dry_run: Option<AutoremoveOption>) -> SpawnCommandResult {
         if let Some(arg) = dry_run {
-            command(HOMEBREW_ALIAS, ["autoremove", arg.into()])
+            spawn_command(HOMEBREW_ALIAS, ["autoremove", arg.into()])
         } else {
-            command(HOMEBREW_ALIAS, ["autoremove"])
+            spawn_command(HOMEBREW_ALIAS, ["autoremove"])
         }
     }
     /// List all locally installable casks including short names
+    #[allow(dead_code)]
     fn brew_casks() -> CommandResult {
         command(HOMEBREW_ALIAS, ["casks"])
     }
 
     /// List all locally installable formulae including short
     /// names
+    #[allow(dead_code)]
     fn brew_formulae() -> CommandResult {
         command(HOMEBREW_ALIAS, ["formulae"])
     }
@@ -110,7 +698,7 @@ impl Homebrew {
     /// formulae. If arguments are specified, only do this for
     /// the given formulae and casks. Removes all downloads
     /// more than 120 days old.
-    fn brew_cleanup<I, J>(options: Option<I>, packages: Option<J>) -> CommandResult
+    fn brew_cleanup<I, J>(options: Option<I>, packages: Option<J>) -> SpawnCommandResult
     where
         I: IntoIterator<Item = CleanupOption>,
         J: IntoIterator<Item = String>,
@@ -124,13 +712,14 @@ impl Homebrew {
             );
         };
         if let Some(packages) = packages {
-            args.extend(packages.into_iter());
+            args.extend(packages);
         }
-        command(HOMEBREW_ALIAS, args)
+        spawn_command(HOMEBREW_ALIAS, args)
     }
 
     /// Control whether Homebrew automatically links external
     /// tap shell completion files
+    #[allow(dead_code)]
     fn brew_completions(subcommand: Option<CompletionsSubcommand>) -> CommandResult {
         let mut args = vec!["completions"];
         if let Some(arg) = subcommand {
@@ -145,6 +734,7 @@ impl Homebrew {
         command(HOMEBREW_ALIAS, ["config"])
     }
     /// Display formula’s name and one-line description
+    #[allow(dead_code)]
     fn brew_desc<I, J>(options: Option<I>, query: Option<J>) -> SpawnCommandResult
     where
         I: IntoIterator<Item = DescOption>,
@@ -155,16 +745,17 @@ impl Homebrew {
             args.extend(options.into_iter().map(|option: DescOption| option.into()));
         }
         if let Some(query) = query {
-            args.extend(query.into_iter());
+            args.extend(query);
         }
 
         spawn_command(HOMEBREW_ALIAS, args)
     }
 
     /// Check your system for potential problems
-    fn brew_doctor<I>(options: Option<I>) -> SpawnCommandResult
+    fn brew_doctor<I, J>(options: Option<I>, checks: Option<J>) -> SpawnCommandResult
     where
         I: IntoIterator<Item = DoctorOption>,
+        J: IntoIterator<Item = String>,
     {
         let mut args = vec!["doctor".to_string()];
         if let Some(options) = options {
@@ -174,12 +765,16 @@ impl Homebrew {
                     .map(|option: DoctorOption| option.into()),
             );
         }
+        if let Some(checks) = checks {
+            args.extend(checks);
+        }
 
         spawn_command(HOMEBREW_ALIAS, args)
     }
 
     /// Open a formula or cask’s homepage in a browser, or
     /// open Homebrew’s own homepage if no argument is provided
+    #[allow(dead_code)]
     fn brew_home<I>(options: Option<HomeOption>, query: Option<I>) -> CommandResult
     where
         I: IntoIterator<Item = String>,
@@ -218,6 +813,249 @@ impl Homebrew {
 
         spawn_command(HOMEBREW_ALIAS, args)
     }
+
+    /// Warnings reported by `brew doctor`, running every check unless
+    /// `checks` names some, forwarding each line of output to `tx`
+    fn doctor(
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        checks: Vec<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        // `--debug` announces every check before running it, which tells
+        // the check each warning comes from
+        let child = Self::brew_doctor(Some([DoctorOption::Debug]), Some(checks))
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let output = stream_spawned_command(rx, tx, child)?;
+        let warnings = doctor_warnings(output.err.as_deref().unwrap_or_default());
+        // `brew doctor` exits unsuccessfully whenever it has a warning, so
+        // the exit status only tells a failure when nothing was reported
+        if warnings.is_empty() {
+            output.report(HOMEBREW_ALIAS)?;
+        }
+        Ok(warnings)
+    }
+
+    /// Every installed tap, official or not
+    fn installed_taps() -> Result<Vec<TapInfo>, Error> {
+        let output = checked(
+            HOMEBREW_ALIAS,
+            Self::brew_tap_info::<_, Vec<String>>(
+                Some([TapInfoOption::Installed, TapInfoOption::Json]),
+                None,
+            ),
+        )?;
+        serde_json::from_slice::<Vec<TapInfo>>(&output.stdout)
+            .map_err(|e| Error::parse("brew tap-info", e))
+    }
+
+    /// Installed formulae and casks, parsed from `<name> <version>...`
+    /// lines where the last version is the most recently installed one
+    fn installed_packages() -> Result<Vec<Package>, Error> {
+        // only formulae can be pinned
+        let output = checked(
+            HOMEBREW_ALIAS,
+            Self::brew_list(Some([ListOption::Formula, ListOption::Pinned])),
+        )?;
+        let pinned = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect::<HashSet<String>>();
+        let mut packages = Vec::new();
+        for (kind, option) in [
+            (PackageKind::Formula, ListOption::Formula),
+            (PackageKind::Cask, ListOption::Cask),
+        ] {
+            let output = checked(
+                HOMEBREW_ALIAS,
+                Self::brew_list(Some([option, ListOption::Versions])),
+            )?;
+            packages.extend(
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| {
+                        let mut fields = line.split_whitespace();
+                        let name = fields.next()?;
+                        let version = fields.last().map(|version| version.to_string());
+                        Some(Package {
+                            version: version.clone(),
+                            installed_version: Some(version.unwrap_or_default()),
+                            kind,
+                            pinned: pinned.contains(name),
+                            ..Package::new(name.to_string(), Backend::Homebrew)
+                        })
+                    }),
+            );
+        }
+        Ok(packages)
+    }
+}
+
+/// Stdout of a `brew bundle` dry run, which exits unsuccessfully whenever
+/// the Brewfile is not satisfied. It only failed for real when it reported
+/// an error on stderr
+fn bundle_report(command: &str, output: Output) -> Result<String, Error> {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() && stderr.contains("Error:") {
+        return Err(Error::from_exit(
+            command,
+            output.status.code(),
+            stderr.into_owned(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `brew config` settings that are always reported, as opposed to the
+/// `HOMEBREW_*` environment variables it only reports once they are set
+const CONFIG_DEFAULTS: [&str; 4] = [
+    "HOMEBREW_VERSION",
+    "HOMEBREW_PREFIX",
+    "HOMEBREW_REPOSITORY",
+    "HOMEBREW_CELLAR",
+];
+
+/// Fields of `brew config`, one `<key>: <value>` per line, where tools are
+/// reported as `<version> => <path>` and missing ones as `N/A`
+fn system_info(output: &str) -> SystemInfo {
+    let mut info = SystemInfo::default();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let missing = value.is_empty() || value == "N/A";
+        let version = value.split(" => ").next().unwrap_or(value).to_string();
+        match key {
+            "HOMEBREW_VERSION" | "HOMEBREW_PREFIX" | "CPU" | "macOS" | "OS" | "Git" | "Curl"
+                if missing => {}
+            "HOMEBREW_VERSION" => info.version = Some(value.to_string()),
+            "HOMEBREW_PREFIX" => info.prefix = Some(value.to_string()),
+            "CPU" => info.cpu = Some(value.to_string()),
+            "macOS" => info.os = Some(format!("macOS {value}")),
+            "OS" => info.os = Some(value.to_string()),
+            "Git" => info.git = Some(version),
+            "Curl" => info.curl = Some(version),
+            key if key.starts_with("HOMEBREW_") && !CONFIG_DEFAULTS.contains(&key) => info
+                .env_overrides
+                .push((key.to_string(), value.to_string())),
+            _ => {
+                info.extra.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+    info
+}
+
+/// Characters a shell would interpret, suggested commands containing any
+/// of them are not taken as fixes since fixes run without a shell
+const SHELL_CHARACTERS: [char; 11] = ['<', '>', '|', '&', ';', '$', '`', '"', '\'', '(', '*'];
+
+/// Warnings printed on stderr by `brew doctor --debug`, each starting at a
+/// `Warning:` or `Error:` line and followed by its explanation up to the
+/// next warning or the `==> Checking <check>` line announcing the next check
+fn doctor_warnings(stderr: &str) -> Vec<HealthWarning> {
+    let mut warnings: Vec<HealthWarning> = Vec::new();
+    let mut check = "";
+    // whether the lines that follow explain the last warning
+    let mut explaining = false;
+    for line in stderr.lines() {
+        if let Some(headline) = line.strip_prefix("==> ") {
+            if let Some(name) = headline.strip_prefix("Checking ") {
+                check = name.trim();
+            }
+            explaining = false;
+            continue;
+        }
+        let severity = if let Some(title) = line.strip_prefix("Warning:") {
+            Some((Severity::Warning, title))
+        } else {
+            line.strip_prefix("Error:")
+                .map(|title| (Severity::Error, title))
+        };
+        match (severity, warnings.last_mut()) {
+            (Some((severity, title)), _) => {
+                warnings.push(HealthWarning {
+                    check: check.to_string(),
+                    title: title.trim().to_string(),
+                    severity,
+                    details: String::new(),
+                    fixes: Vec::new(),
+                });
+                explaining = true;
+            }
+            (None, Some(warning)) if explaining => {
+                warning.details.push_str(line);
+                warning.details.push('\n');
+            }
+            // `Please note that these warnings are just used to help the
+            // Homebrew maintainers...` precedes the first warning
+            _ => {}
+        }
+    }
+    for warning in &mut warnings {
+        warning.details = warning.details.trim().to_string();
+        warning.fixes = suggested_fixes(&warning.details);
+    }
+    warnings
+}
+
+/// `brew` commands suggested by the explanation of a warning, either
+/// quoted in backticks or on a line of their own. A quoted command ending a
+/// line with a colon, as in ``Run `brew link` on these:``, applies to the
+/// indented names listed below it. Other commands, such as the ones run with
+/// sudo, are left in the explanation for the user to run
+fn suggested_fixes(details: &str) -> Vec<String> {
+    let is_command = |text: &str| {
+        !text.contains(SHELL_CHARACTERS) && text.split_whitespace().next() == Some(HOMEBREW_ALIAS)
+    };
+    let lines: Vec<&str> = details.lines().collect();
+    let mut fixes: Vec<String> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.starts_with(char::is_whitespace) && is_command(line.trim()) {
+            fixes.push(line.trim().to_string());
+            continue;
+        }
+        for quoted in line.split('`').skip(1).step_by(2) {
+            if !is_command(quoted) {
+                continue;
+            }
+            let mut fix = quoted.to_string();
+            if line.trim_end().ends_with(':') {
+                let names = lines[index + 1..]
+                    .iter()
+                    .take_while(|name| name.starts_with(char::is_whitespace))
+                    .map(|name| name.trim())
+                    .take_while(|name| !name.is_empty())
+                    .collect::<Vec<&str>>();
+                // ``You should `brew install` the missing dependencies:``
+                // lists the complete commands instead of names
+                if names.first().is_some_and(|name| is_command(name)) {
+                    continue;
+                }
+                for name in names {
+                    fix.push(' ');
+                    fix.push_str(name);
+                }
+            }
+            fixes.push(fix);
+        }
+    }
+    let mut seen = HashSet::new();
+    fixes.retain(|fix| seen.insert(fix.clone()));
+    fixes
+}
+
+/// Tap a formula or cask belongs to, given its name as reported by
+/// `brew search`, where only packages outside the official taps are
+/// qualified as `<user>/<repository>/<name>`
+fn tap(package_name: &str, kind: PackageKind) -> String {
+    match package_name.rsplit_once('/') {
+        Some((tap, _name)) => tap.to_string(),
+        None => match kind {
+            PackageKind::Cask => "homebrew/cask".to_string(),
+            _ => "homebrew/core".to_string(),
+        },
+    }
 }
 
 impl PackageManager for Homebrew {
@@ -230,103 +1068,584 @@ impl PackageManager for Homebrew {
         _rx: Receiver<bool>,
         package_locality: super::PackageLocality,
         pattern: String,
-    ) -> Result<Vec<String>, String> {
+    ) -> Result<Vec<Package>, Error> {
         match package_locality {
-            PackageLocality::Local => match Self::brew_list() {
-                Ok(output) => {
-                    let installed_packages = String::from_utf8(output.stdout)
-                        .unwrap()
-                        .split("\n")
-                        .map(|item| item.to_string())
-                        .collect::<Vec<String>>();
-                    let pattern_vec: Vec<_> = pattern.chars().collect();
-                    let threshold = (pattern_vec.len() + 1) * (25 / 100);
-                    Ok(fuzz(installed_packages, pattern, threshold))
+            PackageLocality::Local => {
+                // `brew list` leaves out the tap, which is looked up among the
+                // packages of every installed tap instead
+                let mut taps = HashMap::new();
+                for info in Self::installed_taps()? {
+                    for name in info.formula_names.iter().chain(&info.cask_tokens) {
+                        let short_name = name.rsplit('/').next().unwrap_or(name);
+                        taps.entry(short_name.to_string())
+                            .or_insert_with(|| info.name.clone());
+                    }
                 }
-                Err(e) => Err(format!("failed to execute command brew list: {e}")),
-            },
-            PackageLocality::Remote => match Self::brew_search(pattern) {
-                Ok(output) => Ok(String::from_utf8(output.stdout)
-                    .unwrap()
-                    .split("\n")
-                    .filter_map(|item| {
-                        if item.is_empty() {
-                            None
-                        } else {
-                            Some(item.to_string())
-                        }
+                let packages = Self::installed_packages()?
+                    .into_iter()
+                    .map(|package| Package {
+                        repository: Some(
+                            taps.get(&package.name)
+                                .cloned()
+                                .unwrap_or_else(|| tap(&package.name, package.kind)),
+                        ),
+                        ..package
                     })
-                    .collect::<Vec<String>>()),
-                Err(e) => Err(format!("failed to execute command brew list: {e}")),
-            },
+                    .collect();
+                Ok(fuzz_packages(packages, pattern))
+            }
+            PackageLocality::Remote => {
+                let installed = Self::installed_packages()?
+                    .into_iter()
+                    .map(|package| (package.name.clone(), package))
+                    .collect::<HashMap<String, Package>>();
+                let output = checked_search(
+                    HOMEBREW_ALIAS,
+                    Self::brew_search(pattern.clone()),
+                    &["No formulae or casks found"],
+                )?;
+                // formulae and casks are listed under `==> Formulae` and
+                // `==> Casks` headings respectively
+                let mut kind = PackageKind::Formula;
+                let mut packages = Vec::new();
+                for line in String::from_utf8_lossy(&output.stdout).lines() {
+                    let line = line.trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    if let Some(heading) = line.strip_prefix("==>") {
+                        kind = match heading.trim() {
+                            "Casks" => PackageKind::Cask,
+                            _ => PackageKind::Formula,
+                        };
+                        continue;
+                    }
+                    let short_name = line.rsplit('/').next().unwrap_or(line);
+                    let installed = installed.get(short_name);
+                    packages.push(Package {
+                        installed_version: installed
+                            .and_then(|package| package.installed_version.clone()),
+                        pinned: installed.is_some_and(|package| package.pinned),
+                        kind,
+                        repository: Some(tap(line, kind)),
+                        ..Package::new(line.to_string(), Backend::Homebrew)
+                    });
+                }
+                Ok(rank_packages(packages, &pattern))
+            }
         }
     }
 
-    fn package_manager_config(&self, rx: Receiver<bool>) -> Result<String, String> {
-        match Self::brew_config() {
-            Ok(output) => Ok(String::from_utf8(output.stdout).unwrap()),
-            Err(e) => Err(format!("{e}")),
+    fn outdated_packages(&self, _rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        let output = checked(
+            HOMEBREW_ALIAS,
+            Self::brew_outdated(Some([OutdatedOption::JsonV2])),
+        )?;
+        let outdated = serde_json::from_slice::<Outdated>(&output.stdout)
+            .map_err(|e| Error::parse("brew outdated", e))?;
+        Ok(outdated
+            .formulae
+            .into_iter()
+            .map(|package| (PackageKind::Formula, package))
+            .chain(
+                outdated
+                    .casks
+                    .into_iter()
+                    .map(|package| (PackageKind::Cask, package)),
+            )
+            .map(|(kind, package)| Package {
+                version: Some(package.current_version),
+                // the most recently installed version is listed last
+                installed_version: package.installed_versions.last().cloned(),
+                kind,
+                repository: Some(tap(&package.name, kind)),
+                pinned: package.pinned,
+                ..Package::new(package.name, Backend::Homebrew)
+            })
+            .collect())
+    }
+
+    fn requested_packages(&self, rx: Receiver<bool>) -> Result<Vec<String>, Error> {
+        let cancellation = Cancellation::forward(rx);
+        let rx_leaves = cancellation.receiver().ok_or(Error::Cancelled)?;
+        let child = Self::brew_leaves(Some(LeavesOption::InstalledOnRequest))
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let leaves = handle_spawned_command(rx_leaves, child)?
+            .success(HOMEBREW_ALIAS)?
+            .out
+            .unwrap_or_default();
+        // casks are never installed as dependencies
+        let rx_casks = cancellation.receiver().ok_or(Error::Cancelled)?;
+        let child = Self::brew_list_casks().map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let casks = handle_spawned_command(rx_casks, child)?
+            .success(HOMEBREW_ALIAS)?
+            .out
+            .unwrap_or_default();
+        Ok(leaves
+            .lines()
+            .chain(casks.lines())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn package_manager_config(&self, _rx: Receiver<bool>) -> Result<String, Error> {
+        let output = checked(HOMEBREW_ALIAS, Self::brew_config())?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn system_info(&self, _rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        let output = checked(HOMEBREW_ALIAS, Self::brew_config())?;
+        Ok(system_info(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let child = Self::brew_info(Some([InfoOption::JsonV2]), Some([package_name]))
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let out = handle_spawned_command(rx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .out
+            .unwrap_or_default();
+        let info = serde_json::from_str::<Info>(&out).map_err(|e| Error::parse("brew info", e))?;
+        Ok(info
+            .formulae
+            .iter()
+            .map(FormulaInfo::details)
+            .chain(info.casks.iter().map(CaskInfo::details))
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+    fn dependencies(
+        &self,
+        _rx: Receiver<bool>,
+        package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        let output = checked(
+            HOMEBREW_ALIAS,
+            Self::brew_deps(Some([DepsOption::Tree]), [package_name]),
+        )?;
+        Ok(dependency_tree(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .flat_map(|package| package.dependencies)
+            .collect())
+    }
+
+    fn dependents(&self, _rx: Receiver<bool>, package_name: String) -> Result<Vec<String>, Error> {
+        let output = checked(
+            HOMEBREW_ALIAS,
+            Self::brew_uses(Some([UsesOption::Installed]), [package_name]),
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(|name| name.to_string())
+            .collect())
+    }
+
+    fn dependency_graph(&self, rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        let child = Self::brew_info::<_, Vec<String>>(
+            Some([InfoOption::JsonV2, InfoOption::Installed]),
+            None,
+        )
+        .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let out = handle_spawned_command(rx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .out
+            .unwrap_or_default();
+        let info = serde_json::from_str::<Info>(&out).map_err(|e| Error::parse("brew info", e))?;
+
+        let mut graph = DependencyGraph::default();
+        for formula in info.formulae {
+            let edges = formula
+                .dependencies
+                .into_iter()
+                .map(|dependency| (dependency, DependencyKind::Runtime))
+                .chain(
+                    formula
+                        .build_dependencies
+                        .into_iter()
+                        .map(|dependency| (dependency, DependencyKind::Build)),
+                )
+                .map(|(to, kind)| GraphEdge {
+                    from: formula.full_name.clone(),
+                    to,
+                    kind,
+                });
+            graph.edges.extend(edges);
+            graph.nodes.push(GraphNode {
+                // the most recently installed version is listed last
+                version: formula
+                    .installed
+                    .last()
+                    .map(|receipt| receipt.version.clone()),
+                name: formula.full_name,
+            });
+        }
+        for cask in info.casks {
+            let edges = cask
+                .depends_on
+                .formula
+                .into_iter()
+                .chain(cask.depends_on.cask)
+                .map(|to| GraphEdge {
+                    from: cask.full_token.clone(),
+                    to,
+                    kind: DependencyKind::Runtime,
+                });
+            graph.edges.extend(edges);
+            graph.nodes.push(GraphNode {
+                name: cask.full_token,
+                version: cask.installed,
+            });
         }
+        // build dependencies are often removed once they are no longer needed
+        graph.retain_installed();
+        Ok(graph)
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::brew_doctor::<Vec<DoctorOption>, Vec<String>>(None, None)
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        // brew doctor prints its warnings on stderr
+        stream_spawned_command(rx, tx, child)?.report(HOMEBREW_ALIAS)
+    }
+
+    fn health_warnings(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Self::doctor(rx, tx, Vec::new())
+    }
+
+    fn run_health_check(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Self::doctor(rx, tx, vec![check])
     }
+    fn clean_preview(&self, rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        let cancellation = Cancellation::forward(rx);
+        let mut preview = CleanupPreview::default();
 
-    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, String> {
-        let child = match Self::brew_info(Some([InfoOption::Json]), Some([package_name])) {
-            Ok(child) => child,
-            Err(e) => return Err(format!("{e}")),
+        // `Would remove: <path> (<size>)` for every file, followed by
+        // `==> This operation would free approximately <size> of disk space.`
+        let Some(rx_cleanup) = cancellation.receiver() else {
+            return Err(Error::Cancelled);
         };
-        match handle_spawned_command(rx, child) {
-            Some(output) => Ok(output.out.unwrap()),
-            None => Err("could not execute command".to_string()),
+        let child = Self::brew_cleanup::<_, Vec<String>>(Some([CleanupOption::DryRun]), None)
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let out = handle_spawned_command(rx_cleanup, child)?
+            .success(HOMEBREW_ALIAS)?
+            .out
+            .unwrap_or_default();
+        for line in out.lines() {
+            if let Some(file) = line.strip_prefix("Would remove: ") {
+                preview.files.push(file.trim().to_string());
+            } else if let Some((_, freed)) = line.split_once("would free approximately ") {
+                preview.freed = freed
+                    .split_once(" of disk space")
+                    .map(|(freed, _)| freed.to_string());
+            }
         }
+
+        // `==> Would autoremove <n> unneeded formulae:` followed by one
+        // formula per line
+        let Some(rx_autoremove) = cancellation.receiver() else {
+            return Err(Error::Cancelled);
+        };
+        let child = Self::brew_autoremove(Some(AutoremoveOption::DryRun))
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let out = handle_spawned_command(rx_autoremove, child)?
+            .success(HOMEBREW_ALIAS)?
+            .out
+            .unwrap_or_default();
+        preview.packages.extend(
+            out.lines()
+                .skip_while(|line| !line.starts_with("==> Would autoremove"))
+                .skip(1)
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty() && !line.starts_with("==>"))
+                .map(|line| line.to_string()),
+        );
+        Ok(preview)
     }
-    fn check_health(&self, rx: Receiver<bool>) -> Result<String, String> {
-        let child = match Self::brew_doctor::<Vec<DoctorOption>>(None) {
-            Ok(child) => child,
-            Err(e) => return Err(format!("{e}")),
+
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let cancellation = Cancellation::forward(rx);
+        let Some(rx_cleanup) = cancellation.receiver() else {
+            return Err(Error::Cancelled);
         };
-        match handle_spawned_command(rx, child) {
-            Some(output) => Ok(output.err.unwrap()),
-            None => Err("could not execute command".to_string()),
-        }
+        let child = Self::brew_cleanup::<Vec<CleanupOption>, Vec<String>>(None, None)
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let cleanup = stream_spawned_command(rx_cleanup, tx.clone(), child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined();
+        let Some(rx_autoremove) = cancellation.receiver() else {
+            return Err(Error::Cancelled);
+        };
+        let child = Self::brew_autoremove(None).map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let autoremove = stream_spawned_command(rx_autoremove, tx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined();
+        Ok(format!("{cleanup}{autoremove}"))
     }
-    fn clean(&self, rx: Receiver<bool>) -> Result<String, String> {
-        match Self::brew_cleanup::<Vec<CleanupOption>, Vec<String>>(None, None) {
-            Ok(output) => Ok(String::from_utf8(output.stdout).unwrap()),
-            Err(e) => Err(format!("{e}")),
-        }
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::brew_install::<Vec<InstallOption>, _>(None, [package_name])
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined())
+    }
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::brew_upgrade(Some([package_name]))
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined())
+    }
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::brew_uninstall::<Vec<UninstallOption>, _>(None, [package_name])
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined())
+    }
+
+    fn pin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        // brew prints nothing when it succeeds
+        checked(HOMEBREW_ALIAS, Self::brew_pin([package_name.clone()]))?;
+        Ok(format!("Pinned {package_name}"))
+    }
+
+    fn unpin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        checked(HOMEBREW_ALIAS, Self::brew_unpin([package_name.clone()]))?;
+        Ok(format!("Unpinned {package_name}"))
+    }
+
+    fn taps(&self, _rx: Receiver<bool>) -> Result<Vec<Tap>, Error> {
+        Ok(Self::installed_taps()?
+            .into_iter()
+            .map(|info| Tap {
+                formulae: info.formula_names.len(),
+                casks: info.cask_tokens.len(),
+                name: info.name,
+                remote: info.remote,
+                last_updated: info.last_commit,
+            })
+            .collect())
+    }
+
+    fn add_tap(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        tap: String,
+    ) -> Result<String, Error> {
+        // a custom remote may follow the name of the tap
+        let mut fields = tap.split_whitespace().map(|field| field.to_string());
+        let name = fields.next().unwrap_or_default();
+        let child =
+            Self::brew_tap(name, fields.next()).map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined())
     }
-    fn install_package(&self, rx: Receiver<bool>, package_name: String) -> Result<(), String> {
-        let child = match Self::brew_install::<Vec<InstallOption>, _>(None, [package_name]) {
-            Ok(child) => child,
-            Err(e) => return Err(format!("{e}")),
-        };
 
-        match handle_spawned_command(rx, child) {
-            Some(_) => Ok(()),
-            None => Err("could not execute command".to_string()),
+    fn remove_tap(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        tap: String,
+    ) -> Result<String, Error> {
+        let child = Self::brew_untap(tap).map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined())
+    }
+
+    fn services(&self, rx: Receiver<bool>) -> Result<Vec<Service>, Error> {
+        let child =
+            Self::brew_services(ServicesSubcommand::List, Some([ServicesOption::Json]), None)
+                .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let out = handle_spawned_command(rx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .out
+            .unwrap_or_default();
+        // nothing is printed at all when no formula has a service
+        if out.trim().is_empty() {
+            return Ok(Vec::new());
         }
+        let services = serde_json::from_str::<Vec<ServiceInfo>>(&out)
+            .map_err(|e| Error::parse("brew services list", e))?;
+        Ok(services
+            .into_iter()
+            .map(|service| Service {
+                name: service.name,
+                status: service.status,
+                user: service.user,
+                file: service.file,
+            })
+            .collect())
     }
-    fn update_package(&self, rx: Receiver<bool>, package_name: String) -> Result<(), String> {
-        let child = match Self::brew_upgrade(Some([package_name])) {
-            Ok(child) => child,
-            Err(e) => return Err(format!("{e}")),
+
+    fn control_service(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        action: ServiceAction,
+        service: String,
+    ) -> Result<String, Error> {
+        let subcommand = match action {
+            ServiceAction::Start => ServicesSubcommand::Start,
+            ServiceAction::Stop => ServicesSubcommand::Stop,
+            ServiceAction::Restart => ServicesSubcommand::Restart,
+            ServiceAction::Run => ServicesSubcommand::Run,
         };
+        let child = Self::brew_services::<Vec<ServicesOption>>(subcommand, None, Some(service))
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
 
-        match handle_spawned_command(rx, child) {
-            Some(_) => Ok(()),
-            None => Err("could not execute command".to_string()),
-        }
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined())
+    }
+
+    fn cleanup_services(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child =
+            Self::brew_services::<Vec<ServicesOption>>(ServicesSubcommand::Cleanup, None, None)
+                .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined())
     }
-    fn uninstall_package(&self, rx: Receiver<bool>, package_name: String) -> Result<(), String> {
-        let child = match Self::brew_uninstall::<Vec<UninstallOption>, _>(None, [package_name]) {
-            Ok(child) => child,
-            Err(e) => return Err(format!("{e}")),
+
+    fn export_brewfile(&self, rx: Receiver<bool>, path: String) -> Result<String, Error> {
+        // running services are dumped as `restart_service` on their formula
+        let child = Self::brew_bundle(
+            BundleSubcommand::Dump,
+            Some([BundleOption::File(path.clone()), BundleOption::Force]),
+        )
+        .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        handle_spawned_command(rx, child)?.success(HOMEBREW_ALIAS)?;
+
+        let brewfile = fs::read_to_string(&path).map_err(|e| Error::Io {
+            command: "brew bundle dump".to_string(),
+            reason: format!("{path}: {e}"),
+        })?;
+        let count = |kind: &str| {
+            brewfile
+                .lines()
+                .filter(|line| line.starts_with(&format!("{kind} ")))
+                .count()
         };
+        let services = brewfile
+            .lines()
+            .filter(|line| line.contains("restart_service"))
+            .count();
+        Ok(format!(
+            "{} taps, {} formulae, {} casks, {services} services\n",
+            count("tap"),
+            count("brew"),
+            count("cask"),
+        ))
+    }
+
+    fn brewfile_plan(&self, _rx: Receiver<bool>, path: String) -> Result<BrewfilePlan, Error> {
+        let mut plan = BrewfilePlan::default();
+
+        // `→ Formula wget needs to be installed or updated.` for every entry
+        // that is missing, and `→ Service postgresql needs to be started.`
+        // for every service that is not running
+        let output = Self::brew_bundle(
+            BundleSubcommand::Check,
+            Some([
+                BundleOption::File(path.clone()),
+                BundleOption::Verbose,
+                BundleOption::NoUpgrade,
+            ]),
+        )
+        .and_then(|child| child.wait_with_output())
+        .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        for line in bundle_report("brew bundle check", output)?.lines() {
+            let Some(entry) = line.trim().strip_prefix("→ ") else {
+                continue;
+            };
+            let Some((kind, name)) = entry
+                .split_once(" needs to be ")
+                .and_then(|(subject, _)| subject.split_once(' '))
+            else {
+                continue;
+            };
+            plan.install.push(format!("{} {name}", kind.to_lowercase()));
+        }
 
-        match handle_spawned_command(rx, child) {
-            Some(_) => Ok(()),
-            None => Err("could not execute command".to_string()),
+        // `Would uninstall formulae:`, `Would uninstall casks:` and
+        // `Would untap:` headings followed by the names, possibly in
+        // several columns
+        let output = Self::brew_bundle(BundleSubcommand::Cleanup, Some([BundleOption::File(path)]))
+            .and_then(|child| child.wait_with_output())
+            .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+        let mut kind = None;
+        for line in bundle_report("brew bundle cleanup", output)?.lines() {
+            let line = line.trim();
+            if let Some(heading) = line.strip_prefix("Would ") {
+                // `brew cleanup` removes stale downloads, not packages
+                kind = match heading.trim_end_matches(':') {
+                    "uninstall formulae" => Some("formula"),
+                    "uninstall casks" => Some("cask"),
+                    "untap" => Some("tap"),
+                    _ => None,
+                };
+                continue;
+            }
+            if line.starts_with("Run ") {
+                kind = None;
+                continue;
+            }
+            if let Some(kind) = kind {
+                plan.uninstall
+                    .extend(line.split_whitespace().map(|name| format!("{kind} {name}")));
+            }
         }
+        Ok(plan)
+    }
+
+    fn import_brewfile(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        path: String,
+    ) -> Result<String, Error> {
+        let child = Self::brew_bundle(
+            BundleSubcommand::Install,
+            Some([BundleOption::File(path), BundleOption::Cleanup]),
+        )
+        .map_err(|e| Error::from_io(HOMEBREW_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(HOMEBREW_ALIAS)?
+            .combined())
     }
 }
 
@@ -342,6 +1661,56 @@ impl From<AutoremoveOption> for &'static str {
     }
 }
 
+pub enum BundleOption {
+    /// Read from or write to the Brewfile at the given path
+    File(String),
+    /// Overwrite an existing Brewfile when dumping, or uninstall when
+    /// cleaning up
+    Force,
+    Verbose,
+    /// Do not count outdated packages as missing
+    NoUpgrade,
+    /// Uninstall everything the Brewfile leaves out after installing
+    Cleanup,
+    /// Add the description of every package as a comment
+    Describe,
+}
+
+impl From<BundleOption> for String {
+    fn from(value: BundleOption) -> Self {
+        match value {
+            BundleOption::File(path) => format!("--file={path}"),
+            BundleOption::Force => "--force".to_string(),
+            BundleOption::Verbose => "--verbose".to_string(),
+            BundleOption::NoUpgrade => "--no-upgrade".to_string(),
+            BundleOption::Cleanup => "--cleanup".to_string(),
+            BundleOption::Describe => "--describe".to_string(),
+        }
+    }
+}
+
+pub enum BundleSubcommand {
+    Install,
+    Dump,
+    Check,
+    /// Uninstall everything the Brewfile leaves out, or only list it
+    /// unless forced
+    Cleanup,
+    List,
+}
+
+impl From<BundleSubcommand> for String {
+    fn from(value: BundleSubcommand) -> Self {
+        match value {
+            BundleSubcommand::Install => "install".to_string(),
+            BundleSubcommand::Dump => "dump".to_string(),
+            BundleSubcommand::Check => "check".to_string(),
+            BundleSubcommand::Cleanup => "cleanup".to_string(),
+            BundleSubcommand::List => "list".to_string(),
+        }
+    }
+}
+
 pub enum CleanupOption {
     Prune,
     DryRun,
@@ -374,6 +1743,38 @@ impl From<CompletionsSubcommand> for &'static str {
     }
 }
 
+pub enum DepsOption {
+    /// Show dependencies as a tree
+    Tree,
+    /// Only list dependencies that are installed, or the dependencies of
+    /// every installed package when none is specified
+    Installed,
+    /// Only list direct dependencies
+    Direct,
+    IncludeBuild,
+    IncludeTest,
+    IncludeOptional,
+    SkipRecommended,
+    Formula,
+    Cask,
+}
+
+impl From<DepsOption> for String {
+    fn from(value: DepsOption) -> Self {
+        match value {
+            DepsOption::Tree => "--tree".to_string(),
+            DepsOption::Installed => "--installed".to_string(),
+            DepsOption::Direct => "--direct".to_string(),
+            DepsOption::IncludeBuild => "--include-build".to_string(),
+            DepsOption::IncludeTest => "--include-test".to_string(),
+            DepsOption::IncludeOptional => "--include-optional".to_string(),
+            DepsOption::SkipRecommended => "--skip-recommended".to_string(),
+            DepsOption::Formula => "--formula".to_string(),
+            DepsOption::Cask => "--cask".to_string(),
+        }
+    }
+}
+
 pub enum DescOption {
     Search,
     Name,
@@ -399,6 +1800,7 @@ impl From<DescOption> for String {
 pub enum DoctorOption {
     ListChecks,
     AuditDebug,
+    Debug,
 }
 
 impl From<DoctorOption> for String {
@@ -406,6 +1808,7 @@ impl From<DoctorOption> for String {
         match value {
             DoctorOption::ListChecks => "--list-checks".to_string(),
             DoctorOption::AuditDebug => "--audit-debug".to_string(),
+            DoctorOption::Debug => "--debug".to_string(),
         }
     }
 }
@@ -431,6 +1834,8 @@ pub enum InfoOption {
     Github,
     FetchManifest,
     Json,
+    /// Version 2 of the JSON output, the only one that covers casks
+    JsonV2,
     Installed,
     EvalAll,
     Variations,
@@ -448,6 +1853,7 @@ impl From<InfoOption> for String {
             InfoOption::Github => "--github".to_string(),
             InfoOption::FetchManifest => "--fetch-manifest".to_string(),
             InfoOption::Json => "--json".to_string(),
+            InfoOption::JsonV2 => "--json=v2".to_string(),
             InfoOption::Installed => "--installed".to_string(),
             InfoOption::EvalAll => "--eval-all".to_string(),
             InfoOption::Variations => "--variations".to_string(),
@@ -534,6 +1940,118 @@ impl From<InstallOption> for String {
     }
 }
 
+pub enum ListOption {
+    Formula,
+    Cask,
+    Versions,
+    Pinned,
+    InstalledOnRequest,
+    InstalledAsDependency,
+}
+
+impl From<ListOption> for String {
+    fn from(value: ListOption) -> Self {
+        match value {
+            ListOption::Formula => "--formula".to_string(),
+            ListOption::Cask => "--cask".to_string(),
+            ListOption::Versions => "--versions".to_string(),
+            ListOption::Pinned => "--pinned".to_string(),
+            ListOption::InstalledOnRequest => "--installed-on-request".to_string(),
+            ListOption::InstalledAsDependency => "--installed-as-dependency".to_string(),
+        }
+    }
+}
+
+pub enum LeavesOption {
+    /// Only list leaves that were manually installed
+    InstalledOnRequest,
+}
+
+impl From<LeavesOption> for String {
+    fn from(value: LeavesOption) -> Self {
+        match value {
+            LeavesOption::InstalledOnRequest => "--installed-on-request".to_string(),
+        }
+    }
+}
+
+pub enum OutdatedOption {
+    Formula,
+    Cask,
+    JsonV2,
+    /// Include casks that update themselves
+    Greedy,
+}
+
+impl From<OutdatedOption> for String {
+    fn from(value: OutdatedOption) -> Self {
+        match value {
+            OutdatedOption::Formula => "--formula".to_string(),
+            OutdatedOption::Cask => "--cask".to_string(),
+            OutdatedOption::JsonV2 => "--json=v2".to_string(),
+            OutdatedOption::Greedy => "--greedy".to_string(),
+        }
+    }
+}
+
+pub enum ServicesOption {
+    Json,
+    /// Run the subcommand on every service
+    All,
+}
+
+impl From<ServicesOption> for String {
+    fn from(value: ServicesOption) -> Self {
+        match value {
+            ServicesOption::Json => "--json".to_string(),
+            ServicesOption::All => "--all".to_string(),
+        }
+    }
+}
+
+pub enum ServicesSubcommand {
+    List,
+    Info,
+    /// Run the service without registering it to start at login
+    Run,
+    Start,
+    Stop,
+    Kill,
+    Restart,
+    /// Remove the services of formulae that are no longer installed
+    Cleanup,
+}
+
+impl From<ServicesSubcommand> for String {
+    fn from(value: ServicesSubcommand) -> Self {
+        match value {
+            ServicesSubcommand::List => "list".to_string(),
+            ServicesSubcommand::Info => "info".to_string(),
+            ServicesSubcommand::Run => "run".to_string(),
+            ServicesSubcommand::Start => "start".to_string(),
+            ServicesSubcommand::Stop => "stop".to_string(),
+            ServicesSubcommand::Kill => "kill".to_string(),
+            ServicesSubcommand::Restart => "restart".to_string(),
+            ServicesSubcommand::Cleanup => "cleanup".to_string(),
+        }
+    }
+}
+
+pub enum TapInfoOption {
+    /// Show information on every installed tap
+    Installed,
+    Json,
+}
+
+impl From<TapInfoOption> for String {
+    fn from(value: TapInfoOption) -> Self {
+        match value {
+            TapInfoOption::Installed => "--installed".to_string(),
+            TapInfoOption::Json => "--json".to_string(),
+        }
+    }
+}
+
 pub enum UninstallOption {
     Force,
     Zap,
@@ -553,3 +2071,218 @@ impl From<UninstallOption> for String {
         }
     }
 }
+
+pub enum UsesOption {
+    /// Only list installed formulae and casks
+    Installed,
+    /// Also list packages that depend on it indirectly
+    Recursive,
+    IncludeBuild,
+    IncludeTest,
+    IncludeOptional,
+    SkipRecommended,
+}
+
+impl From<UsesOption> for String {
+    fn from(value: UsesOption) -> Self {
+        match value {
+            UsesOption::Installed => "--installed".to_string(),
+            UsesOption::Recursive => "--recursive".to_string(),
+            UsesOption::IncludeBuild => "--include-build".to_string(),
+            UsesOption::IncludeTest => "--include-test".to_string(),
+            UsesOption::IncludeOptional => "--include-optional".to_string(),
+            UsesOption::SkipRecommended => "--skip-recommended".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    #[test]
+    fn formula_and_cask_details() {
+        let info = serde_json::from_str::<Info>(include_str!("fixtures/brew_info.json")).unwrap();
+        assert_eq!(
+            info.formulae[0].details(),
+            format!(
+                "wget
+Internet file retriever
+https://www.gnu.org/software/wget/
+==> Overview
+Tap: homebrew/core
+License: GPL-3.0-or-later
+Stable: 1.24.5 (bottled)
+HEAD: HEAD
+Status: installed
+==> Bottles
+arm64_sonoma, sonoma, x86_64_linux
+==> Dependencies
+Required: libidn2, openssl@3
+Build: pkgconf
+==> Installed
+1.24.5 (poured from bottle, installed on request), {}
+  libunistring 1.2
+  libidn2 2.3.7
+  openssl@3 3.3.1
+",
+                age(1717413322)
+            )
+        );
+        assert_eq!(
+            info.casks[0].details(),
+            "firefox (Mozilla Firefox)
+Web browser
+https://www.mozilla.org/firefox/
+==> Overview
+Tap: homebrew/cask
+Version: 127.0
+Updates itself
+==> Conflicts
+Conflicts with: firefox@beta, firefox@cn
+"
+        );
+    }
+
+    #[test]
+    fn details_without_optional_fields() {
+        let info = serde_json::from_str::<Info>(
+            r#"{"formulae": [{"full_name": "wget"}], "casks": [{"full_token": "firefox"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            info.formulae[0].details(),
+            "wget\n==> Bottles\nNo bottles, builds from source\n"
+        );
+        assert_eq!(info.casks[0].details(), "firefox\n");
+    }
+
+    #[test]
+    fn doctor_warnings_by_check() {
+        let warnings = doctor_warnings(include_str!("fixtures/brew_doctor.txt"));
+        let checks = warnings
+            .iter()
+            .map(|warning| warning.check.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            checks,
+            [
+                "check_access_directories",
+                "check_for_unlinked_but_not_keg_only",
+                "check_brew_git_origin",
+                "check_missing_deps",
+            ]
+        );
+        assert_eq!(warnings[1].title, "You have unlinked kegs in your Cellar.");
+        assert_eq!(warnings[1].severity, Severity::Warning);
+        // the preamble and the next check are not part of any explanation
+        assert!(warnings[0].details.starts_with("/usr/local/share/man/man8"));
+        assert!(
+            warnings[0]
+                .details
+                .ends_with("chmod u+w /usr/local/share/man/man8")
+        );
+        assert!(
+            warnings[3]
+                .details
+                .ends_with("Run `brew missing` for more details.")
+        );
+    }
+
+    #[test]
+    fn suggested_brew_fixes() {
+        let warnings = doctor_warnings(include_str!("fixtures/brew_doctor.txt"));
+        let fixes = warnings
+            .iter()
+            .map(|warning| warning.fixes.clone())
+            .collect::<Vec<Vec<String>>>();
+        assert_eq!(
+            fixes,
+            [
+                // sudo, chmod and git are left for the user to run
+                vec![],
+                vec!["brew link python@3.12 node".to_string()],
+                vec![],
+                vec![
+                    "brew install libidn2".to_string(),
+                    "brew missing".to_string(),
+                ],
+            ]
+        );
+        assert_eq!(
+            suggested_fixes("Run `brew reinstall $(brew list)` or `brew cleanup`"),
+            ["brew cleanup"]
+        );
+    }
+
+    #[test]
+    fn brew_config() {
+        let info = system_info(include_str!("fixtures/brew_config.txt"));
+        assert_eq!(info.version.as_deref(), Some("4.3.5"));
+        assert_eq!(info.prefix.as_deref(), Some("/opt/homebrew"));
+        assert_eq!(
+            info.cpu.as_deref(),
+            Some("deca-core 64-bit arm_firestorm_icestorm")
+        );
+        assert_eq!(info.os.as_deref(), Some("macOS 14.5-arm64"));
+        assert_eq!(info.git.as_deref(), Some("2.39.3"));
+        assert_eq!(info.curl.as_deref(), Some("8.6.0"));
+        assert_eq!(
+            info.env_overrides,
+            [
+                ("HOMEBREW_CASK_OPTS".to_string(), "[]".to_string()),
+                ("HOMEBREW_MAKE_JOBS".to_string(), "10".to_string()),
+                ("HOMEBREW_NO_ANALYTICS".to_string(), "set".to_string()),
+                ("HOMEBREW_SORBET_RUNTIME".to_string(), "set".to_string()),
+            ]
+        );
+        // values containing colons are kept whole
+        assert_eq!(
+            info.extra.get("ORIGIN").map(String::as_str),
+            Some("https://github.com/Homebrew/brew")
+        );
+        assert_eq!(
+            info.extra.get("Core tap JSON").map(String::as_str),
+            Some("18 Jun 09:12 UTC")
+        );
+        assert_eq!(info.extra.get("Xcode").map(String::as_str), Some("N/A"));
+        assert_eq!(
+            info.extra.keys().map(String::as_str).collect::<Vec<&str>>(),
+            [
+                "CLT",
+                "Clang",
+                "Core cask tap JSON",
+                "Core tap JSON",
+                "HEAD",
+                "Homebrew Ruby",
+                "Last commit",
+                "ORIGIN",
+                "Rosetta 2",
+                "Xcode",
+            ]
+        );
+    }
+
+    #[test]
+    fn unreadable_brewfile_export() {
+        let path = StubPath::isolated();
+        // brew bundle dump succeeding without writing the Brewfile
+        path.stub("brew", "exit 0");
+        let brewfile = std::env::temp_dir()
+            .join(format!(
+                "wherehouse-missing-{}.Brewfile",
+                std::process::id()
+            ))
+            .display()
+            .to_string();
+        let (_tx, rx) = mpsc::channel();
+        assert!(matches!(
+            Homebrew.export_brewfile(rx, brewfile.clone()),
+            Err(Error::Io { command, reason })
+                if command == "brew bundle dump" && reason.starts_with(&brewfile)
+        ));
+    }
+}
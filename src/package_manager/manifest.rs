@@ -0,0 +1,443 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::Backend;
+
+/// Packages requested per backend, each along with a version constraint
+/// as understood by `satisfies`
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub packages: BTreeMap<Backend, BTreeMap<String, String>>,
+}
+
+/// Exact versions the packages of a manifest were installed at when the
+/// machine was last snapshotted
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub packages: BTreeMap<Backend, BTreeMap<String, String>>,
+}
+
+/// What a backend has installed
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Installed {
+    /// Every installed package along with its installed version
+    pub versions: BTreeMap<String, String>,
+    /// Installed packages the user asked for, or every installed package
+    /// when the backend keeps no record of that
+    pub requested: BTreeSet<String>,
+    /// Whether `requested` comes from the backend's record of what was
+    /// installed on request. Nothing is uninstalled otherwise, since the
+    /// other packages may well be dependencies
+    pub recorded: bool,
+}
+
+/// A package installed at another version than expected
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VersionChange {
+    pub backend: Backend,
+    pub name: String,
+    /// Version constraint of the manifest, or version of the lockfile
+    pub expected: String,
+    pub installed: String,
+}
+
+/// An upgrade to a version satisfying the constraint of the manifest
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Upgrade {
+    pub backend: Backend,
+    pub name: String,
+    pub installed: String,
+    pub version: String,
+}
+
+/// Difference between a manifest and what is installed
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct ManifestDiff {
+    /// Packages of the manifest that are not installed
+    pub missing: Vec<(Backend, String)>,
+    /// Installed packages of the manifest whose version does not satisfy
+    /// their constraint
+    pub unsatisfied: Vec<VersionChange>,
+    /// Installed packages of the lockfile at another version than locked
+    pub drifted: Vec<VersionChange>,
+    /// Unsatisfied packages whose latest version satisfies their
+    /// constraint, see `plan_upgrades`
+    pub upgrades: Vec<Upgrade>,
+    /// Packages installed on request that the manifest leaves out, only
+    /// for the backends it lists
+    pub extra: Vec<(Backend, String)>,
+    /// Backends of the manifest that are not available on this machine
+    pub unavailable: Vec<Backend>,
+    /// Backends of the manifest keeping no record of the packages installed
+    /// on request, which are never looked at for extra packages
+    pub unrecorded: Vec<Backend>,
+}
+
+impl Manifest {
+    /// Manifest and lockfile of the packages installed on purpose, keeping
+    /// the constraints `previous` has for the packages it already lists
+    pub fn snapshot(
+        installed: &BTreeMap<Backend, Installed>,
+        previous: Option<&Manifest>,
+    ) -> (Manifest, Lockfile) {
+        let mut manifest = Manifest::default();
+        let mut lockfile = Lockfile::default();
+        for (backend, installed) in installed {
+            let constraints = previous.and_then(|previous| previous.packages.get(backend));
+            let packages = installed
+                .requested
+                .iter()
+                .map(|name| {
+                    let constraint = constraints
+                        .and_then(|constraints| constraints.get(name))
+                        .cloned()
+                        .unwrap_or_else(|| "*".to_string());
+                    (name.clone(), constraint)
+                })
+                .collect();
+            let versions = installed
+                .requested
+                .iter()
+                .filter_map(|name| Some((name.clone(), installed.versions.get(name)?.clone())))
+                .collect();
+            manifest.packages.insert(*backend, packages);
+            lockfile.packages.insert(*backend, versions);
+        }
+        (manifest, lockfile)
+    }
+
+    /// Compare the manifest and its lockfile with what the backends have
+    /// installed. Backends missing from `installed` count as unavailable
+    pub fn diff(
+        &self,
+        lockfile: &Lockfile,
+        installed: &BTreeMap<Backend, Installed>,
+    ) -> ManifestDiff {
+        let mut diff = ManifestDiff::default();
+        for (backend, packages) in &self.packages {
+            let Some(installed) = installed.get(backend) else {
+                diff.unavailable.push(*backend);
+                continue;
+            };
+            let locked = lockfile.packages.get(backend);
+            for (name, constraint) in packages {
+                let Some(version) = installed.versions.get(name) else {
+                    diff.missing.push((*backend, name.clone()));
+                    continue;
+                };
+                if !satisfies(version, constraint) {
+                    diff.unsatisfied.push(VersionChange {
+                        backend: *backend,
+                        name: name.clone(),
+                        expected: constraint.clone(),
+                        installed: version.clone(),
+                    });
+                }
+                if let Some(locked) = locked.and_then(|locked| locked.get(name))
+                    && locked != version
+                {
+                    diff.drifted.push(VersionChange {
+                        backend: *backend,
+                        name: name.clone(),
+                        expected: locked.clone(),
+                        installed: version.clone(),
+                    });
+                }
+            }
+            if !installed.recorded {
+                diff.unrecorded.push(*backend);
+                continue;
+            }
+            diff.extra.extend(
+                installed
+                    .requested
+                    .iter()
+                    .filter(|name| !packages.contains_key(*name))
+                    .map(|name| (*backend, name.clone())),
+            );
+        }
+        diff
+    }
+
+    /// Whether every package of the manifest that `backend` has installed
+    /// satisfies its constraint
+    pub fn is_satisfied(&self, backend: Backend, installed: &Installed) -> bool {
+        self.packages.get(&backend).is_none_or(|packages| {
+            packages.iter().all(|(name, constraint)| {
+                installed
+                    .versions
+                    .get(name)
+                    .is_none_or(|version| satisfies(version, constraint))
+            })
+        })
+    }
+}
+
+impl ManifestDiff {
+    /// Plan the upgrades of the unsatisfied packages, given the versions
+    /// the backends would upgrade their outdated packages to. Packages whose
+    /// upgrade would not satisfy their constraint either are left alone
+    pub fn plan_upgrades(&mut self, latest: &BTreeMap<Backend, BTreeMap<String, String>>) {
+        self.upgrades = self
+            .unsatisfied
+            .iter()
+            .filter_map(|change| {
+                let version = latest.get(&change.backend)?.get(&change.name)?;
+                satisfies(version, &change.expected).then(|| Upgrade {
+                    backend: change.backend,
+                    name: change.name.clone(),
+                    installed: change.installed.clone(),
+                    version: version.clone(),
+                })
+            })
+            .collect();
+    }
+
+    /// Whether applying the manifest would install, upgrade or uninstall
+    /// anything
+    pub fn has_changes(&self) -> bool {
+        !self.missing.is_empty() || !self.upgrades.is_empty() || !self.extra.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.has_changes()
+            && self.unsatisfied.is_empty()
+            && self.drifted.is_empty()
+            && self.unavailable.is_empty()
+            && self.unrecorded.is_empty()
+    }
+}
+
+impl Display for ManifestDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Everything installed matches the manifest");
+        }
+        if !self.missing.is_empty() {
+            writeln!(f, "==> Not installed ({})", self.missing.len())?;
+            for (backend, name) in &self.missing {
+                writeln!(f, "{backend}: {name}")?;
+            }
+        }
+        if !self.extra.is_empty() {
+            writeln!(f, "==> Not in the manifest ({})", self.extra.len())?;
+            for (backend, name) in &self.extra {
+                writeln!(f, "{backend}: {name}")?;
+            }
+        }
+        if !self.upgrades.is_empty() {
+            writeln!(
+                f,
+                "==> Upgraded to match the version constraint ({})",
+                self.upgrades.len()
+            )?;
+            for upgrade in &self.upgrades {
+                writeln!(
+                    f,
+                    "{}: {} {} -> {}",
+                    upgrade.backend, upgrade.name, upgrade.installed, upgrade.version
+                )?;
+            }
+        }
+        // the unsatisfied packages no upgrade is planned for
+        let unsatisfied =
+            self.unsatisfied
+                .iter()
+                .filter(|change| {
+                    !self.upgrades.iter().any(|upgrade| {
+                        upgrade.backend == change.backend && upgrade.name == change.name
+                    })
+                })
+                .collect::<Vec<&VersionChange>>();
+        if !unsatisfied.is_empty() {
+            writeln!(
+                f,
+                "==> Not matching the version constraint ({})",
+                unsatisfied.len()
+            )?;
+            for change in unsatisfied {
+                writeln!(
+                    f,
+                    "{}: {} {} does not satisfy {}",
+                    change.backend, change.name, change.installed, change.expected
+                )?;
+            }
+        }
+        if !self.drifted.is_empty() {
+            writeln!(f, "==> Drifted from the lockfile ({})", self.drifted.len())?;
+            for change in &self.drifted {
+                writeln!(
+                    f,
+                    "{}: {} {} -> {}",
+                    change.backend, change.name, change.expected, change.installed
+                )?;
+            }
+        }
+        if !self.unavailable.is_empty() {
+            writeln!(f, "==> Backends not available on this machine")?;
+            for backend in &self.unavailable {
+                writeln!(f, "{backend}")?;
+            }
+        }
+        if !self.unrecorded.is_empty() {
+            writeln!(
+                f,
+                "==> Backends keeping no record of the packages installed on request, \
+                 nothing is uninstalled with them"
+            )?;
+            for backend in &self.unrecorded {
+                writeln!(f, "{backend}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `version` satisfies `constraint`, which is `*` for any version,
+/// a version optionally prefixed with `=` for that version or any version
+/// it is a prefix of, or a version prefixed with `>=`, `>`, `<=` or `<`
+pub fn satisfies(version: &str, constraint: &str) -> bool {
+    let constraint = constraint.trim();
+    if constraint.is_empty() || constraint == "*" {
+        return true;
+    }
+    let (operator, expected) = [">=", "<=", ">", "<", "="]
+        .into_iter()
+        .find_map(|operator| {
+            constraint
+                .strip_prefix(operator)
+                .map(|expected| (operator, expected.trim()))
+        })
+        .unwrap_or(("=", constraint));
+    match operator {
+        ">=" => compare_versions(version, expected).is_ge(),
+        "<=" => compare_versions(version, expected).is_le(),
+        ">" => compare_versions(version, expected).is_gt(),
+        "<" => compare_versions(version, expected).is_lt(),
+        _ => {
+            let version = components(version);
+            let expected = components(expected);
+            version.len() >= expected.len()
+                && version
+                    .iter()
+                    .zip(&expected)
+                    .all(|(version, expected)| compare_components(version, expected).is_eq())
+        }
+    }
+}
+
+/// Compare versions component by component, numerically where both
+/// components are numbers, so that `1.10` is newer than `1.9`
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (components(a), components(b));
+    a.iter()
+        .zip(&b)
+        .map(|(a, b)| compare_components(a, b))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+/// Alphanumeric runs of a version, such as `1`, `2` and `rc1` for `1.2-rc1`
+fn components(version: &str) -> Vec<&str> {
+    version
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|component| !component.is_empty())
+        .collect()
+}
+
+fn compare_components(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(versions: &[(&str, &str)], requested: &[&str], recorded: bool) -> Installed {
+        Installed {
+            versions: versions
+                .iter()
+                .map(|(name, version)| (name.to_string(), version.to_string()))
+                .collect(),
+            requested: requested.iter().map(|name| name.to_string()).collect(),
+            recorded,
+        }
+    }
+
+    fn manifest(backend: Backend, packages: &[(&str, &str)]) -> Manifest {
+        let packages = packages
+            .iter()
+            .map(|(name, constraint)| (name.to_string(), constraint.to_string()))
+            .collect();
+        Manifest {
+            packages: BTreeMap::from([(backend, packages)]),
+        }
+    }
+
+    #[test]
+    fn extra_packages_only_with_a_record() {
+        let manifest = manifest(Backend::Apt, &[("curl", "*")]);
+        let mut installed = BTreeMap::from([(
+            Backend::Apt,
+            installed(
+                &[("curl", "7.88.1"), ("vim", "9.0")],
+                &["curl", "vim"],
+                true,
+            ),
+        )]);
+        let diff = manifest.diff(&Lockfile::default(), &installed);
+        assert_eq!(diff.extra, vec![(Backend::Apt, "vim".to_string())]);
+        assert!(diff.has_changes());
+
+        installed.get_mut(&Backend::Apt).unwrap().recorded = false;
+        let diff = manifest.diff(&Lockfile::default(), &installed);
+        assert!(diff.extra.is_empty());
+        assert_eq!(diff.unrecorded, vec![Backend::Apt]);
+        assert!(!diff.has_changes());
+    }
+
+    #[test]
+    fn upgrades_satisfying_the_constraint() {
+        let manifest = manifest(Backend::Homebrew, &[("wget", ">=1.24"), ("git", "2.44")]);
+        let installed = BTreeMap::from([(
+            Backend::Homebrew,
+            installed(&[("wget", "1.21"), ("git", "2.43")], &["wget", "git"], true),
+        )]);
+        assert!(!manifest.is_satisfied(Backend::Homebrew, &installed[&Backend::Homebrew]));
+        let mut diff = manifest.diff(&Lockfile::default(), &installed);
+        assert_eq!(diff.unsatisfied.len(), 2);
+        assert!(!diff.has_changes());
+
+        // git would be upgraded past the pinned minor version
+        let latest = BTreeMap::from([(
+            Backend::Homebrew,
+            BTreeMap::from([
+                ("wget".to_string(), "1.24.5".to_string()),
+                ("git".to_string(), "2.45.1".to_string()),
+            ]),
+        )]);
+        diff.plan_upgrades(&latest);
+        assert_eq!(
+            diff.upgrades,
+            vec![Upgrade {
+                backend: Backend::Homebrew,
+                name: "wget".to_string(),
+                installed: "1.21".to_string(),
+                version: "1.24.5".to_string(),
+            }]
+        );
+        assert!(diff.has_changes());
+        let plan = diff.to_string();
+        assert!(plan.contains(&format!("{}: wget 1.21 -> 1.24.5", Backend::Homebrew)));
+        assert!(plan.contains("git 2.43 does not satisfy 2.44"));
+        assert!(!plan.contains("wget 1.21 does not satisfy"));
+    }
+}
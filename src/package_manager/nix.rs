@@ -0,0 +1,776 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::mpsc::{Receiver, Sender},
+};
+
+use serde::Deserialize;
+
+use super::{
+    Backend, CleanupPreview, CommandResult, Dependency, HealthWarning, Package, PackageLocality,
+    PackageManager, SpawnCommandResult, SystemInfo, command,
+    error::{Error, checked, checked_search},
+    fuzz_packages,
+    graph::DependencyGraph,
+    handle_spawned_command, rank_packages, spawn_command, stream_spawned_command,
+};
+
+pub struct Nix;
+
+const NIX_ALIAS: &str = "nix";
+const NIX_STORE_ALIAS: &str = "nix-store";
+const NIX_COLLECT_GARBAGE_ALIAS: &str = "nix-collect-garbage";
+const NIXPKGS_FLAKE: &str = "nixpkgs";
+
+/// The profile and search subcommands are only available with the
+/// `nix-command` and `flakes` experimental features enabled
+const EXPERIMENTAL_FEATURES: [&str; 2] = ["--extra-experimental-features", "nix-command flakes"];
+
+/// Output of `nix profile list --json`
+#[derive(Deserialize)]
+struct Profile {
+    elements: ProfileElements,
+}
+
+/// Profile elements are keyed by name from profile version 3 onwards and
+/// stored as a plain list before that
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProfileElements {
+    Named(BTreeMap<String, ProfileElement>),
+    Indexed(Vec<ProfileElement>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileElement {
+    attr_path: Option<String>,
+    store_paths: Vec<String>,
+}
+
+/// Value of each entry of `nix search --json`, keyed by attribute path
+#[derive(Deserialize)]
+struct SearchResult {
+    pname: String,
+    version: String,
+    description: String,
+}
+
+/// Output of `nix path-info --json`, keyed by store path from Nix 2.19
+/// onwards and stored as a plain list before that
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PathInfos {
+    Keyed(BTreeMap<String, Option<PathInfo>>),
+    Listed(Vec<PathInfo>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PathInfo {
+    path: Option<String>,
+    nar_size: Option<u64>,
+    closure_size: Option<u64>,
+    deriver: Option<String>,
+    #[serde(default)]
+    references: Vec<String>,
+    #[serde(default)]
+    signatures: Vec<String>,
+}
+
+/// Strip the `<output>.<system>.` prefix off a flake attribute path,
+/// e.g. `legacyPackages.x86_64-linux.ripgrep` becomes `ripgrep`
+fn attribute_name(attr_path: &str) -> String {
+    match attr_path.splitn(3, '.').nth(2) {
+        Some(name) => name.to_string(),
+        None => attr_path.to_string(),
+    }
+}
+
+/// Version part of a store path, split off the name at the first dash
+/// that is not followed by a letter the way `builtins.parseDrvName` does,
+/// e.g. `/nix/store/<hash>-ripgrep-14.1.0` has version `14.1.0`
+fn store_path_version(store_path: &str) -> Option<String> {
+    let (_hash, name) = store_path.rsplit('/').next()?.split_once('-')?;
+    name.match_indices('-')
+        .find(|(index, _)| {
+            !name[index + 1..]
+                .chars()
+                .next()
+                .is_some_and(|ch| ch.is_alphabetic())
+        })
+        .map(|(index, _)| name[index + 1..].to_string())
+}
+
+impl Profile {
+    /// Names of the installed elements, as used by `nix profile upgrade`
+    /// and `nix profile remove`, along with their store paths
+    fn into_elements(self) -> Vec<(String, Vec<String>)> {
+        match self.elements {
+            ProfileElements::Named(elements) => elements
+                .into_iter()
+                .map(|(name, element)| (name, element.store_paths))
+                .collect(),
+            ProfileElements::Indexed(elements) => elements
+                .into_iter()
+                .filter_map(|element| {
+                    let name = attribute_name(element.attr_path.as_deref()?);
+                    Some((name, element.store_paths))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PathInfos {
+    fn into_vec(self) -> Vec<(String, PathInfo)> {
+        match self {
+            PathInfos::Keyed(infos) => infos
+                .into_iter()
+                .filter_map(|(path, info)| Some((path, info?)))
+                .collect(),
+            PathInfos::Listed(infos) => infos
+                .into_iter()
+                .map(|info| (info.path.clone().unwrap_or_default(), info))
+                .collect(),
+        }
+    }
+}
+
+impl Nix {
+    /// List the elements installed in the default profile
+    fn nix_profile_list() -> CommandResult {
+        let mut args = EXPERIMENTAL_FEATURES.to_vec();
+        args.extend(["profile", "list", "--json"]);
+        command(NIX_ALIAS, args)
+    }
+
+    /// Install packages from a flake into the default profile
+    fn nix_profile_install<I, J>(options: Option<I>, installables: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = ProfileOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = EXPERIMENTAL_FEATURES.map(String::from).to_vec();
+        args.extend(["profile".to_string(), "install".to_string()]);
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: ProfileOption| option.into()),
+            );
+        }
+        args.extend(installables);
+
+        spawn_command(NIX_ALIAS, args)
+    }
+
+    /// Upgrade profile elements to the latest version of their flake
+    fn nix_profile_upgrade<I, J>(options: Option<I>, elements: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = ProfileOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = EXPERIMENTAL_FEATURES.map(String::from).to_vec();
+        args.extend(["profile".to_string(), "upgrade".to_string()]);
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: ProfileOption| option.into()),
+            );
+        }
+        args.extend(elements);
+
+        spawn_command(NIX_ALIAS, args)
+    }
+
+    /// Remove elements from the default profile
+    fn nix_profile_remove<J>(elements: J) -> SpawnCommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = EXPERIMENTAL_FEATURES.map(String::from).to_vec();
+        args.extend(["profile".to_string(), "remove".to_string()]);
+        args.extend(elements);
+
+        spawn_command(NIX_ALIAS, args)
+    }
+
+    /// Search nixpkgs for packages whose name or description
+    /// matches the specified regex
+    fn nix_search(pattern: String) -> CommandResult {
+        let mut args = EXPERIMENTAL_FEATURES.map(String::from).to_vec();
+        args.extend([
+            "search".to_string(),
+            NIXPKGS_FLAKE.to_string(),
+            pattern,
+            "--json".to_string(),
+        ]);
+        command(NIX_ALIAS, args)
+    }
+
+    /// Query information about store paths
+    fn nix_path_info<I, J>(options: Option<I>, paths: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = PathInfoOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = EXPERIMENTAL_FEATURES.map(String::from).to_vec();
+        args.extend(["path-info".to_string(), "--json".to_string()]);
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: PathInfoOption| option.into()),
+            );
+        }
+        args.extend(paths);
+
+        spawn_command(NIX_ALIAS, args)
+    }
+
+    /// Show the Nix configuration
+    fn nix_config_show() -> CommandResult {
+        let mut args = EXPERIMENTAL_FEATURES.to_vec();
+        args.extend(["config", "show"]);
+        command(NIX_ALIAS, args)
+    }
+
+    /// Run the garbage collector on the Nix store
+    fn nix_store_gc<I>(options: Option<I>) -> CommandResult
+    where
+        I: IntoIterator<Item = GcOption>,
+    {
+        let mut args = vec!["--gc".to_string()];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: GcOption| option.into()));
+        }
+        command(NIX_STORE_ALIAS, args)
+    }
+
+    /// Verify the consistency of the Nix database and the
+    /// contents of the store
+    fn nix_store_verify<I>(options: Option<I>) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = VerifyOption>,
+    {
+        let mut args = vec!["--verify".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: VerifyOption| option.into()),
+            );
+        }
+
+        spawn_command(NIX_STORE_ALIAS, args)
+    }
+
+    /// Delete unreachable store objects
+    fn nix_collect_garbage<I>(options: Option<I>) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = CollectGarbageOption>,
+    {
+        let args = match options {
+            Some(options) => options
+                .into_iter()
+                .map(|option: CollectGarbageOption| option.into())
+                .collect(),
+            None => Vec::<String>::new(),
+        };
+        spawn_command(NIX_COLLECT_GARBAGE_ALIAS, args)
+    }
+
+    fn profile_elements() -> Result<Vec<(String, Vec<String>)>, Error> {
+        let output = checked(NIX_ALIAS, Self::nix_profile_list())?;
+        serde_json::from_slice::<Profile>(&output.stdout)
+            .map(|profile| profile.into_elements())
+            .map_err(|e| Error::parse("nix profile list", e))
+    }
+
+    /// Search results keyed by attribute path, nix exits unsuccessfully
+    /// without printing any JSON when nothing matches
+    fn search(pattern: String) -> Result<BTreeMap<String, SearchResult>, Error> {
+        let output = checked_search(NIX_ALIAS, Self::nix_search(pattern), &["no results"])?;
+        if output.stdout.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+        serde_json::from_slice::<BTreeMap<String, SearchResult>>(&output.stdout)
+            .map_err(|e| Error::parse("nix search", e))
+    }
+}
+
+impl PackageManager for Nix {
+    fn alias(&self) -> &'static str {
+        NIX_ALIAS
+    }
+
+    fn filter_packages(
+        &self,
+        _rx: Receiver<bool>,
+        package_locality: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        match package_locality {
+            PackageLocality::Local => {
+                let installed_packages = Self::profile_elements()?
+                    .into_iter()
+                    .map(|(name, store_paths)| {
+                        let version = store_paths
+                            .first()
+                            .and_then(|store_path| store_path_version(store_path));
+                        Package {
+                            version: version.clone(),
+                            installed_version: Some(version.unwrap_or_default()),
+                            ..Package::new(name, Backend::Nix)
+                        }
+                    })
+                    .collect::<Vec<Package>>();
+                Ok(fuzz_packages(installed_packages, pattern))
+            }
+            PackageLocality::Remote => {
+                let installed_versions = Self::profile_elements()?
+                    .into_iter()
+                    .map(|(name, store_paths)| {
+                        let version = store_paths
+                            .first()
+                            .and_then(|store_path| store_path_version(store_path));
+                        (name, version.unwrap_or_default())
+                    })
+                    .collect::<HashMap<String, String>>();
+                let packages = Self::search(pattern.clone())?
+                    .into_iter()
+                    .map(|(attr_path, result)| {
+                        let name = attribute_name(&attr_path);
+                        Package {
+                            version: Some(result.version),
+                            installed_version: installed_versions.get(&name).cloned(),
+                            description: Some(result.description)
+                                .filter(|description| !description.is_empty()),
+                            repository: Some(NIXPKGS_FLAKE.to_string()),
+                            ..Package::new(name, Backend::Nix)
+                        }
+                    })
+                    .collect();
+                Ok(rank_packages(packages, &pattern))
+            }
+        }
+    }
+
+    fn outdated_packages(&self, _rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        Err(Error::unsupported(
+            "listing outdated packages in a nix profile",
+        ))
+    }
+
+    fn package_manager_config(&self, _rx: Receiver<bool>) -> Result<String, Error> {
+        let output = checked(NIX_ALIAS, Self::nix_config_show())?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn system_info(&self, _rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        Err(Error::unsupported("reading the system information of nix"))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let store_paths = Self::profile_elements()?
+            .into_iter()
+            .find(|(name, _store_paths)| *name == package_name)
+            .map(|(_name, store_paths)| store_paths);
+
+        // packages that are not installed have no store paths yet, so only
+        // the metadata published in nixpkgs can be shown for them
+        let Some(store_paths) = store_paths else {
+            return Ok(Self::search(format!("^{package_name}$"))?
+                .into_iter()
+                .map(|(attr_path, result)| {
+                    format!(
+                        "{}\n  attribute: {attr_path}\n  version: {}\n  description: {}\n",
+                        result.pname, result.version, result.description
+                    )
+                })
+                .collect());
+        };
+
+        let child = Self::nix_path_info(Some([PathInfoOption::ClosureSize]), store_paths)
+            .map_err(|e| Error::from_io(NIX_ALIAS, e))?;
+        let out = handle_spawned_command(rx, child)?
+            .success(NIX_ALIAS)?
+            .out
+            .unwrap_or_default();
+        let infos = serde_json::from_str::<PathInfos>(&out)
+            .map_err(|e| Error::parse("nix path-info", e))?;
+        Ok(infos
+            .into_vec()
+            .into_iter()
+            .map(|(path, info)| {
+                let mut details = format!("{path}\n");
+                if let Some(nar_size) = info.nar_size {
+                    details.push_str(&format!("  nar size: {nar_size} bytes\n"));
+                }
+                if let Some(closure_size) = info.closure_size {
+                    details.push_str(&format!("  closure size: {closure_size} bytes\n"));
+                }
+                if let Some(deriver) = info.deriver {
+                    details.push_str(&format!("  deriver: {deriver}\n"));
+                }
+                details.push_str("  references:\n");
+                for reference in info.references {
+                    details.push_str(&format!("    {reference}\n"));
+                }
+                details.push_str("  signatures:\n");
+                for signature in info.signatures {
+                    details.push_str(&format!("    {signature}\n"));
+                }
+                details
+            })
+            .collect())
+    }
+
+    fn dependencies(
+        &self,
+        _rx: Receiver<bool>,
+        _package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        Err(Error::unsupported(
+            "listing the dependencies of packages in a nix profile",
+        ))
+    }
+
+    fn dependents(&self, _rx: Receiver<bool>, _package_name: String) -> Result<Vec<String>, Error> {
+        Err(Error::unsupported(
+            "listing the dependents of packages in a nix profile",
+        ))
+    }
+
+    fn dependency_graph(&self, _rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        Err(Error::unsupported(
+            "exporting the dependency graph of a nix profile",
+        ))
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::nix_store_verify(Some([VerifyOption::CheckContents]))
+            .map_err(|e| Error::from_io(NIX_STORE_ALIAS, e))?;
+        // nix-store reports its progress and any problems on stderr
+        stream_spawned_command(rx, tx, child)?.report(NIX_STORE_ALIAS)
+    }
+
+    fn health_warnings(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "splitting the health check of nix into warnings",
+        ))
+    }
+
+    fn run_health_check(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "running individual health checks with nix",
+        ))
+    }
+
+    fn clean_preview(&self, _rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        // unreachable store paths are printed one per line
+        let output = checked(
+            NIX_STORE_ALIAS,
+            Self::nix_store_gc(Some([GcOption::PrintDead])),
+        )?;
+        Ok(CleanupPreview {
+            files: String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|path| path.trim())
+                .filter(|path| !path.is_empty())
+                .map(|path| path.to_string())
+                .collect(),
+            ..CleanupPreview::default()
+        })
+    }
+
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::nix_collect_garbage::<Vec<CollectGarbageOption>>(None)
+            .map_err(|e| Error::from_io(NIX_COLLECT_GARBAGE_ALIAS, e))?;
+
+        // nix-collect-garbage reports the freed space on stderr
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(NIX_COLLECT_GARBAGE_ALIAS)?
+            .combined())
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::nix_profile_install::<Vec<ProfileOption>, _>(
+            None,
+            [format!("{NIXPKGS_FLAKE}#{package_name}")],
+        )
+        .map_err(|e| Error::from_io(NIX_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(NIX_ALIAS)?
+            .combined())
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::nix_profile_upgrade::<Vec<ProfileOption>, _>(None, [package_name])
+            .map_err(|e| Error::from_io(NIX_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(NIX_ALIAS)?
+            .combined())
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child =
+            Self::nix_profile_remove([package_name]).map_err(|e| Error::from_io(NIX_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(NIX_ALIAS)?
+            .combined())
+    }
+
+    fn pin_package(&self, _rx: Receiver<bool>, _package_name: String) -> Result<String, Error> {
+        Err(Error::unsupported("pinning packages in a nix profile"))
+    }
+
+    fn unpin_package(&self, _rx: Receiver<bool>, _package_name: String) -> Result<String, Error> {
+        Err(Error::unsupported("unpinning packages in a nix profile"))
+    }
+}
+
+pub enum CollectGarbageOption {
+    DeleteOld,
+    DryRun,
+}
+
+impl From<CollectGarbageOption> for String {
+    fn from(value: CollectGarbageOption) -> Self {
+        match value {
+            CollectGarbageOption::DeleteOld => "--delete-old".to_string(),
+            CollectGarbageOption::DryRun => "--dry-run".to_string(),
+        }
+    }
+}
+
+pub enum GcOption {
+    PrintDead,
+    PrintLive,
+}
+
+impl From<GcOption> for String {
+    fn from(value: GcOption) -> Self {
+        match value {
+            GcOption::PrintDead => "--print-dead".to_string(),
+            GcOption::PrintLive => "--print-live".to_string(),
+        }
+    }
+}
+
+pub enum PathInfoOption {
+    ClosureSize,
+    Recursive,
+    SigningInfo,
+}
+
+impl From<PathInfoOption> for String {
+    fn from(value: PathInfoOption) -> Self {
+        match value {
+            PathInfoOption::ClosureSize => "--closure-size".to_string(),
+            PathInfoOption::Recursive => "--recursive".to_string(),
+            PathInfoOption::SigningInfo => "--sigs".to_string(),
+        }
+    }
+}
+
+pub enum ProfileOption {
+    Impure,
+    Refresh,
+    Offline,
+}
+
+impl From<ProfileOption> for String {
+    fn from(value: ProfileOption) -> Self {
+        match value {
+            ProfileOption::Impure => "--impure".to_string(),
+            ProfileOption::Refresh => "--refresh".to_string(),
+            ProfileOption::Offline => "--offline".to_string(),
+        }
+    }
+}
+
+pub enum VerifyOption {
+    CheckContents,
+    Repair,
+}
+
+impl From<VerifyOption> for String {
+    fn from(value: VerifyOption) -> Self {
+        match value {
+            VerifyOption::CheckContents => "--check-contents".to_string(),
+            VerifyOption::Repair => "--repair".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    /// Profile of version 3, keyed by element name
+    const PROFILE: &str = r#"{"version":3,"elements":{"ripgrep":{"active":true,"attrPath":"legacyPackages.x86_64-linux.ripgrep","originalUrl":"flake:nixpkgs","storePaths":["/nix/store/8w1jc6k4m3-ripgrep-14.1.0"]},"git-lfs":{"active":true,"attrPath":"legacyPackages.x86_64-linux.git-lfs","originalUrl":"flake:nixpkgs","storePaths":["/nix/store/qk1jwn9b5p-git-lfs-3.5.1"]}}}"#;
+
+    /// Stub nix printing `profile` for `nix profile list --json` and
+    /// `search` for `nix search nixpkgs <pattern> --json`
+    fn stub_nix(path: &StubPath, profile: &str, search: &str) {
+        path.stub(
+            "nix",
+            &format!(
+                "case \"$3 $4\" in\n\
+                 'profile list') echo '{profile}' ;;\n\
+                 'search nixpkgs') {search} ;;\n\
+                 *) exit 1 ;;\n\
+                 esac"
+            ),
+        );
+    }
+
+    #[test]
+    fn missing_binary() {
+        let _path = StubPath::isolated();
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Nix.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::BinaryMissing {
+                binary: NIX_ALIAS.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn installed_packages() {
+        let path = StubPath::isolated();
+        stub_nix(&path, PROFILE, "exit 1");
+        let (_tx, rx) = mpsc::channel();
+        let packages = Nix
+            .filter_packages(rx, PackageLocality::Local, "git".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "git-lfs");
+        assert_eq!(packages[0].installed_version.as_deref(), Some("3.5.1"));
+    }
+
+    #[test]
+    fn installed_packages_of_an_older_profile() {
+        let path = StubPath::isolated();
+        stub_nix(
+            &path,
+            r#"{"version":2,"elements":[{"active":true,"attrPath":"legacyPackages.x86_64-linux.ripgrep","storePaths":["/nix/store/8w1jc6k4m3-ripgrep-14.1.0"]},{"active":true,"attrPath":null,"storePaths":["/nix/store/v2ad6mq0b9-local-tool"]}]}"#,
+            "exit 1",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Nix
+            .filter_packages(rx, PackageLocality::Local, String::default())
+            .unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "ripgrep");
+        assert_eq!(packages[0].installed_version.as_deref(), Some("14.1.0"));
+    }
+
+    #[test]
+    fn remote_packages() {
+        let path = StubPath::isolated();
+        stub_nix(
+            &path,
+            PROFILE,
+            r#"echo '{"legacyPackages.x86_64-linux.ripgrep":{"pname":"ripgrep","version":"14.1.1","description":"A search tool"},"legacyPackages.x86_64-linux.ripgrep-all":{"pname":"ripgrep-all","version":"0.10.6","description":""}}'"#,
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Nix
+            .filter_packages(rx, PackageLocality::Remote, "ripgrep".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "ripgrep");
+        assert_eq!(packages[0].version.as_deref(), Some("14.1.1"));
+        assert_eq!(packages[0].installed_version.as_deref(), Some("14.1.0"));
+        assert_eq!(packages[0].description.as_deref(), Some("A search tool"));
+        assert_eq!(packages[1].name, "ripgrep-all");
+        assert_eq!(packages[1].installed_version, None);
+        assert_eq!(packages[1].description, None);
+    }
+
+    #[test]
+    fn nothing_found() {
+        let path = StubPath::isolated();
+        stub_nix(
+            &path,
+            PROFILE,
+            "echo 'error: no results for the given search term(s)!' >&2; exit 1",
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Nix.filter_packages(rx, PackageLocality::Remote, "nothing".to_string()),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn malformed_profile() {
+        let path = StubPath::isolated();
+        stub_nix(&path, "{\"version\":3}", "exit 1");
+        let (_tx, rx) = mpsc::channel();
+        assert!(matches!(
+            Nix.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::Parse { command, .. }) if command == "nix profile list"
+        ));
+    }
+
+    #[test]
+    fn failed_profile_list() {
+        let path = StubPath::isolated();
+        path.stub(
+            "nix",
+            "echo \"error: experimental Nix feature 'flakes' is disabled\" >&2\nexit 1",
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Nix.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::NonZeroExit {
+                command: NIX_ALIAS.to_string(),
+                code: Some(1),
+                stderr: "error: experimental Nix feature 'flakes' is disabled".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn outdated_packages_unsupported() {
+        let (_tx, rx) = mpsc::channel();
+        assert!(matches!(
+            Nix.outdated_packages(rx),
+            Err(Error::Unsupported { .. })
+        ));
+    }
+}
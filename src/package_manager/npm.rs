@@ -0,0 +1,490 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::mpsc::{Receiver, Sender},
+};
+
+use serde::Deserialize;
+
+use super::{
+    Backend, CleanupPreview, CommandResult, HealthWarning, Package, PackageLocality,
+    PackageManager, SpawnCommandResult, SystemInfo, command,
+    error::{Error, checked, unchecked},
+    fuzz_packages,
+    graph::DependencyGraph,
+    handle_spawned_command, rank_packages, spawn_command, stream_spawned_command,
+};
+
+pub struct Npm;
+
+const NPM_ALIAS: &str = "npm";
+
+/// Output of `npm ls --json`
+#[derive(Deserialize)]
+struct Dependencies {
+    #[serde(default)]
+    dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Deserialize)]
+struct Dependency {
+    version: Option<String>,
+}
+
+/// Value of each entry of `npm outdated --json`, keyed by package name
+#[derive(Deserialize)]
+struct OutdatedDependency {
+    current: Option<String>,
+    latest: Option<String>,
+}
+
+/// Each entry of `npm search --json`
+#[derive(Deserialize)]
+struct SearchResult {
+    name: String,
+    version: Option<String>,
+    description: Option<String>,
+}
+
+impl Npm {
+    /// Install packages globally
+    fn npm_install<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["install".to_string(), "--global".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+
+        spawn_command(NPM_ALIAS, args)
+    }
+
+    /// Update globally installed packages to the latest version
+    fn npm_update<J>(package_list: J) -> SpawnCommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["update".to_string(), "--global".to_string()];
+        args.extend(package_list);
+
+        spawn_command(NPM_ALIAS, args)
+    }
+
+    /// Uninstall globally installed packages
+    fn npm_uninstall<J>(package_list: J) -> SpawnCommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["uninstall".to_string(), "--global".to_string()];
+        args.extend(package_list);
+
+        spawn_command(NPM_ALIAS, args)
+    }
+
+    /// List globally installed top-level packages
+    fn npm_ls() -> CommandResult {
+        command(NPM_ALIAS, ["ls", "--global", "--depth=0", "--json"])
+    }
+
+    /// Search the registry for packages matching the specified pattern
+    fn npm_search(pattern: String) -> CommandResult {
+        command(
+            NPM_ALIAS,
+            ["search".to_string(), "--json".to_string(), pattern],
+        )
+    }
+
+    /// Show the registry metadata of a package
+    fn npm_view(package_name: String) -> SpawnCommandResult {
+        spawn_command(NPM_ALIAS, ["view".to_string(), package_name])
+    }
+
+    /// Check the environment for common problems
+    fn npm_doctor() -> SpawnCommandResult {
+        spawn_command(NPM_ALIAS, ["doctor"])
+    }
+
+    /// Garbage collect unneeded data from the cache and verify its
+    /// integrity
+    fn npm_cache_verify() -> SpawnCommandResult {
+        spawn_command(NPM_ALIAS, ["cache", "verify"])
+    }
+
+    /// List globally installed packages that have a newer version
+    fn npm_outdated() -> CommandResult {
+        command(NPM_ALIAS, ["outdated", "--global", "--json"])
+    }
+
+    /// Show the configuration settings
+    fn npm_config_list() -> CommandResult {
+        command(NPM_ALIAS, ["config", "list"])
+    }
+
+    /// Globally installed top-level packages along with their versions
+    fn installed_packages() -> Result<Vec<Package>, Error> {
+        let output = match Self::npm_ls() {
+            Ok(output) => output,
+            Err(e) => return Err(Error::from_io(NPM_ALIAS, e)),
+        };
+        match serde_json::from_slice::<Dependencies>(&output.stdout) {
+            Ok(dependencies) => Ok(dependencies
+                .dependencies
+                .into_iter()
+                .map(|(name, dependency)| Package {
+                    version: dependency.version.clone(),
+                    installed_version: Some(dependency.version.unwrap_or_default()),
+                    repository: Some("npm".to_string()),
+                    ..Package::new(name, Backend::Npm)
+                })
+                .collect()),
+            Err(e) => Err(Error::parse("npm ls", e)),
+        }
+    }
+}
+
+impl PackageManager for Npm {
+    fn alias(&self) -> &'static str {
+        NPM_ALIAS
+    }
+
+    fn filter_packages(
+        &self,
+        _rx: Receiver<bool>,
+        package_locality: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        match package_locality {
+            PackageLocality::Local => Ok(fuzz_packages(Self::installed_packages()?, pattern)),
+            PackageLocality::Remote => {
+                let installed_versions = Self::installed_packages()?
+                    .into_iter()
+                    .map(|package| (package.name, package.installed_version))
+                    .collect::<HashMap<String, Option<String>>>();
+                match Self::npm_search(pattern.clone()) {
+                    Ok(output) => match serde_json::from_slice::<Vec<SearchResult>>(&output.stdout)
+                    {
+                        Ok(results) => Ok(rank_packages(
+                            results
+                                .into_iter()
+                                .map(|result| Package {
+                                    version: result.version,
+                                    installed_version: installed_versions
+                                        .get(&result.name)
+                                        .cloned()
+                                        .flatten(),
+                                    description: result.description,
+                                    repository: Some("npm".to_string()),
+                                    ..Package::new(result.name, Backend::Npm)
+                                })
+                                .collect(),
+                            &pattern,
+                        )),
+                        Err(e) => Err(Error::parse("npm search", e)),
+                    },
+                    Err(e) => Err(Error::from_io(NPM_ALIAS, e)),
+                }
+            }
+        }
+    }
+
+    fn outdated_packages(&self, _rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        // npm outdated exits unsuccessfully whenever something is outdated
+        let output = unchecked(NPM_ALIAS, Self::npm_outdated())?;
+        if output.stdout.iter().all(|byte| byte.is_ascii_whitespace()) {
+            return Ok(Vec::new());
+        }
+        let outdated =
+            serde_json::from_slice::<BTreeMap<String, OutdatedDependency>>(&output.stdout)
+                .map_err(|e| Error::parse("npm outdated", e))?;
+        Ok(outdated
+            .into_iter()
+            .map(|(name, dependency)| Package {
+                version: dependency.latest,
+                installed_version: dependency.current,
+                repository: Some("npm".to_string()),
+                ..Package::new(name, Backend::Npm)
+            })
+            .collect())
+    }
+
+    fn package_manager_config(&self, _rx: Receiver<bool>) -> Result<String, Error> {
+        let output = checked(NPM_ALIAS, Self::npm_config_list())?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn system_info(&self, _rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        Err(Error::unsupported("reading the system information of npm"))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let child = Self::npm_view(package_name).map_err(|e| Error::from_io(NPM_ALIAS, e))?;
+        Ok(handle_spawned_command(rx, child)?
+            .success(NPM_ALIAS)?
+            .out
+            .unwrap_or_default())
+    }
+
+    fn dependencies(
+        &self,
+        _rx: Receiver<bool>,
+        _package_name: String,
+    ) -> Result<Vec<super::Dependency>, Error> {
+        Err(Error::unsupported(
+            "listing the dependencies of global npm packages",
+        ))
+    }
+
+    fn dependents(&self, _rx: Receiver<bool>, _package_name: String) -> Result<Vec<String>, Error> {
+        Err(Error::unsupported(
+            "listing the dependents of global npm packages",
+        ))
+    }
+
+    fn dependency_graph(&self, _rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        Err(Error::unsupported(
+            "exporting the dependency graph of global npm packages",
+        ))
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::npm_doctor().map_err(|e| Error::from_io(NPM_ALIAS, e))?;
+        stream_spawned_command(rx, tx, child)?.report(NPM_ALIAS)
+    }
+
+    fn health_warnings(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "splitting the health check of npm into warnings",
+        ))
+    }
+
+    fn run_health_check(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "running individual health checks with npm",
+        ))
+    }
+
+    fn clean_preview(&self, _rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        Err(Error::unsupported("previewing npm cache verify"))
+    }
+
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::npm_cache_verify().map_err(|e| Error::from_io(NPM_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(NPM_ALIAS)?
+            .combined())
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::npm_install::<Vec<InstallOption>, _>(None, [package_name])
+            .map_err(|e| Error::from_io(NPM_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(NPM_ALIAS)?
+            .combined())
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::npm_update([package_name]).map_err(|e| Error::from_io(NPM_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(NPM_ALIAS)?
+            .combined())
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child =
+            Self::npm_uninstall([package_name]).map_err(|e| Error::from_io(NPM_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(NPM_ALIAS)?
+            .combined())
+    }
+
+    fn pin_package(&self, _rx: Receiver<bool>, _package_name: String) -> Result<String, Error> {
+        Err(Error::unsupported("pinning global packages with npm"))
+    }
+
+    fn unpin_package(&self, _rx: Receiver<bool>, _package_name: String) -> Result<String, Error> {
+        Err(Error::unsupported("unpinning global packages with npm"))
+    }
+}
+
+pub enum InstallOption {
+    Force,
+    IgnoreScripts,
+    NoAudit,
+    NoFund,
+    PreferOffline,
+    DryRun,
+}
+
+impl From<InstallOption> for String {
+    fn from(value: InstallOption) -> Self {
+        match value {
+            InstallOption::Force => "--force".to_string(),
+            InstallOption::IgnoreScripts => "--ignore-scripts".to_string(),
+            InstallOption::NoAudit => "--no-audit".to_string(),
+            InstallOption::NoFund => "--no-fund".to_string(),
+            InstallOption::PreferOffline => "--prefer-offline".to_string(),
+            InstallOption::DryRun => "--dry-run".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    /// npm with typescript and prettier installed globally
+    const NPM_LS: &str = r#"echo '{"name":"lib","dependencies":{"prettier":{"version":"3.2.5","overridden":false},"typescript":{"version":"5.4.5","overridden":false}}}'"#;
+
+    #[test]
+    fn missing_binary() {
+        let _path = StubPath::isolated();
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Npm.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::BinaryMissing {
+                binary: NPM_ALIAS.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn installed_packages() {
+        let path = StubPath::isolated();
+        path.stub("npm", &format!("[ \"$1\" = ls ] || exit 1\n{NPM_LS}"));
+        let (_tx, rx) = mpsc::channel();
+        let packages = Npm
+            .filter_packages(rx, PackageLocality::Local, "ts".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "typescript");
+        assert_eq!(packages[0].installed_version.as_deref(), Some("5.4.5"));
+    }
+
+    #[test]
+    fn nothing_installed() {
+        let path = StubPath::isolated();
+        path.stub("npm", "echo '{}'");
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Npm.filter_packages(rx, PackageLocality::Local, String::default()),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn remote_packages() {
+        let path = StubPath::isolated();
+        path.stub(
+            "npm",
+            &format!(
+                "case \"$1\" in\n\
+                 ls) {NPM_LS} ;;\n\
+                 search) echo '[{{\"name\":\"prettier-plugin-sql\",\"version\":\"0.18.0\",\"description\":\"SQL plugin\"}},{{\"name\":\"prettier\",\"version\":\"3.3.0\",\"description\":\"Prettier is an opinionated code formatter\"}}]' ;;\n\
+                 *) exit 1 ;;\n\
+                 esac"
+            ),
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Npm
+            .filter_packages(rx, PackageLocality::Remote, "prettier".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "prettier");
+        assert_eq!(packages[0].version.as_deref(), Some("3.3.0"));
+        assert_eq!(packages[0].installed_version.as_deref(), Some("3.2.5"));
+        assert_eq!(packages[1].name, "prettier-plugin-sql");
+        assert_eq!(packages[1].installed_version, None);
+        assert_eq!(packages[1].description.as_deref(), Some("SQL plugin"));
+    }
+
+    #[test]
+    fn malformed_search() {
+        let path = StubPath::isolated();
+        path.stub(
+            "npm",
+            &format!(
+                "[ \"$1\" = search ] || {{\n{NPM_LS}\nexit 0\n}}\n\
+                 echo 'npm ERR! code ENOTFOUND' >&2\nexit 1"
+            ),
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert!(matches!(
+            Npm.filter_packages(rx, PackageLocality::Remote, "prettier".to_string()),
+            Err(Error::Parse { command, .. }) if command == "npm search"
+        ));
+    }
+
+    #[test]
+    fn outdated_packages() {
+        let path = StubPath::isolated();
+        // npm outdated exits with 1 whenever something is outdated
+        path.stub(
+            "npm",
+            "[ \"$1\" = outdated ] || exit 1\n\
+             echo '{\"typescript\":{\"current\":\"5.4.5\",\"wanted\":\"5.4.5\",\"latest\":\"5.5.2\",\"location\":\"/usr/lib/node_modules/typescript\"}}'\n\
+             exit 1",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Npm.outdated_packages(rx).unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "typescript");
+        assert_eq!(packages[0].installed_version.as_deref(), Some("5.4.5"));
+        assert_eq!(packages[0].version.as_deref(), Some("5.5.2"));
+    }
+
+    #[test]
+    fn nothing_outdated() {
+        let path = StubPath::isolated();
+        path.stub("npm", "exit 0");
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(Npm.outdated_packages(rx), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn malformed_outdated() {
+        let path = StubPath::isolated();
+        path.stub("npm", "echo 'npm ERR! outdated failed'\nexit 1");
+        let (_tx, rx) = mpsc::channel();
+        assert!(matches!(
+            Npm.outdated_packages(rx),
+            Err(Error::Parse { command, .. }) if command == "npm outdated"
+        ));
+    }
+}
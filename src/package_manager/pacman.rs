@@ -0,0 +1,753 @@
+use std::sync::mpsc::{Receiver, Sender};
+
+use super::{
+    Backend, CleanupPreview, CommandResult, Dependency, HealthWarning, Package, PackageLocality,
+    PackageManager, SpawnCommandResult, SystemInfo, command,
+    error::{Error, checked, checked_search},
+    fuzz_packages,
+    graph::{DependencyGraph, DependencyKind, GraphEdge, GraphNode},
+    handle_spawned_command, rank_packages, spawn_command, stream_spawned_command,
+};
+
+pub struct Pacman;
+
+const PACMAN_ALIAS: &str = "pacman";
+const PACMAN_CONF_ALIAS: &str = "pacman-conf";
+
+/// Package names listed in the `<label> : <names>` field of `--info`
+/// output, where long lists continue on indented lines and `None` stands
+/// for an empty list. Version constraints such as `glibc>=2.38` are dropped
+fn package_field(details: &str, label: &str) -> Vec<String> {
+    let mut lines = details.lines().skip_while(|line| {
+        line.split_once(':')
+            .is_none_or(|(field, _value)| field.trim() != label)
+    });
+    let Some((_label, value)) = lines.next().and_then(|line| line.split_once(':')) else {
+        return Vec::new();
+    };
+    let continuation = lines.take_while(|line| line.starts_with(' '));
+    std::iter::once(value)
+        .chain(continuation)
+        .flat_map(|line| line.split_whitespace())
+        .filter(|name| *name != "None")
+        .filter_map(|name| name.split(['<', '>', '=']).next())
+        .map(|name| name.to_string())
+        .collect()
+}
+
+impl Pacman {
+    /// Query the local package database
+    fn pacman_query<I, J>(options: Option<I>, package_list: Option<J>) -> CommandResult
+    where
+        I: IntoIterator<Item = QueryOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["--query".to_string()];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: QueryOption| option.into()));
+        }
+        if let Some(packages) = package_list {
+            args.extend(packages);
+        }
+
+        command(PACMAN_ALIAS, args)
+    }
+
+    /// List the names of the packages installed explicitly, as opposed
+    /// to the ones installed as dependencies
+    fn pacman_query_explicit() -> SpawnCommandResult {
+        spawn_command(
+            PACMAN_ALIAS,
+            [
+                "--query".to_string(),
+                QueryOption::Explicit.into(),
+                QueryOption::Quiet.into(),
+            ],
+        )
+    }
+
+    /// List installed packages along with their versions
+    fn pacman_query_list() -> CommandResult {
+        command(PACMAN_ALIAS, ["--query"])
+    }
+
+    /// Details of a package from the local database when it is installed,
+    /// or from the sync databases otherwise
+    fn package_details(package_name: String) -> Result<String, Error> {
+        if let Ok(output) =
+            Self::pacman_query(Some([QueryOption::Info]), Some([package_name.clone()]))
+            && output.status.success()
+        {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+        let output = checked(
+            PACMAN_ALIAS,
+            command(
+                PACMAN_ALIAS,
+                ["--sync".to_string(), SyncOption::Info.into(), package_name],
+            ),
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Synchronize packages, installing them directly from the
+    /// remote repositories
+    fn pacman_sync<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = SyncOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["--sync".to_string()];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: SyncOption| option.into()));
+        }
+        args.extend(package_list);
+
+        spawn_command(PACMAN_ALIAS, args)
+    }
+
+    /// Search each package in the sync databases for names or
+    /// descriptions that match the specified pattern
+    fn pacman_sync_search(pattern: String) -> CommandResult {
+        command(
+            PACMAN_ALIAS,
+            ["--sync".to_string(), SyncOption::Search.into(), pattern],
+        )
+    }
+
+    /// Remove packages from the cache that are no longer
+    /// installed, along with unused sync databases
+    fn pacman_sync_clean<I>(options: Option<I>) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = SyncOption>,
+    {
+        let mut args = vec!["--sync".to_string(), SyncOption::Clean.into()];
+        if let Some(options) = options {
+            args.extend(options.into_iter().map(|option: SyncOption| option.into()));
+        }
+        spawn_command(PACMAN_ALIAS, args)
+    }
+
+    /// Remove specified packages
+    fn pacman_remove<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = RemoveOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["--remove".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: RemoveOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+
+        spawn_command(PACMAN_ALIAS, args)
+    }
+
+    /// Operate on the package database, e.g. to check it for
+    /// consistency
+    fn pacman_database<I>(options: Option<I>) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = DatabaseOption>,
+    {
+        let mut args = vec!["--database".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: DatabaseOption| option.into()),
+            );
+        }
+
+        spawn_command(PACMAN_ALIAS, args)
+    }
+
+    /// Display the parsed pacman configuration
+    fn pacman_conf() -> CommandResult {
+        command(PACMAN_CONF_ALIAS, Vec::<String>::new())
+    }
+}
+
+impl PackageManager for Pacman {
+    fn alias(&self) -> &'static str {
+        PACMAN_ALIAS
+    }
+
+    fn filter_packages(
+        &self,
+        _rx: Receiver<bool>,
+        package_locality: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        match package_locality {
+            PackageLocality::Local => match Self::pacman_query_list() {
+                // each result is formatted as `<package> <version>`
+                Ok(output) => {
+                    let installed_packages = String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .filter_map(|line| line.split_once(' '))
+                        .map(|(package, version)| Package {
+                            version: Some(version.to_string()),
+                            installed_version: Some(version.to_string()),
+                            ..Package::new(package.to_string(), Backend::Pacman)
+                        })
+                        .collect::<Vec<Package>>();
+                    Ok(fuzz_packages(installed_packages, pattern))
+                }
+                Err(e) => Err(Error::from_io(PACMAN_ALIAS, e)),
+            },
+            PackageLocality::Remote => match Self::pacman_sync_search(pattern.clone()) {
+                // each result is formatted as `<repository>/<package> <version>`,
+                // optionally followed by groups and an `[installed]` marker,
+                // with an indented description on the next line
+                Ok(output) => {
+                    let mut packages: Vec<Package> = Vec::new();
+                    for line in String::from_utf8_lossy(&output.stdout).lines() {
+                        if line.starts_with(char::is_whitespace) {
+                            if let Some(package) = packages.last_mut() {
+                                package.description = Some(line.trim().to_string());
+                            }
+                            continue;
+                        }
+                        let mut fields = line.split_whitespace();
+                        let Some(package) = fields.next() else {
+                            continue;
+                        };
+                        let (repository, package) = match package.split_once('/') {
+                            Some((repository, package)) => (Some(repository.to_string()), package),
+                            None => (None, package),
+                        };
+                        let version = fields.next().map(|version| version.to_string());
+                        // `[installed: <version>]` is reported when the installed
+                        // version differs from the one in the sync database
+                        let installed_version = match line.split_once("[installed") {
+                            Some((_, marker)) => {
+                                match marker.trim_end_matches(']').split_once(": ") {
+                                    Some((_, installed_version)) => {
+                                        Some(installed_version.to_string())
+                                    }
+                                    None => version.clone(),
+                                }
+                            }
+                            None => None,
+                        };
+                        packages.push(Package {
+                            version,
+                            installed_version,
+                            repository,
+                            ..Package::new(package.to_string(), Backend::Pacman)
+                        });
+                    }
+                    Ok(rank_packages(packages, &pattern))
+                }
+                Err(e) => Err(Error::from_io(PACMAN_ALIAS, e)),
+            },
+        }
+    }
+
+    fn outdated_packages(&self, _rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        // pacman exits unsuccessfully without printing anything when
+        // there is nothing to upgrade
+        let output = checked_search(
+            PACMAN_ALIAS,
+            Self::pacman_query::<_, Vec<String>>(Some([QueryOption::Upgrades]), None),
+            &[],
+        )?;
+        // each upgrade is listed as `<package> <installed version> -> <version>`,
+        // followed by `[ignored]` for packages held back by IgnorePkg
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let package = fields.next()?;
+                let installed_version = fields.next()?;
+                let version = fields.nth(1)?;
+                Some(Package {
+                    version: Some(version.to_string()),
+                    installed_version: Some(installed_version.to_string()),
+                    pinned: line.ends_with("[ignored]"),
+                    ..Package::new(package.to_string(), Backend::Pacman)
+                })
+            })
+            .collect())
+    }
+
+    fn requested_packages(&self, rx: Receiver<bool>) -> Result<Vec<String>, Error> {
+        let child = Self::pacman_query_explicit().map_err(|e| Error::from_io(PACMAN_ALIAS, e))?;
+        let out = handle_spawned_command(rx, child)?
+            .success(PACMAN_ALIAS)?
+            .out
+            .unwrap_or_default();
+        Ok(out.lines().map(str::to_string).collect())
+    }
+
+    fn package_manager_config(&self, _rx: Receiver<bool>) -> Result<String, Error> {
+        let output = checked(PACMAN_ALIAS, Self::pacman_conf())?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn system_info(&self, _rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        Err(Error::unsupported(
+            "reading the system information of pacman",
+        ))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        // prefer the local database so that install details are shown for
+        // installed packages, falling back to the sync databases otherwise
+        if let Ok(output) =
+            Self::pacman_query(Some([QueryOption::Info]), Some([package_name.clone()]))
+            && output.status.success()
+        {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+
+        let child = Self::pacman_sync(Some([SyncOption::Info]), [package_name])
+            .map_err(|e| Error::from_io(PACMAN_ALIAS, e))?;
+        Ok(handle_spawned_command(rx, child)?
+            .success(PACMAN_ALIAS)?
+            .out
+            .unwrap_or_default())
+    }
+
+    fn dependencies(
+        &self,
+        _rx: Receiver<bool>,
+        package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        let details = Self::package_details(package_name)?;
+        Ok(package_field(&details, "Depends On")
+            .into_iter()
+            .map(Dependency::new)
+            .collect())
+    }
+
+    fn dependents(&self, _rx: Receiver<bool>, package_name: String) -> Result<Vec<String>, Error> {
+        // only the local database knows what requires a package
+        let output = checked(
+            PACMAN_ALIAS,
+            Self::pacman_query(Some([QueryOption::Info]), Some([package_name])),
+        )?;
+        Ok(package_field(
+            &String::from_utf8_lossy(&output.stdout),
+            "Required By",
+        ))
+    }
+
+    fn dependency_graph(&self, _rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        // without a package, the details of every installed package are
+        // printed one after the other, separated by empty lines
+        let output = checked(
+            PACMAN_ALIAS,
+            Self::pacman_query::<_, Vec<String>>(Some([QueryOption::Info]), None),
+        )?;
+        let mut graph = DependencyGraph::default();
+        for details in String::from_utf8_lossy(&output.stdout).split("\n\n") {
+            let Some(name) = package_field(details, "Name").into_iter().next() else {
+                continue;
+            };
+            graph
+                .edges
+                .extend(
+                    package_field(details, "Depends On")
+                        .into_iter()
+                        .map(|to| GraphEdge {
+                            from: name.clone(),
+                            to,
+                            kind: DependencyKind::Runtime,
+                        }),
+                );
+            graph.nodes.push(GraphNode {
+                name,
+                version: package_field(details, "Version").into_iter().next(),
+            });
+        }
+        // dependencies on virtual packages such as `sh` have no node
+        graph.retain_installed();
+        Ok(graph)
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::pacman_database(Some([DatabaseOption::Check]))
+            .map_err(|e| Error::from_io(PACMAN_ALIAS, e))?;
+        stream_spawned_command(rx, tx, child)?.report(PACMAN_ALIAS)
+    }
+
+    fn health_warnings(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "splitting the health check of pacman into warnings",
+        ))
+    }
+
+    fn run_health_check(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "running individual health checks with pacman",
+        ))
+    }
+
+    fn clean_preview(&self, _rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        Err(Error::unsupported("previewing pacman -Sc"))
+    }
+
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::pacman_sync_clean(Some([SyncOption::NoConfirm]))
+            .map_err(|e| Error::from_io(PACMAN_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(PACMAN_ALIAS)?
+            .combined())
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::pacman_sync(
+            Some([SyncOption::Needed, SyncOption::NoConfirm]),
+            [package_name],
+        )
+        .map_err(|e| Error::from_io(PACMAN_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(PACMAN_ALIAS)?
+            .combined())
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        // partial upgrades are unsupported on Arch, so the package is
+        // upgraded as part of a full system upgrade
+        let child = Self::pacman_sync(
+            Some([
+                SyncOption::Refresh,
+                SyncOption::SysUpgrade,
+                SyncOption::NoConfirm,
+            ]),
+            [package_name],
+        )
+        .map_err(|e| Error::from_io(PACMAN_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(PACMAN_ALIAS)?
+            .combined())
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::pacman_remove(Some([RemoveOption::NoConfirm]), [package_name])
+            .map_err(|e| Error::from_io(PACMAN_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(PACMAN_ALIAS)?
+            .combined())
+    }
+
+    fn pin_package(&self, _rx: Receiver<bool>, _package_name: String) -> Result<String, Error> {
+        // packages are held back through IgnorePkg in pacman.conf
+        Err(Error::unsupported("pinning packages with pacman"))
+    }
+
+    fn unpin_package(&self, _rx: Receiver<bool>, _package_name: String) -> Result<String, Error> {
+        Err(Error::unsupported("unpinning packages with pacman"))
+    }
+}
+
+pub enum DatabaseOption {
+    Check,
+    AsDeps,
+    AsExplicit,
+    Quiet,
+}
+
+impl From<DatabaseOption> for String {
+    fn from(value: DatabaseOption) -> Self {
+        match value {
+            DatabaseOption::Check => "--check".to_string(),
+            DatabaseOption::AsDeps => "--asdeps".to_string(),
+            DatabaseOption::AsExplicit => "--asexplicit".to_string(),
+            DatabaseOption::Quiet => "--quiet".to_string(),
+        }
+    }
+}
+
+pub enum QueryOption {
+    Info,
+    Search,
+    Explicit,
+    Deps,
+    Foreign,
+    Native,
+    Unrequired,
+    Upgrades,
+    Quiet,
+}
+
+impl From<QueryOption> for String {
+    fn from(value: QueryOption) -> Self {
+        match value {
+            QueryOption::Info => "--info".to_string(),
+            QueryOption::Search => "--search".to_string(),
+            QueryOption::Explicit => "--explicit".to_string(),
+            QueryOption::Deps => "--deps".to_string(),
+            QueryOption::Foreign => "--foreign".to_string(),
+            QueryOption::Native => "--native".to_string(),
+            QueryOption::Unrequired => "--unrequired".to_string(),
+            QueryOption::Upgrades => "--upgrades".to_string(),
+            QueryOption::Quiet => "--quiet".to_string(),
+        }
+    }
+}
+
+pub enum RemoveOption {
+    NoConfirm,
+    Cascade,
+    NoDeps,
+    NoSave,
+    Recursive,
+    Unneeded,
+    Print,
+}
+
+impl From<RemoveOption> for String {
+    fn from(value: RemoveOption) -> Self {
+        match value {
+            RemoveOption::NoConfirm => "--noconfirm".to_string(),
+            RemoveOption::Cascade => "--cascade".to_string(),
+            RemoveOption::NoDeps => "--nodeps".to_string(),
+            RemoveOption::NoSave => "--nosave".to_string(),
+            RemoveOption::Recursive => "--recursive".to_string(),
+            RemoveOption::Unneeded => "--unneeded".to_string(),
+            RemoveOption::Print => "--print".to_string(),
+        }
+    }
+}
+
+pub enum SyncOption {
+    NoConfirm,
+    Needed,
+    AsDeps,
+    AsExplicit,
+    Clean,
+    DownloadOnly,
+    Info,
+    Search,
+    Refresh,
+    SysUpgrade,
+    NoDeps,
+    Print,
+    Quiet,
+}
+
+impl From<SyncOption> for String {
+    fn from(value: SyncOption) -> Self {
+        match value {
+            SyncOption::NoConfirm => "--noconfirm".to_string(),
+            SyncOption::Needed => "--needed".to_string(),
+            SyncOption::AsDeps => "--asdeps".to_string(),
+            SyncOption::AsExplicit => "--asexplicit".to_string(),
+            SyncOption::Clean => "--clean".to_string(),
+            SyncOption::DownloadOnly => "--downloadonly".to_string(),
+            SyncOption::Info => "--info".to_string(),
+            SyncOption::Search => "--search".to_string(),
+            SyncOption::Refresh => "--refresh".to_string(),
+            SyncOption::SysUpgrade => "--sysupgrade".to_string(),
+            SyncOption::NoDeps => "--nodeps".to_string(),
+            SyncOption::Print => "--print".to_string(),
+            SyncOption::Quiet => "--quiet".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    #[test]
+    fn missing_binary() {
+        let _path = StubPath::isolated();
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Pacman.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::BinaryMissing {
+                binary: PACMAN_ALIAS.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn installed_packages() {
+        let path = StubPath::isolated();
+        path.stub(
+            "pacman",
+            "[ \"$1\" = --query ] || exit 1\necho 'ripgrep 14.1.0-1'\necho 'vim 9.1.0-1'",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Pacman
+            .filter_packages(rx, PackageLocality::Local, "rg".to_string())
+            .unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "ripgrep");
+        assert_eq!(packages[0].installed_version.as_deref(), Some("14.1.0-1"));
+    }
+
+    #[test]
+    fn remote_packages() {
+        let path = StubPath::isolated();
+        path.stub(
+            "pacman",
+            "[ \"$1 $2\" = '--sync --search' ] || exit 1\n\
+             echo 'extra/ripgrep-all 0.10.6-1 [installed: 0.10.5-1]'\n\
+             echo '    rga: ripgrep, but also search in PDFs'\n\
+             echo 'extra/ripgrep 14.1.0-1 [installed]'\n\
+             echo '    A search tool that combines the usability of ag with the raw speed of grep'\n\
+             echo 'extra/ugrep 6.0.0-1'\n\
+             echo '    Ultra fast grep with interactive query UI'",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Pacman
+            .filter_packages(rx, PackageLocality::Remote, "ripgrep".to_string())
+            .unwrap();
+        // results matching on their description only are listed last
+        assert_eq!(packages.len(), 3);
+        assert_eq!(packages[2].name, "ugrep");
+        assert_eq!(packages[0].name, "ripgrep");
+        assert_eq!(packages[0].repository.as_deref(), Some("extra"));
+        assert_eq!(packages[0].installed_version.as_deref(), Some("14.1.0-1"));
+        assert_eq!(
+            packages[0].description.as_deref(),
+            Some("A search tool that combines the usability of ag with the raw speed of grep")
+        );
+        assert_eq!(packages[1].name, "ripgrep-all");
+        assert_eq!(packages[1].version.as_deref(), Some("0.10.6-1"));
+        assert_eq!(packages[1].installed_version.as_deref(), Some("0.10.5-1"));
+    }
+
+    #[test]
+    fn outdated_packages() {
+        let path = StubPath::isolated();
+        path.stub(
+            "pacman",
+            "[ \"$1 $2\" = '--query --upgrades' ] || exit 1\n\
+             echo 'linux 6.9.1.arch1-1 -> 6.9.2.arch1-1'\n\
+             echo 'vim 9.1.0-1 -> 9.1.0-2 [ignored]'",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Pacman.outdated_packages(rx).unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "linux");
+        assert_eq!(
+            packages[0].installed_version.as_deref(),
+            Some("6.9.1.arch1-1")
+        );
+        assert_eq!(packages[0].version.as_deref(), Some("6.9.2.arch1-1"));
+        assert!(!packages[0].pinned);
+        assert_eq!(packages[1].name, "vim");
+        assert!(packages[1].pinned);
+    }
+
+    #[test]
+    fn nothing_outdated() {
+        let path = StubPath::isolated();
+        path.stub("pacman", "exit 1");
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(Pacman.outdated_packages(rx), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn failed_outdated_packages() {
+        let path = StubPath::isolated();
+        path.stub(
+            "pacman",
+            "echo 'error: failed to initialize alpm library' >&2\nexit 1",
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Pacman.outdated_packages(rx),
+            Err(Error::NonZeroExit {
+                command: PACMAN_ALIAS.to_string(),
+                code: Some(1),
+                stderr: "error: failed to initialize alpm library".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn requested_packages() {
+        let path = StubPath::isolated();
+        path.stub(
+            "pacman",
+            "[ \"$1 $2 $3\" = '--query --explicit --quiet' ] || exit 1\necho base\necho vim",
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Pacman.requested_packages(rx),
+            Ok(vec!["base".to_string(), "vim".to_string()])
+        );
+    }
+
+    #[test]
+    fn failed_requested_packages() {
+        let path = StubPath::isolated();
+        path.stub("pacman", "echo 'error: database not found' >&2\nexit 1");
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Pacman.requested_packages(rx),
+            Err(Error::NonZeroExit {
+                command: PACMAN_ALIAS.to_string(),
+                code: Some(1),
+                stderr: "error: database not found".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn dependencies_and_dependents() {
+        let path = StubPath::isolated();
+        path.stub(
+            "pacman",
+            "[ \"$1 $2 $3\" = '--query --info git' ] || exit 1\n\
+             echo 'Name            : git'\n\
+             echo 'Version         : 2.45.1-1'\n\
+             echo 'Depends On      : curl  expat  perl>=5.14.0  openssl'\n\
+             echo '                  pcre2  zlib'\n\
+             echo 'Optional Deps   : tk: gitk and git gui'\n\
+             echo 'Required By     : None'",
+        );
+        let (_tx, rx) = mpsc::channel();
+        let dependencies = Pacman.dependencies(rx, "git".to_string()).unwrap();
+        assert_eq!(
+            dependencies
+                .iter()
+                .map(|dependency| dependency.name.as_str())
+                .collect::<Vec<_>>(),
+            ["curl", "expat", "perl", "openssl", "pcre2", "zlib"]
+        );
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(Pacman.dependents(rx, "git".to_string()), Ok(Vec::new()));
+    }
+}
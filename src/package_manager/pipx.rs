@@ -0,0 +1,394 @@
+use std::{
+    collections::BTreeMap,
+    sync::mpsc::{Receiver, Sender},
+};
+
+use serde::Deserialize;
+
+use super::{
+    Backend, CleanupPreview, CommandResult, Dependency, HealthWarning, Package, PackageLocality,
+    PackageManager, SpawnCommandResult, SystemInfo, command,
+    error::{Error, checked},
+    fuzz_packages,
+    graph::DependencyGraph,
+    handle_spawned_command, spawn_command, stream_spawned_command,
+};
+
+pub struct Pipx;
+
+const PIPX_ALIAS: &str = "pipx";
+
+/// Output of `pipx list --json`
+#[derive(Deserialize)]
+struct Venvs {
+    venvs: BTreeMap<String, Venv>,
+}
+
+#[derive(Deserialize)]
+struct Venv {
+    metadata: VenvMetadata,
+}
+
+#[derive(Deserialize)]
+struct VenvMetadata {
+    main_package: MainPackage,
+}
+
+#[derive(Deserialize)]
+struct MainPackage {
+    package: String,
+    package_version: Option<String>,
+    /// Only reported by pipx 1.6 and later
+    #[serde(default)]
+    pinned: bool,
+}
+
+impl Pipx {
+    /// Install a package into its own virtual environment
+    fn pipx_install<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["install".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+
+        spawn_command(PIPX_ALIAS, args)
+    }
+
+    /// Upgrade a package in its virtual environment
+    fn pipx_upgrade<I, J>(options: Option<I>, package_list: J) -> SpawnCommandResult
+    where
+        I: IntoIterator<Item = InstallOption>,
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["upgrade".to_string()];
+        if let Some(options) = options {
+            args.extend(
+                options
+                    .into_iter()
+                    .map(|option: InstallOption| option.into()),
+            );
+        }
+        args.extend(package_list);
+
+        spawn_command(PIPX_ALIAS, args)
+    }
+
+    /// Uninstall a package along with its virtual environment
+    fn pipx_uninstall<J>(package_list: J) -> SpawnCommandResult
+    where
+        J: IntoIterator<Item = String>,
+    {
+        let mut args = vec!["uninstall".to_string()];
+        args.extend(package_list);
+
+        spawn_command(PIPX_ALIAS, args)
+    }
+
+    /// List installed packages and their virtual environments
+    fn pipx_list_json() -> CommandResult {
+        command(PIPX_ALIAS, ["list", "--json"])
+    }
+
+    /// List installed packages, reporting virtual environments that
+    /// are broken e.g. due to a missing interpreter
+    fn pipx_list() -> SpawnCommandResult {
+        spawn_command(PIPX_ALIAS, ["list"])
+    }
+
+    /// Show the metadata pip records for a package installed in the
+    /// virtual environment of the same name
+    fn pipx_runpip_show(package_name: String) -> SpawnCommandResult {
+        spawn_command(
+            PIPX_ALIAS,
+            [
+                "runpip".to_string(),
+                package_name.clone(),
+                "show".to_string(),
+                package_name,
+            ],
+        )
+    }
+
+    /// Prevent a package from being upgraded, or allow it again
+    fn pipx_pin(package_name: String, pinned: bool) -> CommandResult {
+        let subcommand = match pinned {
+            true => "pin",
+            false => "unpin",
+        };
+        command(PIPX_ALIAS, [subcommand.to_string(), package_name])
+    }
+
+    /// Print the environment variables and paths used by pipx
+    fn pipx_environment() -> CommandResult {
+        command(PIPX_ALIAS, ["environment"])
+    }
+}
+
+impl PackageManager for Pipx {
+    fn alias(&self) -> &'static str {
+        PIPX_ALIAS
+    }
+
+    fn filter_packages(
+        &self,
+        _rx: Receiver<bool>,
+        package_locality: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        match package_locality {
+            PackageLocality::Local => match Self::pipx_list_json() {
+                Ok(output) => match serde_json::from_slice::<Venvs>(&output.stdout) {
+                    Ok(venvs) => {
+                        let installed_packages = venvs
+                            .venvs
+                            .into_values()
+                            .map(|venv| {
+                                let main_package = venv.metadata.main_package;
+                                Package {
+                                    version: main_package.package_version.clone(),
+                                    installed_version: Some(
+                                        main_package.package_version.unwrap_or_default(),
+                                    ),
+                                    repository: Some("PyPI".to_string()),
+                                    pinned: main_package.pinned,
+                                    ..Package::new(main_package.package, Backend::Pipx)
+                                }
+                            })
+                            .collect::<Vec<Package>>();
+                        Ok(fuzz_packages(installed_packages, pattern))
+                    }
+                    Err(e) => Err(Error::parse("pipx list", e)),
+                },
+                Err(e) => Err(Error::from_io(PIPX_ALIAS, e)),
+            },
+            // PyPI no longer offers a search API, so there is nothing to query
+            PackageLocality::Remote => Err(Error::unsupported("searching PyPI with pipx")),
+        }
+    }
+
+    fn outdated_packages(&self, _rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        Err(Error::unsupported("listing outdated packages with pipx"))
+    }
+
+    fn package_manager_config(&self, _rx: Receiver<bool>) -> Result<String, Error> {
+        let output = checked(PIPX_ALIAS, Self::pipx_environment())?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn system_info(&self, _rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        Err(Error::unsupported("reading the system information of pipx"))
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let child =
+            Self::pipx_runpip_show(package_name).map_err(|e| Error::from_io(PIPX_ALIAS, e))?;
+        Ok(handle_spawned_command(rx, child)?
+            .success(PIPX_ALIAS)?
+            .out
+            .unwrap_or_default())
+    }
+
+    fn dependencies(
+        &self,
+        _rx: Receiver<bool>,
+        _package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        Err(Error::unsupported(
+            "listing the dependencies of pipx packages",
+        ))
+    }
+
+    fn dependents(&self, _rx: Receiver<bool>, _package_name: String) -> Result<Vec<String>, Error> {
+        Err(Error::unsupported(
+            "listing the dependents of pipx packages",
+        ))
+    }
+
+    fn dependency_graph(&self, _rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        Err(Error::unsupported(
+            "exporting the dependency graph of pipx packages",
+        ))
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        let child = Self::pipx_list().map_err(|e| Error::from_io(PIPX_ALIAS, e))?;
+        // broken virtual environments are reported on stderr
+        stream_spawned_command(rx, tx, child)?.report(PIPX_ALIAS)
+    }
+
+    fn health_warnings(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "splitting the health check of pipx into warnings",
+        ))
+    }
+
+    fn run_health_check(
+        &self,
+        _rx: Receiver<bool>,
+        _tx: Sender<String>,
+        _check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        Err(Error::unsupported(
+            "running individual health checks with pipx",
+        ))
+    }
+
+    fn clean_preview(&self, _rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        Err(Error::unsupported("cleaning installed packages with pipx"))
+    }
+
+    fn clean(&self, _rx: Receiver<bool>, _tx: Sender<String>) -> Result<String, Error> {
+        Err(Error::unsupported("cleaning installed packages with pipx"))
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::pipx_install::<Vec<InstallOption>, _>(None, [package_name])
+            .map_err(|e| Error::from_io(PIPX_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(PIPX_ALIAS)?
+            .combined())
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child = Self::pipx_upgrade::<Vec<InstallOption>, _>(None, [package_name])
+            .map_err(|e| Error::from_io(PIPX_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(PIPX_ALIAS)?
+            .combined())
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        let child =
+            Self::pipx_uninstall([package_name]).map_err(|e| Error::from_io(PIPX_ALIAS, e))?;
+
+        Ok(stream_spawned_command(rx, tx, child)?
+            .success(PIPX_ALIAS)?
+            .combined())
+    }
+
+    fn pin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let output = checked(PIPX_ALIAS, Self::pipx_pin(package_name, true))?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn unpin_package(&self, _rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        let output = checked(PIPX_ALIAS, Self::pipx_pin(package_name, false))?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+pub enum InstallOption {
+    Force,
+    IncludeDeps,
+    SystemSitePackages,
+    Verbose,
+    Quiet,
+}
+
+impl From<InstallOption> for String {
+    fn from(value: InstallOption) -> Self {
+        match value {
+            InstallOption::Force => "--force".to_string(),
+            InstallOption::IncludeDeps => "--include-deps".to_string(),
+            InstallOption::SystemSitePackages => "--system-site-packages".to_string(),
+            InstallOption::Verbose => "--verbose".to_string(),
+            InstallOption::Quiet => "--quiet".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::package_manager::stub::StubPath;
+
+    #[test]
+    fn missing_binary() {
+        let _path = StubPath::isolated();
+        let (_tx, rx) = mpsc::channel();
+        assert_eq!(
+            Pipx.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::BinaryMissing {
+                binary: PIPX_ALIAS.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn installed_packages() {
+        let path = StubPath::isolated();
+        path.stub(
+            "pipx",
+            r#"[ "$1 $2" = 'list --json' ] || exit 1
+echo '{"pipx_spec_version":"0.1","venvs":{"black":{"metadata":{"main_package":{"package":"black","package_version":"24.4.2","pinned":true}}},"httpie":{"metadata":{"main_package":{"package":"httpie","package_version":"3.2.2"}}}}}'"#,
+        );
+        let (_tx, rx) = mpsc::channel();
+        let packages = Pipx
+            .filter_packages(rx, PackageLocality::Local, String::default())
+            .unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "black");
+        assert_eq!(packages[0].installed_version.as_deref(), Some("24.4.2"));
+        assert!(packages[0].pinned);
+        // pipx before 1.6 does not report pins
+        assert_eq!(packages[1].name, "httpie");
+        assert!(!packages[1].pinned);
+    }
+
+    #[test]
+    fn malformed_list() {
+        let path = StubPath::isolated();
+        path.stub("pipx", "echo 'nothing has been installed with pipx'");
+        let (_tx, rx) = mpsc::channel();
+        assert!(matches!(
+            Pipx.filter_packages(rx, PackageLocality::Local, String::default()),
+            Err(Error::Parse { command, .. }) if command == "pipx list"
+        ));
+    }
+
+    #[test]
+    fn search_and_outdated_unsupported() {
+        let (_tx, rx) = mpsc::channel();
+        assert!(matches!(
+            Pipx.filter_packages(rx, PackageLocality::Remote, "black".to_string()),
+            Err(Error::Unsupported { .. })
+        ));
+        let (_tx, rx) = mpsc::channel();
+        assert!(matches!(
+            Pipx.outdated_packages(rx),
+            Err(Error::Unsupported { .. })
+        ));
+    }
+}
@@ -0,0 +1,282 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    mpsc::{Receiver, Sender},
+};
+
+use tracing::{info, warn};
+
+use super::{
+    Backend, BrewfilePlan, CleanupPreview, Dependency, HealthWarning, Package, PackageLocality,
+    PackageManager, Service, ServiceAction, SystemInfo, Tap, aggregate::Aggregate, error::Error,
+    graph::DependencyGraph,
+};
+
+/// Collection of the backends detected on this machine, one of which is
+/// active at a time. Every `PackageManager` call is forwarded to the active
+/// backend, or to all backends at once while aggregated, so the backend can
+/// be switched at runtime.
+pub struct Registry {
+    backends: Vec<(Backend, Arc<dyn PackageManager>)>,
+    aggregate: Arc<Aggregate>,
+    active: AtomicUsize,
+    aggregated: AtomicBool,
+}
+
+impl Registry {
+    pub fn new<I>(backends: I) -> Self
+    where
+        I: IntoIterator<Item = Backend>,
+    {
+        let backends = backends
+            .into_iter()
+            .map(|backend| (backend, backend.package_manager()))
+            .collect::<Vec<_>>();
+        Self {
+            aggregate: Arc::new(Aggregate::new(backends.clone())),
+            backends,
+            active: AtomicUsize::new(0),
+            aggregated: AtomicBool::new(false),
+        }
+    }
+
+    /// Probe PATH for every supported backend, making the most preferred
+    /// of the available backends the active one
+    pub fn detect() -> Self {
+        let available = Backend::ALL
+            .into_iter()
+            .filter(|backend| backend.is_available())
+            .collect::<Vec<Backend>>();
+        info!("detected package managers {available:?}");
+        if available.is_empty() {
+            warn!("no supported package manager found on PATH, falling back to Homebrew");
+            return Self::new([Backend::Homebrew]);
+        }
+        Self::new(available)
+    }
+
+    pub fn available(&self) -> Vec<Backend> {
+        self.backends.iter().map(|(backend, _)| *backend).collect()
+    }
+
+    pub fn active(&self) -> Backend {
+        self.backends[self.active.load(Ordering::SeqCst)].0
+    }
+
+    /// Make the given backend the active one, returning false if it was
+    /// not detected
+    pub fn select(&self, backend: Backend) -> bool {
+        match self.backends.iter().position(|(b, _)| *b == backend) {
+            Some(index) => {
+                self.active.store(index, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Activate the backend following the active one, wrapping around
+    pub fn select_next(&self) -> Backend {
+        let index = (self.active.load(Ordering::SeqCst) + 1) % self.backends.len();
+        self.active.store(index, Ordering::SeqCst);
+        self.active()
+    }
+
+    /// Activate the backend preceding the active one, wrapping around
+    pub fn select_previous(&self) -> Backend {
+        let len = self.backends.len();
+        let index = (self.active.load(Ordering::SeqCst) + len - 1) % len;
+        self.active.store(index, Ordering::SeqCst);
+        self.active()
+    }
+
+    pub fn is_aggregated(&self) -> bool {
+        self.aggregated.load(Ordering::SeqCst)
+    }
+
+    /// Forward calls to all backends at once instead of the active one
+    pub fn set_aggregated(&self, aggregated: bool) {
+        self.aggregated.store(aggregated, Ordering::SeqCst);
+    }
+
+    fn package_manager(&self) -> Arc<dyn PackageManager> {
+        if self.is_aggregated() {
+            return self.aggregate.clone();
+        }
+        self.backends[self.active.load(Ordering::SeqCst)].1.clone()
+    }
+}
+
+impl PackageManager for Registry {
+    fn alias(&self) -> &'static str {
+        self.package_manager().alias()
+    }
+
+    fn filter_packages(
+        &self,
+        rx: Receiver<bool>,
+        source: PackageLocality,
+        pattern: String,
+    ) -> Result<Vec<Package>, Error> {
+        self.package_manager().filter_packages(rx, source, pattern)
+    }
+
+    fn outdated_packages(&self, rx: Receiver<bool>) -> Result<Vec<Package>, Error> {
+        self.package_manager().outdated_packages(rx)
+    }
+
+    fn requested_packages(&self, rx: Receiver<bool>) -> Result<Vec<String>, Error> {
+        self.package_manager().requested_packages(rx)
+    }
+
+    fn package_manager_config(&self, rx: Receiver<bool>) -> Result<String, Error> {
+        self.package_manager().package_manager_config(rx)
+    }
+
+    fn system_info(&self, rx: Receiver<bool>) -> Result<SystemInfo, Error> {
+        self.package_manager().system_info(rx)
+    }
+
+    fn package_info(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        self.package_manager().package_info(rx, package_name)
+    }
+
+    fn dependencies(
+        &self,
+        rx: Receiver<bool>,
+        package_name: String,
+    ) -> Result<Vec<Dependency>, Error> {
+        self.package_manager().dependencies(rx, package_name)
+    }
+
+    fn dependents(&self, rx: Receiver<bool>, package_name: String) -> Result<Vec<String>, Error> {
+        self.package_manager().dependents(rx, package_name)
+    }
+
+    fn dependency_graph(&self, rx: Receiver<bool>) -> Result<DependencyGraph, Error> {
+        self.package_manager().dependency_graph(rx)
+    }
+
+    fn check_health(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        self.package_manager().check_health(rx, tx)
+    }
+
+    fn health_warnings(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        self.package_manager().health_warnings(rx, tx)
+    }
+
+    fn run_health_check(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        check: String,
+    ) -> Result<Vec<HealthWarning>, Error> {
+        self.package_manager().run_health_check(rx, tx, check)
+    }
+
+    fn clean_preview(&self, rx: Receiver<bool>) -> Result<CleanupPreview, Error> {
+        self.package_manager().clean_preview(rx)
+    }
+
+    fn clean(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        self.package_manager().clean(rx, tx)
+    }
+
+    fn install_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        self.package_manager().install_package(rx, tx, package_name)
+    }
+
+    fn update_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        self.package_manager().update_package(rx, tx, package_name)
+    }
+
+    fn uninstall_package(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        package_name: String,
+    ) -> Result<String, Error> {
+        self.package_manager()
+            .uninstall_package(rx, tx, package_name)
+    }
+
+    fn pin_package(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        self.package_manager().pin_package(rx, package_name)
+    }
+
+    fn unpin_package(&self, rx: Receiver<bool>, package_name: String) -> Result<String, Error> {
+        self.package_manager().unpin_package(rx, package_name)
+    }
+
+    fn taps(&self, rx: Receiver<bool>) -> Result<Vec<Tap>, Error> {
+        self.package_manager().taps(rx)
+    }
+
+    fn add_tap(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        tap: String,
+    ) -> Result<String, Error> {
+        self.package_manager().add_tap(rx, tx, tap)
+    }
+
+    fn remove_tap(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        tap: String,
+    ) -> Result<String, Error> {
+        self.package_manager().remove_tap(rx, tx, tap)
+    }
+
+    fn services(&self, rx: Receiver<bool>) -> Result<Vec<Service>, Error> {
+        self.package_manager().services(rx)
+    }
+
+    fn control_service(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        action: ServiceAction,
+        service: String,
+    ) -> Result<String, Error> {
+        self.package_manager()
+            .control_service(rx, tx, action, service)
+    }
+
+    fn cleanup_services(&self, rx: Receiver<bool>, tx: Sender<String>) -> Result<String, Error> {
+        self.package_manager().cleanup_services(rx, tx)
+    }
+
+    fn export_brewfile(&self, rx: Receiver<bool>, path: String) -> Result<String, Error> {
+        self.package_manager().export_brewfile(rx, path)
+    }
+
+    fn brewfile_plan(&self, rx: Receiver<bool>, path: String) -> Result<BrewfilePlan, Error> {
+        self.package_manager().brewfile_plan(rx, path)
+    }
+
+    fn import_brewfile(
+        &self,
+        rx: Receiver<bool>,
+        tx: Sender<String>,
+        path: String,
+    ) -> Result<String, Error> {
+        self.package_manager().import_brewfile(rx, tx, path)
+    }
+}
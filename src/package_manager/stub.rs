@@ -0,0 +1,89 @@
+//! Stub executables put on PATH so that backends can be tested without the
+//! package managers they drive
+
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    process,
+    sync::{
+        Mutex, MutexGuard,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// PATH is shared by every test of the process, so tests using stubs take
+/// turns
+static PATH_LOCK: Mutex<()> = Mutex::new(());
+static NEXT_DIRECTORY: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory of stub executables searched by commands instead of, or ahead
+/// of, the usual PATH until dropped
+pub struct StubPath {
+    directory: PathBuf,
+    previous: Option<OsString>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl StubPath {
+    /// PATH holding nothing but the stubs, so that every other binary is
+    /// missing
+    pub fn isolated() -> Self {
+        Self::new(false)
+    }
+
+    /// PATH with the stubs ahead of the usual PATH, so that stubs can run
+    /// standard utilities such as `sleep`
+    pub fn prepended() -> Self {
+        Self::new(true)
+    }
+
+    fn new(keep_path: bool) -> Self {
+        let lock = PATH_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let directory = env::temp_dir().join(format!(
+            "wherehouse-stubs-{}-{}",
+            process::id(),
+            NEXT_DIRECTORY.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&directory).expect("failed to create the stub directory");
+        let previous = env::var_os("PATH");
+        let mut paths = vec![directory.clone()];
+        if keep_path && let Some(previous) = &previous {
+            paths.extend(env::split_paths(previous));
+        }
+        let path = env::join_paths(paths).expect("failed to join PATH");
+        // SAFETY: PATH is only changed while holding `PATH_LOCK`
+        unsafe { env::set_var("PATH", path) };
+        Self {
+            directory,
+            previous,
+            _lock: lock,
+        }
+    }
+
+    /// Add an executable named `name` running the shell `script`
+    pub fn stub(&self, name: &str, script: &str) -> &Self {
+        let path = self.directory.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{script}\n")).expect("failed to write the stub");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .expect("failed to make the stub executable");
+        self
+    }
+}
+
+impl Drop for StubPath {
+    fn drop(&mut self) {
+        // SAFETY: PATH is only changed while holding `PATH_LOCK`
+        unsafe {
+            match &self.previous {
+                Some(previous) => env::set_var("PATH", previous),
+                None => env::remove_var("PATH"),
+            }
+        }
+        let _ = fs::remove_dir_all(&self.directory);
+    }
+}
@@ -1,12 +1,19 @@
 use std::{
+    collections::{BTreeSet, HashSet},
     fmt::Display,
+    fs, io,
+    path::PathBuf,
     sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
 };
 
 use ratatui::widgets::ListState;
-use wherehouse::package_manager::PackageLocality;
+use wherehouse::package_manager::{
+    Backend, Command, Dependency, HealthWarning, Package, PackageLocality, Service, SystemInfo,
+    Tap, error::Error,
+};
 
-use crate::commands::PackageManager;
+use crate::logging::get_data_dir;
 
 #[derive(Clone, Copy)]
 pub enum InputMode {
@@ -18,6 +25,9 @@ pub enum InputMode {
 pub enum Pane {
     SearchInput,
     SearchResults,
+    Outdated,
+    Taps,
+    Services,
     Info,
     Context,
 }
@@ -31,22 +41,125 @@ impl Display for InputMode {
     }
 }
 
-impl Display for SearchSource {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Local => write!(f, "Local"),
-            Self::Remote => write!(f, "Remote"),
+pub type SearchResults = Vec<Package>;
+
+/// A package operation waiting for the user to confirm it
+#[derive(Clone)]
+pub struct Confirmation {
+    pub command: Command,
+    pub package_names: Vec<String>,
+    /// Number of pinned packages left out of a bulk upgrade
+    pub skipped: usize,
+    pub package_manager: Backend,
+    pub aggregated: bool,
+    /// Backends offering the package in aggregated mode, the operation
+    /// runs with the one at `source`
+    pub sources: Vec<Backend>,
+    pub source: usize,
+    /// Qualified names of the packages a manifest is applied by
+    /// uninstalling, listed for the user to review. Nothing else is
+    /// uninstalled once confirmed
+    pub removals: Vec<String>,
+}
+
+impl Confirmation {
+    /// Lines listed below the prompt for the user to review: the exact
+    /// commands of a health fix, or the packages a manifest uninstalls
+    pub fn listed(&self) -> &[String] {
+        match self.command {
+            Command::RunHealthFix => &self.package_names,
+            _ => &self.removals,
         }
     }
-}
 
-#[derive(Copy, Clone)]
-pub enum SearchSource {
-    Remote,
-    Local,
+    /// Package names to run the operation on, qualified with the chosen
+    /// backend in aggregated mode
+    pub fn targets(&self) -> Vec<String> {
+        match (self.aggregated, self.sources.get(self.source)) {
+            (true, Some(backend)) => self
+                .package_names
+                .iter()
+                .map(|package_name| backend.qualify(package_name))
+                .collect(),
+            _ => self.package_names.clone(),
+        }
+    }
+
+    /// Run the operation with the next backend offering the package
+    pub fn next_source(&mut self) {
+        if !self.sources.is_empty() {
+            self.source = (self.source + 1) % self.sources.len();
+        }
+    }
+
+    pub fn prompt(&self) -> String {
+        let action = match self.command {
+            Command::InstallPackage => "Install",
+            Command::UninstallPackage => "Uninstall",
+            Command::UpdatePackage => "Upgrade",
+            Command::PinPackage => "Pin",
+            Command::UnpinPackage => "Unpin",
+            Command::RemoveTap => "Untap",
+            Command::Clean => {
+                return match self.aggregated {
+                    true => "Clean up every backend?".to_string(),
+                    false => format!("Clean up {}?", self.package_manager),
+                };
+            }
+            Command::ImportBrewfile => {
+                return format!(
+                    "Install and uninstall what is listed to match {}?",
+                    self.package_names.join(", ")
+                );
+            }
+            Command::ApplyManifest => {
+                let prompt = format!(
+                    "Install and upgrade what is listed to match {}?",
+                    self.package_names.join(", ")
+                );
+                return match self.removals.len() {
+                    0 => prompt,
+                    1 => format!("{prompt} 1 package is uninstalled:"),
+                    removals => format!("{prompt} {removals} packages are uninstalled:"),
+                };
+            }
+            Command::CleanupServices => {
+                return "Remove the services of packages that are no longer installed?".to_string();
+            }
+            Command::RunHealthFix => {
+                return match self.package_names.len() {
+                    1 => "Run this command, as it is and without a shell?".to_string(),
+                    fixes => {
+                        format!("Run these {fixes} commands, as they are and without a shell?")
+                    }
+                };
+            }
+            _ => "Run",
+        };
+        let packages = match &self.package_names[..] {
+            [package_name] => package_name.clone(),
+            package_names => format!("{} packages", package_names.len()),
+        };
+        let prompt = match (self.aggregated, self.sources.get(self.source)) {
+            (false, _) => format!("{action} {packages} with {}?", self.package_manager),
+            (true, Some(backend)) => format!("{action} {packages} with {backend}?"),
+            (true, None) => format!("{action} {packages}?"),
+        };
+        match self.skipped {
+            0 => prompt,
+            1 => format!("{prompt} 1 pinned package is skipped"),
+            skipped => format!("{prompt} {skipped} pinned packages are skipped"),
+        }
+    }
 }
 
-pub type SearchResults = Vec<String>;
+/// Text the user is asked to type before `command` is run with it
+#[derive(Clone)]
+pub struct Prompt {
+    pub command: Command,
+    pub label: String,
+    pub input: String,
+}
 
 pub struct SearchState {
     pub query: String,
@@ -57,32 +170,339 @@ pub struct SearchState {
     pub source: PackageLocality,
 }
 
+/// Installed packages with a newer version available, some of which may
+/// be marked for upgrading
+#[derive(Default)]
+pub struct OutdatedState {
+    pub packages: Vec<Package>,
+    /// Names of the packages marked for upgrading
+    pub selected: HashSet<String>,
+    pub list_state: ListState,
+}
+
+impl OutdatedState {
+    /// The marked packages, or the highlighted package when nothing is
+    /// marked
+    pub fn selection(&self) -> Vec<&Package> {
+        let marked = self
+            .packages
+            .iter()
+            .filter(|package| self.selected.contains(&package.name))
+            .collect::<Vec<&Package>>();
+        if !marked.is_empty() {
+            return marked;
+        }
+        self.highlighted().into_iter().collect()
+    }
+
+    pub fn highlighted(&self) -> Option<&Package> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.packages.get(index))
+    }
+}
+
+/// Taps added to the active backend
+#[derive(Default)]
+pub struct TapsState {
+    pub taps: Vec<Tap>,
+    pub list_state: ListState,
+}
+
+impl TapsState {
+    pub fn highlighted(&self) -> Option<&Tap> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.taps.get(index))
+    }
+}
+
+/// Background services of the active backend
+#[derive(Default)]
+pub struct ServicesState {
+    pub services: Vec<Service>,
+    pub list_state: ListState,
+}
+
+impl ServicesState {
+    pub fn highlighted(&self) -> Option<&Service> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.services.get(index))
+    }
+}
+
+/// Warnings reported by the health checks of the active backend
+#[derive(Default)]
+pub struct HealthState {
+    pub warnings: Vec<HealthWarning>,
+    /// Titles of the warnings that are not shown, kept across sessions
+    pub ignored: BTreeSet<String>,
+    pub list_state: ListState,
+    /// Whether the health checks ran since the backend was selected
+    pub checked: bool,
+    /// Whether the health checks were asked for since the backend was
+    /// selected, the search results keep their place until then
+    pub requested: bool,
+}
+
+impl HealthState {
+    pub fn new() -> Self {
+        let ignored = fs::read_to_string(Self::ignored_path())
+            .map(|ignored| ignored.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        Self {
+            ignored,
+            ..Self::default()
+        }
+    }
+
+    fn ignored_path() -> PathBuf {
+        get_data_dir().join("ignored_warnings")
+    }
+
+    /// Write the ignored warnings so that they stay hidden next time
+    pub fn save_ignored(&self) -> io::Result<()> {
+        let ignored = self
+            .ignored
+            .iter()
+            .map(|title| format!("{title}\n"))
+            .collect::<String>();
+        fs::create_dir_all(get_data_dir())?;
+        fs::write(Self::ignored_path(), ignored)
+    }
+
+    /// Warnings that are not ignored, in the order they were reported
+    pub fn visible(&self) -> Vec<&HealthWarning> {
+        self.warnings
+            .iter()
+            .filter(|warning| !self.ignored.contains(&warning.title))
+            .collect()
+    }
+
+    pub fn highlighted(&self) -> Option<&HealthWarning> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.visible().get(index).copied())
+    }
+
+    /// Keep the highlight on a visible warning after the list changed
+    pub fn clamp_selection(&mut self) {
+        let count = self.visible().len();
+        let selected = match count {
+            0 => None,
+            _ => Some(self.list_state.selected().unwrap_or(0).min(count - 1)),
+        };
+        self.list_state.select(selected);
+    }
+
+    /// Details of the highlighted warning, or how many there are when none
+    /// is left to highlight
+    pub fn summary(&self) -> String {
+        if let Some(warning) = self.highlighted() {
+            return warning.to_string();
+        }
+        let ignored = self.warnings.len() - self.visible().len();
+        match ignored {
+            0 => "==> No health warnings".to_string(),
+            1 => "==> No health warnings, 1 is ignored".to_string(),
+            ignored => format!("==> No health warnings, {ignored} are ignored"),
+        }
+    }
+}
+
+/// Side of the dependency graph a node of the explorer belongs to
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// Groups the nodes below it, or explains why there are none
+    Heading,
+    /// Package the explored package depends on
+    Dependency,
+    /// Installed package depending on the explored package
+    Dependent,
+}
+
+pub struct TreeNode {
+    pub name: String,
+    pub kind: NodeKind,
+    pub children: Vec<TreeNode>,
+    pub expanded: bool,
+    /// Dependents are only queried for their own dependents once they
+    /// are expanded
+    pub loaded: bool,
+}
+
+impl TreeNode {
+    pub fn heading(name: String, children: Vec<TreeNode>) -> Self {
+        Self {
+            name,
+            kind: NodeKind::Heading,
+            children,
+            expanded: true,
+            loaded: true,
+        }
+    }
+    pub fn dependency(dependency: Dependency) -> Self {
+        Self {
+            name: dependency.name,
+            kind: NodeKind::Dependency,
+            children: dependency
+                .dependencies
+                .into_iter()
+                .map(Self::dependency)
+                .collect(),
+            expanded: false,
+            loaded: true,
+        }
+    }
+    pub fn dependent(name: String) -> Self {
+        Self {
+            name,
+            kind: NodeKind::Dependent,
+            children: Vec::new(),
+            expanded: false,
+            loaded: false,
+        }
+    }
+    /// Whether expanding the node shows anything, or has to query it first
+    pub fn is_expandable(&self) -> bool {
+        !self.children.is_empty() || !self.loaded
+    }
+}
+
+/// Dependencies and dependents of a package, explored as a tree in place
+/// of the context pane
+#[derive(Default)]
+pub struct DependencyTreeState {
+    /// Package the tree is rooted at, qualified with the backend it was
+    /// looked up with in aggregated mode
+    pub package_name: String,
+    pub roots: Vec<TreeNode>,
+    /// Index of the highlighted row among the visible rows
+    pub selected: usize,
+    /// Path of the dependent whose own dependents are being queried
+    pub pending: Option<Vec<usize>>,
+}
+
+impl DependencyTreeState {
+    /// `package_name` qualified with the backend the root was looked up
+    /// with, so that the whole tree is explored with the same backend
+    pub fn qualify(&self, package_name: &str) -> String {
+        match Backend::unqualify(&self.package_name) {
+            Some((backend, _)) => backend.qualify(package_name),
+            None => package_name.to_string(),
+        }
+    }
+
+    /// Nodes that are not hidden inside a collapsed node, in the order
+    /// they are drawn, along with the child indices leading to them
+    pub fn rows(&self) -> Vec<(Vec<usize>, &TreeNode)> {
+        fn visit<'a>(
+            nodes: &'a [TreeNode],
+            path: &mut Vec<usize>,
+            rows: &mut Vec<(Vec<usize>, &'a TreeNode)>,
+        ) {
+            for (index, node) in nodes.iter().enumerate() {
+                path.push(index);
+                rows.push((path.clone(), node));
+                if node.expanded {
+                    visit(&node.children, path, rows);
+                }
+                path.pop();
+            }
+        }
+        let mut rows = Vec::new();
+        visit(&self.roots, &mut Vec::new(), &mut rows);
+        rows
+    }
+    pub fn selected_path(&self) -> Option<Vec<usize>> {
+        self.rows()
+            .into_iter()
+            .nth(self.selected)
+            .map(|(path, _node)| path)
+    }
+    pub fn node(&self, path: &[usize]) -> Option<&TreeNode> {
+        let (first, rest) = path.split_first()?;
+        rest.iter()
+            .try_fold(self.roots.get(*first)?, |node, index| {
+                node.children.get(*index)
+            })
+    }
+    pub fn node_mut(&mut self, path: &[usize]) -> Option<&mut TreeNode> {
+        let (first, rest) = path.split_first()?;
+        rest.iter()
+            .try_fold(self.roots.get_mut(*first)?, |node, index| {
+                node.children.get_mut(*index)
+            })
+    }
+}
+
+/// A long running command whose output is tailed in the context pane
+#[derive(Clone)]
+pub struct Operation {
+    pub command: Command,
+    pub label: String,
+    pub started: Instant,
+}
+
 pub struct Config {
-    pub package_manager: PackageManager,
+    pub package_manager: Backend,
+    pub available_package_managers: Vec<Backend>,
+    /// Whether queries are fanned out to every available backend
+    pub aggregated: bool,
     pub package_manager_version: String,
     pub system_config: String,
+    /// `system_config` split into fields, for backends able to do so
+    pub system_info: Option<SystemInfo>,
     pub app_version: String,
     pub app_name: String,
 }
 
+impl Config {
+    /// Fields of the system configuration lined up as a table, or the
+    /// configuration as reported when it could not be split into fields
+    pub fn system_report(&self) -> String {
+        match &self.system_info {
+            Some(system_info) => system_info.to_string(),
+            None => self.system_config.clone(),
+        }
+    }
+}
+
 pub struct State {
     pub current_pane: Arc<Mutex<Pane>>,
     pub input_mode: Arc<Mutex<InputMode>>,
     pub search: Arc<Mutex<SearchState>>,
+    pub outdated: Arc<Mutex<OutdatedState>>,
+    pub taps: Arc<Mutex<TapsState>>,
+    pub services: Arc<Mutex<ServicesState>>,
+    pub health: Arc<Mutex<HealthState>>,
+    pub dependency_tree: Arc<Mutex<DependencyTreeState>>,
     pub should_quit: Arc<Mutex<bool>>,
     pub config: Arc<Mutex<Config>>,
     pub healthcheck_results: Arc<Mutex<String>>,
     pub context_content: Arc<Mutex<String>>,
+    /// Latest failure along with the command that caused it
+    pub error: Arc<Mutex<Option<(Command, Error)>>>,
+    pub confirmation: Arc<Mutex<Option<Confirmation>>>,
+    pub prompt: Arc<Mutex<Option<Prompt>>>,
+    pub operation: Arc<Mutex<Option<Operation>>>,
+    /// Set once a package operation changed what is installed, so that
+    /// the search results are queried again
+    pub refresh: Arc<Mutex<bool>>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            package_manager: PackageManager::Homebrew,
+            package_manager: Backend::Homebrew,
+            available_package_managers: Vec::default(),
+            aggregated: false,
             package_manager_version: String::default(),
             app_version: String::default(),
             app_name: String::from("WhereHouse"),
             system_config: String::default(),
+            system_info: None,
         }
     }
 }
@@ -106,10 +526,20 @@ impl State {
             current_pane: Arc::new(Mutex::new(Pane::SearchInput)),
             input_mode: Arc::new(Mutex::new(InputMode::Insert)),
             search: Arc::new(Mutex::new(SearchState::default())),
+            outdated: Arc::new(Mutex::new(OutdatedState::default())),
+            taps: Arc::new(Mutex::new(TapsState::default())),
+            services: Arc::new(Mutex::new(ServicesState::default())),
+            health: Arc::new(Mutex::new(HealthState::new())),
+            dependency_tree: Arc::new(Mutex::new(DependencyTreeState::default())),
             should_quit: Arc::new(Mutex::new(false)),
             config: Arc::new(Mutex::new(Config::default())),
             healthcheck_results: Arc::new(Mutex::new(String::default())),
             context_content: Arc::new(Mutex::new(String::default())),
+            error: Arc::new(Mutex::new(None)),
+            confirmation: Arc::new(Mutex::new(None)),
+            prompt: Arc::new(Mutex::new(None)),
+            operation: Arc::new(Mutex::new(None)),
+            refresh: Arc::new(Mutex::new(false)),
         }
     }
     pub fn current_pane(&self) -> MutexGuard<'_, Pane> {
@@ -119,4 +549,191 @@ impl State {
         let mut context_content = self.context_content.lock().unwrap();
         *context_content = content;
     }
+    pub fn append_context(&self, line: &str) {
+        let mut context_content = self.context_content.lock().unwrap();
+        context_content.push_str(line);
+        context_content.push('\n');
+    }
+    /// Show `label` above an emptied context pane until the operation
+    /// started at the returned instant is finished
+    pub fn start_operation(&self, command: Command, label: String) -> Instant {
+        let started = Instant::now();
+        *self.operation.lock().unwrap() = Some(Operation {
+            command,
+            label,
+            started,
+        });
+        self.update_context(String::default());
+        started
+    }
+    /// Stop showing the operation started at `started`, unless it has
+    /// already been replaced by a newer one
+    pub fn finish_operation(&self, started: Instant) {
+        let mut operation = self.operation.lock().unwrap();
+        if operation
+            .as_ref()
+            .is_some_and(|operation| operation.started == started)
+        {
+            *operation = None;
+        }
+    }
+    /// Ask the user to confirm running `command` on `package_names` with
+    /// the active backend
+    pub fn request_confirmation(&self, command: Command, package_names: Vec<String>) {
+        self.confirm(command, package_names, 0, Vec::new(), Vec::new());
+    }
+    /// Ask the user to confirm applying the manifest at `path`, which
+    /// uninstalls `removals` along the way
+    pub fn request_manifest_confirmation(&self, path: String, removals: Vec<String>) {
+        self.confirm(Command::ApplyManifest, vec![path], 0, Vec::new(), removals);
+    }
+    /// Ask the user to confirm running `command` on `package`, along with
+    /// the backend to run it with when several backends offer it
+    pub fn request_package_confirmation(&self, command: Command, package: &Package) {
+        self.confirm(
+            command,
+            vec![package.name.clone()],
+            0,
+            package.sources.clone(),
+            Vec::new(),
+        );
+    }
+    /// Ask the user to confirm upgrading `packages`, leaving out the pinned
+    /// ones. Nothing is asked when every one of them is pinned
+    pub fn request_upgrade(&self, packages: Vec<&Package>) {
+        let (pinned, upgradable): (Vec<&Package>, Vec<&Package>) =
+            packages.into_iter().partition(|package| package.pinned);
+        if upgradable.is_empty() {
+            if !pinned.is_empty() {
+                self.update_context(format!(
+                    "Nothing to upgrade, every selected package is pinned\n{}\n",
+                    pinned
+                        .iter()
+                        .map(|package| package.name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join("\n")
+                ));
+            }
+            return;
+        }
+        if let [package] = upgradable[..] {
+            let package_names = vec![package.name.clone()];
+            self.confirm(
+                Command::UpdatePackage,
+                package_names,
+                pinned.len(),
+                package.sources.clone(),
+                Vec::new(),
+            );
+            return;
+        }
+        // each outdated package was reported by a single backend, which
+        // is the one upgrading it
+        let aggregated = self.config.lock().unwrap().aggregated;
+        let package_names = upgradable
+            .iter()
+            .map(|package| match (aggregated, package.sources.first()) {
+                (true, Some(backend)) => backend.qualify(&package.name),
+                _ => package.name.clone(),
+            })
+            .collect();
+        self.confirm(
+            Command::UpdatePackage,
+            package_names,
+            pinned.len(),
+            Vec::new(),
+            Vec::new(),
+        );
+    }
+    fn confirm(
+        &self,
+        command: Command,
+        package_names: Vec<String>,
+        skipped: usize,
+        sources: Vec<Backend>,
+        removals: Vec<String>,
+    ) {
+        let config = self.config.lock().unwrap();
+        *self.confirmation.lock().unwrap() = Some(Confirmation {
+            command,
+            package_names,
+            skipped,
+            package_manager: config.package_manager,
+            aggregated: config.aggregated,
+            sources,
+            source: 0,
+            removals,
+        });
+    }
+    /// Ask the user to type the input of `command`, starting out with
+    /// `input` so that a sensible default only has to be confirmed
+    pub fn request_input(&self, command: Command, label: String, input: String) {
+        *self.prompt.lock().unwrap() = Some(Prompt {
+            command,
+            label,
+            input,
+        });
+    }
+    pub fn set_error(&self, command: Command, error: Error) {
+        *self.error.lock().unwrap() = Some((command, error));
+    }
+    /// Dismiss the error reported by `command` once it succeeds, leaving
+    /// errors reported by other commands in place
+    pub fn clear_error(&self, command: Command) {
+        let mut error = self.error.lock().unwrap();
+        if error.as_ref().is_some_and(|(failed, _)| *failed == command) {
+            *error = None;
+        }
+    }
+    /// Reflect the active backend, discarding everything that was
+    /// reported by the previously active one
+    pub fn update_package_manager(&self, package_manager: Backend, available: Vec<Backend>) {
+        let mut config = self.config.lock().unwrap();
+        config.package_manager = package_manager;
+        config.available_package_managers = available;
+        config.package_manager_version = String::default();
+        config.system_config = String::default();
+        config.system_info = None;
+        drop(config);
+
+        let mut search = self.search.lock().unwrap();
+        search.results = SearchResults::default();
+        search.selected_result = 0;
+        search.selected_result_info = String::default();
+        search.list_state.select(None);
+        drop(search);
+
+        *self.outdated.lock().unwrap() = OutdatedState::default();
+        *self.taps.lock().unwrap() = TapsState::default();
+        *self.services.lock().unwrap() = ServicesState::default();
+        self.clear_health();
+        *self.healthcheck_results.lock().unwrap() = String::default();
+        *self.error.lock().unwrap() = None;
+    }
+    /// Reflect whether queries are fanned out to every available backend,
+    /// discarding the results reported so far
+    pub fn update_aggregated(&self, aggregated: bool) {
+        self.config.lock().unwrap().aggregated = aggregated;
+
+        let mut search = self.search.lock().unwrap();
+        search.results = SearchResults::default();
+        search.selected_result = 0;
+        search.selected_result_info = String::default();
+        search.list_state.select(None);
+        drop(search);
+
+        *self.outdated.lock().unwrap() = OutdatedState::default();
+        *self.taps.lock().unwrap() = TapsState::default();
+        *self.services.lock().unwrap() = ServicesState::default();
+        self.clear_health();
+    }
+    /// Forget the warnings of the previous health checks, but not which
+    /// ones are ignored
+    pub fn clear_health(&self) {
+        let mut health = self.health.lock().unwrap();
+        health.warnings.clear();
+        health.list_state.select(None);
+        health.checked = false;
+        health.requested = false;
+    }
 }
@@ -1,20 +1,25 @@
-use tracing::info;
-use wherehouse::package_manager::{self, Command, PackageManager, homebrew::Homebrew};
+use wherehouse::package_manager::{
+    Backend, Cancellation, Command, PackageLocality, PackageManager, ServiceAction,
+    error::Error,
+    manifest::{Installed, Lockfile, Manifest},
+    run_fix,
+};
 
-use crate::state::State;
+use crate::state::{State, TreeNode};
+use directories::BaseDirs;
+use serde::{Serialize, de::DeserializeOwned};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
     sync::{
         Arc,
         mpsc::{self, Sender},
     },
     thread,
+    time::Instant,
 };
 
-pub enum TaskEvent {
-    Stop,
-}
-
 pub struct TaskManager<T> {
     state: Arc<State>,
     package_manager: Arc<T>,
@@ -34,6 +39,10 @@ impl<T: PackageManager + Send + Sync + 'static> TaskManager<T> {
         let state = self.state.clone();
         let package_manager = self.package_manager.clone();
         let (tx_task, rx_task) = mpsc::channel::<bool>();
+        let previous = match command {
+            Command::Clean | Command::CleanupServices => self.queue(command),
+            _ => None,
+        };
 
         let worker = match command {
             Command::FilterPackages => Worker::new(tx_task, move || {
@@ -42,27 +51,31 @@ impl<T: PackageManager + Send + Sync + 'static> TaskManager<T> {
                 let source = search.source;
                 drop(search);
                 let result = package_manager.filter_packages(rx_task, source, query);
+                let results = report(&state, command, result, false);
                 let mut search = state.search.lock().unwrap();
 
-                let output = match result {
-                    Ok(results) => results,
-                    Err(_) => Vec::default(),
-                };
-                search.results = output;
+                search.results = results.unwrap_or_default();
             }),
             Command::PackageInfo => Worker::new(tx_task, move || {
                 let search = state.search.lock().unwrap();
                 let package_name = match search.results.get(search.selected_result) {
-                    Some(result) => result.clone(),
+                    Some(result) => result.name.clone(),
                     None => String::default(),
                 };
                 drop(search);
+                // there is nothing to look up until a result is selected
+                if package_name.is_empty() {
+                    if update_context {
+                        state.update_context(String::default());
+                    }
+                    state.search.lock().unwrap().selected_result_info = String::default();
+                    return;
+                }
                 let result = package_manager.package_info(rx_task, package_name);
-                let mut search = state.search.lock().unwrap();
-                let output = match result {
-                    Ok(output) => output,
-                    Err(_) => String::default(),
+                let Some(output) = report(&state, command, result, update_context) else {
+                    return;
                 };
+                let mut search = state.search.lock().unwrap();
                 if update_context {
                     state.update_context(output.clone());
                 }
@@ -70,29 +83,279 @@ impl<T: PackageManager + Send + Sync + 'static> TaskManager<T> {
                 search.selected_result_info = output;
             }),
             Command::CheckHealth => Worker::new(tx_task, move || {
-                let result = package_manager.check_health(rx_task);
-                let mut healthcheck_results = state.healthcheck_results.lock().unwrap();
-                let output = match result {
-                    Ok(output) => output,
-                    Err(_) => String::default(),
+                // the output is only tailed when it is going to be shown
+                let (tx_output, tail) = match update_context {
+                    true => {
+                        let (tx_output, tail) =
+                            Tail::start(state.clone(), command, "Checking health".to_string());
+                        (tx_output, Some(tail))
+                    }
+                    false => (mpsc::channel::<String>().0, None),
+                };
+                let cancellation = Cancellation::forward(rx_task);
+                let Some(rx_warnings) = cancellation.receiver() else {
+                    return;
+                };
+                // backends that cannot split their health check into
+                // warnings have it shown as they report it
+                let result = match package_manager.health_warnings(rx_warnings, tx_output.clone()) {
+                    Err(Error::Unsupported { .. }) => match cancellation.receiver() {
+                        Some(rx_report) => package_manager
+                            .check_health(rx_report, tx_output)
+                            .map(|output| (None, output)),
+                        None => Err(Error::Cancelled),
+                    },
+                    result => {
+                        drop(tx_output);
+                        result.map(|warnings| {
+                            let output = warnings.iter().map(|warning| warning.to_string());
+                            let output = output.collect::<Vec<String>>().join("\n");
+                            (Some(warnings), output)
+                        })
+                    }
+                };
+                if let Some(tail) = tail {
+                    tail.finish(&state, &result);
+                }
+                let Some((warnings, output)) = report(&state, command, result, update_context)
+                else {
+                    return;
                 };
+                let mut health = state.health.lock().unwrap();
+                health.warnings = warnings.unwrap_or_default();
+                health.checked = true;
+                health.list_state.select(None);
+                health.clamp_selection();
                 if update_context {
-                    state.update_context(output.clone());
+                    match health.warnings.is_empty() && !output.is_empty() {
+                        true => state.update_context(output.clone()),
+                        false => state.update_context(health.summary()),
+                    }
                 }
+                drop(health);
 
-                *healthcheck_results = output;
+                *state.healthcheck_results.lock().unwrap() = output;
             }),
-            Command::Config => Worker::new(tx_task, move || {
-                let result = package_manager.package_manager_config(rx_task);
-                let mut config = state.config.lock().unwrap();
-                let output = match result {
-                    Ok(output) => output,
-                    Err(_) => String::default(),
+            Command::RecheckHealth => Worker::new(tx_task, move || {
+                let health = state.health.lock().unwrap();
+                let Some(check) = health.highlighted().map(|warning| warning.check.clone()) else {
+                    return;
+                };
+                drop(health);
+                // the output is only tailed when it is going to be shown
+                let (tx_output, tail) = match update_context {
+                    true => {
+                        let (tx_output, tail) =
+                            Tail::start(state.clone(), command, format!("Checking {check}"));
+                        (tx_output, Some(tail))
+                    }
+                    false => (mpsc::channel::<String>().0, None),
+                };
+                let result = package_manager.run_health_check(rx_task, tx_output, check.clone());
+                if let Some(tail) = tail {
+                    tail.finish(&state, &result);
+                }
+                let Some(found) = report(&state, command, result, update_context) else {
+                    return;
                 };
+                // the warnings of the check take the place of the ones it
+                // reported before, so that fixed warnings disappear
+                let mut health = state.health.lock().unwrap();
+                let position = health
+                    .warnings
+                    .iter()
+                    .position(|warning| warning.check == check)
+                    .unwrap_or(health.warnings.len());
+                health.warnings.retain(|warning| warning.check != check);
+                let fixed = found.is_empty();
+                health.warnings.splice(position..position, found);
+                health.clamp_selection();
                 if update_context {
-                    state.update_context(output.clone());
+                    match fixed {
+                        true => state.update_context(format!("==> {check} reports no warnings")),
+                        false => state.update_context(health.summary()),
+                    }
+                }
+            }),
+            Command::OutdatedPackages => Worker::new(tx_task, move || {
+                let result = package_manager.outdated_packages(rx_task);
+                let Some(packages) = report(&state, command, result, update_context) else {
+                    return;
+                };
+                let mut outdated = state.outdated.lock().unwrap();
+                // keep marks and the highlight on packages that are still outdated
+                outdated
+                    .selected
+                    .retain(|name| packages.iter().any(|package| package.name == *name));
+                let highlighted = match packages.len() {
+                    0 => None,
+                    len => Some(outdated.list_state.selected().unwrap_or(0).min(len - 1)),
+                };
+                outdated.list_state.select(highlighted);
+                outdated.packages = packages;
+            }),
+            Command::Taps => Worker::new(tx_task, move || {
+                let result = package_manager.taps(rx_task);
+                let Some(found) = report(&state, command, result, update_context) else {
+                    return;
+                };
+                let mut taps = state.taps.lock().unwrap();
+                let highlighted = match found.len() {
+                    0 => None,
+                    len => Some(taps.list_state.selected().unwrap_or(0).min(len - 1)),
+                };
+                taps.list_state.select(highlighted);
+                taps.taps = found;
+            }),
+            Command::Services => Worker::new(tx_task, move || {
+                let result = package_manager.services(rx_task);
+                let Some(found) = report(&state, command, result, update_context) else {
+                    return;
+                };
+                let mut services = state.services.lock().unwrap();
+                let highlighted = match found.len() {
+                    0 => None,
+                    len => Some(services.list_state.selected().unwrap_or(0).min(len - 1)),
+                };
+                services.list_state.select(highlighted);
+                services.services = found;
+            }),
+            Command::Dependencies => Worker::new(tx_task, move || {
+                let package_name = state.dependency_tree.lock().unwrap().package_name.clone();
+                // both sides of the graph are queried one after the other
+                let cancellation = Cancellation::forward(rx_task);
+                let Some(rx_dependencies) = cancellation.receiver() else {
+                    return;
+                };
+                let dependencies =
+                    package_manager.dependencies(rx_dependencies, package_name.clone());
+                let Some(dependencies) =
+                    tree_nodes(&state, command, dependencies, TreeNode::dependency)
+                else {
+                    return;
+                };
+                let Some(rx_dependents) = cancellation.receiver() else {
+                    return;
+                };
+                let dependents = package_manager.dependents(rx_dependents, package_name.clone());
+                let Some(dependents) = tree_nodes(&state, command, dependents, TreeNode::dependent)
+                else {
+                    return;
+                };
+                let mut tree = state.dependency_tree.lock().unwrap();
+                tree.roots = vec![
+                    TreeNode::heading(format!("{package_name} depends on"), dependencies),
+                    TreeNode::heading(
+                        format!("Installed packages using {package_name}"),
+                        dependents,
+                    ),
+                ];
+                tree.selected = 0;
+                tree.pending = None;
+            }),
+            Command::Dependents => Worker::new(tx_task, move || {
+                let tree = state.dependency_tree.lock().unwrap();
+                let Some(path) = tree.pending.clone() else {
+                    return;
+                };
+                let Some(package_name) = tree.node(&path).map(|node| node.name.clone()) else {
+                    return;
+                };
+                let target = tree.qualify(&package_name);
+                drop(tree);
+                let result = package_manager.dependents(rx_task, target);
+                let Some(dependents) = tree_nodes(&state, command, result, TreeNode::dependent)
+                else {
+                    return;
+                };
+                let mut tree = state.dependency_tree.lock().unwrap();
+                // the tree may have been replaced by another package meanwhile
+                if tree.pending.as_ref() != Some(&path) {
+                    return;
                 }
+                tree.pending = None;
+                if let Some(node) = tree
+                    .node_mut(&path)
+                    .filter(|node| node.name == package_name)
+                {
+                    node.children = dependents;
+                    node.loaded = true;
+                    node.expanded = true;
+                }
+            }),
+            Command::Config => Worker::new(tx_task, move || {
+                let cancellation = Cancellation::forward(rx_task);
+                let Some(rx_config) = cancellation.receiver() else {
+                    return;
+                };
+                let result = package_manager.package_manager_config(rx_config);
+                let Some(output) = report(&state, command, result, update_context) else {
+                    return;
+                };
+                // backends that cannot split their configuration into fields
+                // only have it shown as reported
+                let system_info = match cancellation
+                    .receiver()
+                    .map(|rx_info| package_manager.system_info(rx_info))
+                {
+                    Some(Err(Error::Unsupported { .. })) | None => None,
+                    Some(result) => report(&state, command, result, false),
+                };
+                let mut config = state.config.lock().unwrap();
+                config.package_manager_version = system_info
+                    .as_ref()
+                    .and_then(|system_info| system_info.version.clone())
+                    .unwrap_or_default();
                 config.system_config = output;
+                config.system_info = system_info;
+                if update_context {
+                    state.update_context(config.system_report());
+                }
+            }),
+            Command::PreviewClean => Worker::new(tx_task, move || {
+                let result = package_manager.clean_preview(rx_task);
+                // backends without a dry run can still be cleaned up, only
+                // without knowing upfront what is going to be removed
+                let preview = match result {
+                    Err(e @ Error::Unsupported { .. }) => {
+                        format!("{e}\nThe cleanup can still be run without a preview\n")
+                    }
+                    result => match report(&state, command, result, update_context) {
+                        Some(preview) if preview.is_empty() => {
+                            state.update_context(preview.to_string());
+                            return;
+                        }
+                        Some(preview) => preview.to_string(),
+                        None => return,
+                    },
+                };
+                state.update_context(preview);
+                state.request_confirmation(Command::Clean, Vec::new());
+            }),
+            Command::Clean => Worker::queued(previous, move |rx_task| {
+                let (tx_output, tail) =
+                    Tail::start(state.clone(), command, "Cleaning up".to_string());
+                let result = package_manager.clean(rx_task, tx_output);
+                let tailed = tail.finish(&state, &result);
+                if let Some(output) = report(&state, command, result, true) {
+                    if !tailed {
+                        state.update_context(output);
+                    }
+                    // autoremoving unneeded packages changes what is installed
+                    *state.refresh.lock().unwrap() = true;
+                }
+            }),
+            Command::CleanupServices => Worker::queued(previous, move |rx_task| {
+                let (tx_output, tail) =
+                    Tail::start(state.clone(), command, "Cleaning up services".to_string());
+                let result = package_manager.cleanup_services(rx_task, tx_output);
+                let tailed = tail.finish(&state, &result);
+                if let Some(output) = report(&state, command, result, true) {
+                    if !tailed {
+                        state.update_context(output);
+                    }
+                    *state.refresh.lock().unwrap() = true;
+                }
             }),
             _ => Worker::new(tx_task, || {}),
         };
@@ -102,10 +365,601 @@ impl<T: PackageManager + Send + Sync + 'static> TaskManager<T> {
 
         Ok(())
     }
+
+    /// Run a package operation on each of `package_names` in turn, tailing
+    /// the output in the context pane and asking for the search results to
+    /// be refreshed once any of them succeeded
+    pub fn execute_on(
+        &mut self,
+        command: Command,
+        package_names: Vec<String>,
+    ) -> color_eyre::Result<()> {
+        type Operation<T> =
+            fn(&T, mpsc::Receiver<bool>, Sender<String>, String) -> Result<String, Error>;
+        let (verb, operation): (&str, Operation<T>) = match command {
+            Command::InstallPackage => ("Installing", T::install_package),
+            Command::UninstallPackage => ("Uninstalling", T::uninstall_package),
+            Command::UpdatePackage => ("Upgrading", T::update_package),
+            // pinning is quick, there is no output worth tailing
+            Command::PinPackage => ("Pinning", |package_manager, rx, _tx, package_name| {
+                package_manager.pin_package(rx, package_name)
+            }),
+            Command::UnpinPackage => ("Unpinning", |package_manager, rx, _tx, package_name| {
+                package_manager.unpin_package(rx, package_name)
+            }),
+            Command::AddTap => ("Tapping", T::add_tap),
+            Command::RemoveTap => ("Untapping", T::remove_tap),
+            Command::ImportBrewfile => ("Importing", T::import_brewfile),
+            Command::StartService => ("Starting", |package_manager, rx, tx, service| {
+                package_manager.control_service(rx, tx, ServiceAction::Start, service)
+            }),
+            Command::StopService => ("Stopping", |package_manager, rx, tx, service| {
+                package_manager.control_service(rx, tx, ServiceAction::Stop, service)
+            }),
+            Command::RestartService => ("Restarting", |package_manager, rx, tx, service| {
+                package_manager.control_service(rx, tx, ServiceAction::Restart, service)
+            }),
+            Command::RunService => ("Running", |package_manager, rx, tx, service| {
+                package_manager.control_service(rx, tx, ServiceAction::Run, service)
+            }),
+            // fixes are shell commands suggested by the backend, not run by it
+            Command::RunHealthFix => ("Running", |_package_manager, rx, tx, fix| {
+                run_fix(rx, tx, fix)
+            }),
+            _ => return self.execute(command, true),
+        };
+        let state = self.state.clone();
+        let package_manager = self.package_manager.clone();
+        let previous = self.queue(command);
+        if previous.is_some() {
+            state.append_context(&format!(
+                "==> {verb} {} once the running operation finished",
+                package_names.join(", ")
+            ));
+        }
+
+        let worker = Worker::queued(previous, move |rx_task| {
+            let cancellation = Cancellation::forward(rx_task);
+            let count = package_names.len();
+            let mut outputs = Vec::new();
+            for (index, package_name) in package_names.into_iter().enumerate() {
+                let Some(rx_operation) = cancellation.receiver() else {
+                    break;
+                };
+                let label = match count {
+                    1 => format!("{verb} {package_name}"),
+                    _ => format!("{verb} {package_name} ({}/{count})", index + 1),
+                };
+                let (tx_output, tail) = Tail::start(state.clone(), command, label);
+                let result = operation(&package_manager, rx_operation, tx_output, package_name);
+                let tailed = tail.finish(&state, &result);
+                let Some(output) = report(&state, command, result, true) else {
+                    // the remaining packages are left alone once one failed
+                    break;
+                };
+                // backends running blocking commands have nothing to tail
+                if !tailed {
+                    outputs.push(output);
+                    state.update_context(outputs.join("\n"));
+                }
+                *state.refresh.lock().unwrap() = true;
+            }
+        });
+        if let Some(worker) = self.pool.insert(command, worker) {
+            worker.stop()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the dependency graph of the installed packages to `path`, as
+    /// DOT or JSON depending on its extension, or as both next to each
+    /// other when it has neither
+    pub fn export_graph(&mut self, path: String) -> color_eyre::Result<()> {
+        let command = Command::ExportGraph;
+        let state = self.state.clone();
+        let package_manager = self.package_manager.clone();
+        let (tx_task, rx_task) = mpsc::channel::<bool>();
+        let path = expand_home(path.trim());
+
+        let worker = Worker::new(tx_task, move || {
+            let (tx_output, tail) = Tail::start(
+                state.clone(),
+                command,
+                "Exporting the dependency graph".to_string(),
+            );
+            drop(tx_output);
+            let result = package_manager.dependency_graph(rx_task);
+            tail.finish(&state, &result);
+            let Some(graph) = report(&state, command, result, true) else {
+                return;
+            };
+            let extension = path.extension().and_then(|extension| extension.to_str());
+            let files = match extension {
+                Some("dot") => vec![(path.clone(), graph.to_dot())],
+                Some("json") => vec![(path.clone(), graph.to_json())],
+                _ => vec![
+                    (with_extension(&path, "dot"), graph.to_dot()),
+                    (with_extension(&path, "json"), graph.to_json()),
+                ],
+            };
+            let mut written = Vec::new();
+            for (path, content) in files {
+                if let Err(e) = fs::write(&path, content) {
+                    state.update_context(format!("==> Failed to write {}\n{e}\n", path.display()));
+                    return;
+                }
+                written.push(path.display().to_string());
+            }
+            state.update_context(format!(
+                "==> Exported the dependency graph\n{} packages, {} dependencies, {} leaves\n{}\n",
+                graph.nodes.len(),
+                graph.edges.len(),
+                graph.leaves().len(),
+                written.join("\n")
+            ));
+        });
+        if let Some(worker) = self.pool.insert(command, worker) {
+            worker.stop()?;
+        }
+
+        Ok(())
+    }
+
+    /// Export the installed state to the Brewfile at `path`, or compare the
+    /// Brewfile at `path` with the installed state. Planning an import asks
+    /// for the plan to be confirmed, checking for drift only reports it
+    pub fn brewfile(&mut self, command: Command, path: String) -> color_eyre::Result<()> {
+        let state = self.state.clone();
+        let package_manager = self.package_manager.clone();
+        let (tx_task, rx_task) = mpsc::channel::<bool>();
+        let path = expand_home(path.trim()).display().to_string();
+
+        let worker = Worker::new(tx_task, move || {
+            let label = match command {
+                Command::ExportBrewfile => format!("Exporting {path}"),
+                _ => format!("Comparing with {path}"),
+            };
+            let (tx_output, tail) = Tail::start(state.clone(), command, label);
+            drop(tx_output);
+            if let Command::ExportBrewfile = command {
+                let result = package_manager.export_brewfile(rx_task, path.clone());
+                tail.finish(&state, &result);
+                if let Some(summary) = report(&state, command, result, true) {
+                    state.update_context(format!("==> Exported {path}\n{summary}"));
+                }
+                return;
+            }
+            let result = package_manager.brewfile_plan(rx_task, path.clone());
+            tail.finish(&state, &result);
+            let Some(plan) = report(&state, command, result, true) else {
+                return;
+            };
+            if let Command::BrewfileDrift = command {
+                state.update_context(format!("==> Drift from {path}\n{plan}"));
+                return;
+            }
+            state.update_context(format!("==> Plan for importing {path}\n{plan}"));
+            if !plan.is_empty() {
+                state.request_confirmation(Command::ImportBrewfile, vec![path]);
+            }
+        });
+        if let Some(worker) = self.pool.insert(command, worker) {
+            worker.stop()?;
+        }
+
+        Ok(())
+    }
+
+    /// Snapshot the packages installed on purpose by `backends` into the
+    /// manifest at `path` and the lockfile next to it, or compare the
+    /// manifest with what is installed. Planning asks for the plan to be
+    /// confirmed along with the packages it uninstalls, applying installs
+    /// what is missing, upgrades what does not satisfy its constraint and
+    /// uninstalls the packages of `removals` the manifest leaves out through
+    /// the backend of each package
+    pub fn manifest(
+        &mut self,
+        command: Command,
+        path: String,
+        backends: Vec<Backend>,
+        removals: Vec<String>,
+    ) -> color_eyre::Result<()> {
+        let state = self.state.clone();
+        let path = expand_home(path.trim());
+        let lock_path = path.with_extension("lock");
+        let previous = match command {
+            Command::ApplyManifest => self.queue(command),
+            _ => None,
+        };
+
+        let worker = Worker::queued(previous, move |rx_task| {
+            let cancellation = Cancellation::forward(rx_task);
+            let (manifest, lockfile) = match (
+                read_json::<Manifest>(&path),
+                read_json::<Lockfile>(&lock_path),
+            ) {
+                (Ok(manifest), Ok(lockfile)) => (manifest, lockfile.unwrap_or_default()),
+                (Err(e), _) | (_, Err(e)) => {
+                    state.update_context(e);
+                    return;
+                }
+            };
+            // only the backends the manifest lists are compared with it
+            let backends = match (&manifest, command) {
+                (_, Command::SnapshotManifest) => backends,
+                (Some(manifest), _) => manifest
+                    .packages
+                    .keys()
+                    .copied()
+                    .filter(|backend| backends.contains(backend))
+                    .collect(),
+                (None, _) => {
+                    state.update_context(format!(
+                        "==> There is no manifest at {}\nSnapshot this machine to create one\n",
+                        path.display()
+                    ));
+                    return;
+                }
+            };
+
+            let (tx_output, tail) = Tail::start(
+                state.clone(),
+                command,
+                "Looking up the installed packages".to_string(),
+            );
+            drop(tx_output);
+            let mut installed = BTreeMap::new();
+            let mut latest = BTreeMap::new();
+            let result = backends.into_iter().try_for_each(|backend| {
+                let package_manager = backend.package_manager();
+                let found = installed_packages(package_manager.as_ref(), &cancellation)?;
+                // upgrades are only looked up when applying would need one
+                if let (Command::PlanManifest | Command::ApplyManifest, Some(manifest)) =
+                    (command, &manifest)
+                    && !manifest.is_satisfied(backend, &found)
+                {
+                    let rx = cancellation.receiver().ok_or(Error::Cancelled)?;
+                    latest.insert(backend, latest_versions(package_manager.as_ref(), rx)?);
+                }
+                installed.insert(backend, found);
+                Ok(())
+            });
+            tail.finish(&state, &result);
+            if report(&state, command, result, true).is_none() {
+                return;
+            }
+
+            if let Command::SnapshotManifest = command {
+                let (manifest, lockfile) = Manifest::snapshot(&installed, manifest.as_ref());
+                for (path, json) in [
+                    (&path, to_json(&manifest)),
+                    (&lock_path, to_json(&lockfile)),
+                ] {
+                    if let Err(e) = fs::write(path, json) {
+                        state.update_context(format!(
+                            "==> Failed to write {}\n{e}\n",
+                            path.display()
+                        ));
+                        return;
+                    }
+                }
+                state.update_context(format!(
+                    "==> Snapshotted {} packages of {} backends\n{}\n{}\n",
+                    manifest.packages.values().map(BTreeMap::len).sum::<usize>(),
+                    manifest.packages.len(),
+                    path.display(),
+                    lock_path.display()
+                ));
+                return;
+            }
+            let mut diff = manifest.unwrap_or_default().diff(&lockfile, &installed);
+            diff.plan_upgrades(&latest);
+            match command {
+                Command::PlanManifest => {
+                    state.update_context(format!(
+                        "==> Plan for applying {}\nMissing packages are installed, packages \
+                         not matching their version constraint are upgraded when an upgrade \
+                         matches it and packages not in the manifest are uninstalled\n{diff}",
+                        path.display()
+                    ));
+                    if diff.has_changes() {
+                        state.request_manifest_confirmation(
+                            path.display().to_string(),
+                            diff.extra
+                                .iter()
+                                .map(|(backend, package_name)| backend.qualify(package_name))
+                                .collect(),
+                        );
+                    }
+                    return;
+                }
+                Command::ApplyManifest => {}
+                _ => {
+                    state
+                        .update_context(format!("==> Differences from {}\n{diff}", path.display()));
+                    return;
+                }
+            }
+
+            // packages left out of the manifest since the plan was confirmed
+            // are only uninstalled once confirmed in turn
+            let (extra, unconfirmed): (Vec<_>, Vec<_>) =
+                diff.extra.into_iter().partition(|(backend, package_name)| {
+                    removals.contains(&backend.qualify(package_name))
+                });
+            let mut outputs = Vec::new();
+            if !unconfirmed.is_empty() {
+                outputs.push(format!(
+                    "==> Left installed since they were not in the confirmed plan\n{}\n",
+                    unconfirmed
+                        .iter()
+                        .map(|(backend, package_name)| format!("{backend}: {package_name}"))
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                ));
+                state.update_context(outputs.join("\n"));
+            }
+            let operations =
+                diff.missing
+                    .into_iter()
+                    .map(|(backend, package_name)| (backend, package_name, Operation::Install))
+                    .chain(
+                        diff.upgrades
+                            .into_iter()
+                            .map(|upgrade| (upgrade.backend, upgrade.name, Operation::Upgrade)),
+                    )
+                    .chain(extra.into_iter().map(|(backend, package_name)| {
+                        (backend, package_name, Operation::Uninstall)
+                    }))
+                    .collect::<Vec<(Backend, String, Operation)>>();
+            let count = operations.len();
+            for (index, (backend, package_name, operation)) in operations.into_iter().enumerate() {
+                let Some(rx_operation) = cancellation.receiver() else {
+                    break;
+                };
+                let verb = match operation {
+                    Operation::Install => "Installing",
+                    Operation::Upgrade => "Upgrading",
+                    Operation::Uninstall => "Uninstalling",
+                };
+                let label = format!(
+                    "{verb} {package_name} with {backend} ({}/{count})",
+                    index + 1
+                );
+                let (tx_output, tail) = Tail::start(state.clone(), command, label);
+                let package_manager = backend.package_manager();
+                let result = match operation {
+                    Operation::Install => {
+                        package_manager.install_package(rx_operation, tx_output, package_name)
+                    }
+                    Operation::Upgrade => {
+                        package_manager.update_package(rx_operation, tx_output, package_name)
+                    }
+                    Operation::Uninstall => {
+                        package_manager.uninstall_package(rx_operation, tx_output, package_name)
+                    }
+                };
+                let tailed = tail.finish(&state, &result);
+                let Some(output) = report(&state, command, result, true) else {
+                    // the remaining packages are left alone once one failed
+                    break;
+                };
+                if !tailed {
+                    outputs.push(output);
+                    state.update_context(outputs.join("\n"));
+                }
+                *state.refresh.lock().unwrap() = true;
+            }
+        });
+        if let Some(worker) = self.pool.insert(command, worker) {
+            worker.stop()?;
+        }
+
+        Ok(())
+    }
+
+    /// Take the worker still running `command` out of the pool, for the
+    /// next one to be queued behind it instead of cancelling it
+    fn queue(&mut self, command: Command) -> Option<Worker> {
+        self.pool.remove(&command).filter(Worker::is_running)
+    }
+
+    /// Cancel `command` if it is still running
+    pub fn cancel(&self, command: Command) -> color_eyre::Result<()> {
+        if let Some(worker) = self.pool.get(&command) {
+            worker.stop()?;
+        }
+        Ok(())
+    }
+}
+
+/// Output of a long running command, tailed in the context pane line by
+/// line while the command runs
+struct Tail {
+    started: Instant,
+    handle: thread::JoinHandle<bool>,
+}
+
+impl Tail {
+    /// Start tailing everything sent on the returned sender, until every
+    /// clone of it has been dropped
+    fn start(state: Arc<State>, command: Command, label: String) -> (Sender<String>, Self) {
+        let started = state.start_operation(command, label);
+        let (tx_output, rx_output) = mpsc::channel::<String>();
+        let handle = thread::spawn(move || {
+            let mut tailed = false;
+            for line in rx_output {
+                state.append_context(&line);
+                tailed = true;
+            }
+            tailed
+        });
+        (tx_output, Self { started, handle })
+    }
+
+    /// Wait for the remaining output of the finished command, returning
+    /// whether anything was tailed at all
+    fn finish<O>(self, state: &State, result: &Result<O, Error>) -> bool {
+        let tailed = self.handle.join().unwrap_or_default();
+        if let Err(Error::Cancelled) = result {
+            state.append_context("==> Cancelled");
+        }
+        state.finish_operation(self.started);
+        tailed
+    }
+}
+
+/// What a manifest does with one of its packages when applied
+enum Operation {
+    Install,
+    Upgrade,
+    Uninstall,
+}
+
+/// What `package_manager` has installed, where the packages installed on
+/// request are the ones the backend records as such, or every installed
+/// package for backends keeping no such record
+fn installed_packages(
+    package_manager: &dyn PackageManager,
+    cancellation: &Cancellation,
+) -> Result<Installed, Error> {
+    let rx = cancellation.receiver().ok_or(Error::Cancelled)?;
+    let versions = package_manager
+        .filter_packages(rx, PackageLocality::Local, String::default())?
+        .into_iter()
+        .filter_map(|package| Some((package.name, package.installed_version?)))
+        .collect::<BTreeMap<String, String>>();
+    let rx = cancellation.receiver().ok_or(Error::Cancelled)?;
+    let (requested, recorded) = match package_manager.requested_packages(rx) {
+        // Homebrew qualifies the packages of third-party taps
+        Ok(requested) => (
+            requested
+                .iter()
+                .filter_map(|name| {
+                    [name.as_str(), name.rsplit('/').next().unwrap_or(name)]
+                        .into_iter()
+                        .find(|name| versions.contains_key(*name))
+                        .map(|name| name.to_string())
+                })
+                .collect(),
+            true,
+        ),
+        Err(Error::Unsupported { .. }) => (versions.keys().cloned().collect(), false),
+        Err(e) => return Err(e),
+    };
+    Ok(Installed {
+        versions,
+        requested,
+        recorded,
+    })
+}
+
+/// Versions `package_manager` would upgrade its outdated packages to,
+/// leaving out the pinned ones which are not upgraded
+fn latest_versions(
+    package_manager: &dyn PackageManager,
+    rx: mpsc::Receiver<bool>,
+) -> Result<BTreeMap<String, String>, Error> {
+    Ok(package_manager
+        .outdated_packages(rx)?
+        .into_iter()
+        .filter(|package| !package.pinned)
+        .filter_map(|package| Some((package.name, package.version?)))
+        .collect())
+}
+
+/// Contents of the JSON file at `path`, None when there is no such file
+fn read_json<D: DeserializeOwned>(path: &Path) -> Result<Option<D>, String> {
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("==> Failed to parse {}\n{e}\n", path.display())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("==> Failed to read {}\n{e}\n", path.display())),
+    }
+}
+
+fn to_json<S: Serialize>(value: &S) -> String {
+    serde_json::to_string_pretty(value).expect("manifests only consist of strings")
+}
+
+/// `path` with a leading `~` standing for the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), BaseDirs::new()) {
+        (Some(path), Some(base_dirs)) => base_dirs.home_dir().join(path),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// `path` with `extension` appended, unlike `Path::with_extension` which
+/// would replace anything after a dot in the file name
+fn with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+/// Nodes of the dependency explorer for what a dependency query reported.
+/// A failed query is explained by a single heading in place of the nodes,
+/// None is returned when it was cancelled
+fn tree_nodes<O>(
+    state: &State,
+    command: Command,
+    result: Result<Vec<O>, Error>,
+    node: fn(O) -> TreeNode,
+) -> Option<Vec<TreeNode>> {
+    match result {
+        Ok(found) if found.is_empty() => {
+            state.clear_error(command);
+            Some(vec![TreeNode::heading("none".to_string(), Vec::new())])
+        }
+        Ok(found) => {
+            state.clear_error(command);
+            Some(found.into_iter().map(node).collect())
+        }
+        Err(Error::Cancelled) => None,
+        Err(e) => {
+            let reason = e.to_string().lines().next().unwrap_or_default().to_string();
+            // a backend without dependency queries is not worth an error
+            if !matches!(e, Error::Unsupported { .. }) {
+                state.set_error(command, e);
+            }
+            Some(vec![TreeNode::heading(reason, Vec::new())])
+        }
+    }
+}
+
+/// Record the outcome of `command` in the state, returning its output when
+/// it succeeded. Cancelled commands have been superseded by a newer run, so
+/// they are not reported
+fn report<O>(
+    state: &State,
+    command: Command,
+    result: Result<O, Error>,
+    update_context: bool,
+) -> Option<O> {
+    match result {
+        Ok(output) => {
+            state.clear_error(command);
+            Some(output)
+        }
+        Err(Error::Cancelled) => None,
+        Err(e) => {
+            if update_context {
+                state.update_context(e.to_string());
+            }
+            state.set_error(command, e);
+            None
+        }
+    }
 }
 
 struct Worker {
     tx: Sender<bool>,
+    /// Workers queued ahead of this one, cancelled along with it
+    ahead: Vec<Sender<bool>>,
     thread: thread::JoinHandle<()>,
 }
 
@@ -115,13 +969,87 @@ impl Worker {
         F: FnOnce() + Send + 'static,
     {
         let thread = thread::spawn(f);
-        Self { tx, thread }
+        Self {
+            tx,
+            ahead: Vec::new(),
+            thread,
+        }
+    }
+
+    /// Run `f` once `previous` finished rather than cancelling it, since
+    /// cancelling an operation part-way can leave a package half installed.
+    /// A worker cancelled while it waits for its turn never runs `f`
+    fn queued<F>(previous: Option<Worker>, f: F) -> Self
+    where
+        F: FnOnce(mpsc::Receiver<bool>) + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel::<bool>();
+        let Some(previous) = previous else {
+            return Self::new(tx, move || f(rx));
+        };
+        let mut ahead = previous.ahead;
+        ahead.push(previous.tx);
+        let waiting = previous.thread;
+        let thread = thread::spawn(move || {
+            let _ = waiting.join();
+            if rx.try_recv().is_ok() {
+                return;
+            }
+            f(rx)
+        });
+        Self { tx, ahead, thread }
+    }
+
+    fn is_running(&self) -> bool {
+        !self.thread.is_finished()
     }
 
+    /// Ask the worker and the workers queued ahead of it to cancel their
+    /// command, the worker may already have finished in which case there
+    /// is nobody left to receive the request
     pub fn stop(&self) -> color_eyre::Result<()> {
-        match self.tx.send(true) {
-            _ => Ok(()), // Ok(_) => Ok(()),
-                         // Err(e) => bail!("an error occurred while stopping the thread: {e}"),
+        // this worker first, for it to be cancelled by the time the worker
+        // it waits for stops
+        let _ = self.tx.send(true);
+        for tx in &self.ahead {
+            let _ = tx.send(true);
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn queued_worker_waits_for_the_running_one() {
+        let (tx_first, rx_first) = mpsc::channel::<bool>();
+        let (tx_order, rx_order) = mpsc::channel::<&str>();
+        let tx_second = tx_order.clone();
+        let first = Worker::new(tx_first, move || {
+            let _ = rx_first.recv_timeout(Duration::from_millis(100));
+            tx_order.send("first").unwrap();
+        });
+        let second = Worker::queued(Some(first), move |_rx| tx_second.send("second").unwrap());
+        second.thread.join().unwrap();
+        assert_eq!(rx_order.iter().collect::<Vec<_>>(), ["first", "second"]);
+    }
+
+    #[test]
+    fn cancelling_a_queued_worker_cancels_the_running_one() {
+        let (tx_first, rx_first) = mpsc::channel::<bool>();
+        let (tx_order, rx_order) = mpsc::channel::<&str>();
+        let tx_second = tx_order.clone();
+        let first = Worker::new(tx_first, move || {
+            if rx_first.recv().is_ok() {
+                tx_order.send("first cancelled").unwrap();
+            }
+        });
+        let second = Worker::queued(Some(first), move |_rx| tx_second.send("second").unwrap());
+        second.stop().unwrap();
+        second.thread.join().unwrap();
+        assert_eq!(rx_order.iter().collect::<Vec<_>>(), ["first cancelled"]);
     }
 }
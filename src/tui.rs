@@ -1,12 +1,18 @@
 use std::sync::Arc;
 
-use ratatui::layout::{Constraint, Layout};
+use ratatui::{
+    layout::{Constraint, Layout},
+    widgets::ListState,
+};
 
 use crate::{
-    state::State,
+    state::{Pane, State},
     widget::{
-        context_pane::ContextPane, info_pane::InfoPane, search_input_pane::SearchInputPane,
-        search_results_pane::SearchResultsPane, status_bar::StatusBar,
+        confirm_dialog::ConfirmDialog, context_pane::ContextPane, dependency_tree::DependencyTree,
+        health_pane::HealthPane, info_pane::InfoPane, outdated_pane::OutdatedPane,
+        prompt_dialog::PromptDialog, search_input_pane::SearchInputPane,
+        search_results_pane::SearchResultsPane, services_pane::ServicesPane, status_bar::StatusBar,
+        taps_pane::TapsPane,
     },
 };
 
@@ -30,7 +36,7 @@ pub fn init() -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
 fn set_panic_hook() {
     let hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
-        let _ = ratatui::restore();
+        ratatui::restore();
         hook(panic_info);
     }));
 }
@@ -55,10 +61,10 @@ impl Tui {
     ) -> color_eyre::Result<()> {
         loop {
             terminal.draw(|frame| self.draw(frame))?;
-            if let Ok(should_quit) = self.state.should_quit.try_lock() {
-                if *should_quit {
-                    break;
-                }
+            if let Ok(should_quit) = self.state.should_quit.try_lock()
+                && *should_quit
+            {
+                break;
             }
         }
         Ok(())
@@ -84,14 +90,63 @@ impl Tui {
         let search_input_pane = SearchInputPane::new(self.state.clone());
         frame.render_widget(search_input_pane, sidebar_layout[1]);
 
-        let search_results_pane = SearchResultsPane::new(self.state.clone());
-        let mut list_state = self.state.search.lock().unwrap().list_state.clone();
-        frame.render_stateful_widget(search_results_pane, sidebar_layout[2], &mut list_state);
+        // the outdated packages, the taps, the services and the health
+        // warnings take the place of the search results while they are being
+        // looked at
+        let current_pane = self.state.current_pane().clone();
+        match current_pane {
+            Pane::Outdated => {
+                let outdated_pane = OutdatedPane::new(self.state.clone());
+                let mut list_state = self.state.outdated.lock().unwrap().list_state.clone();
+                frame.render_stateful_widget(outdated_pane, sidebar_layout[2], &mut list_state);
+            }
+            Pane::Taps => {
+                let taps_pane = TapsPane::new(self.state.clone());
+                let mut list_state = self.state.taps.lock().unwrap().list_state.clone();
+                frame.render_stateful_widget(taps_pane, sidebar_layout[2], &mut list_state);
+            }
+            Pane::Services => {
+                let services_pane = ServicesPane::new(self.state.clone());
+                let mut list_state = self.state.services.lock().unwrap().list_state.clone();
+                frame.render_stateful_widget(services_pane, sidebar_layout[2], &mut list_state);
+            }
+            // the health list takes the place of the search results once a
+            // check was asked for
+            Pane::Info if self.state.health.lock().unwrap().requested => {
+                let health_pane = HealthPane::new(self.state.clone());
+                let mut list_state = self.state.health.lock().unwrap().list_state.clone();
+                frame.render_stateful_widget(health_pane, sidebar_layout[2], &mut list_state);
+            }
+            _ => {
+                let search_results_pane = SearchResultsPane::new(self.state.clone());
+                let mut list_state = self.state.search.lock().unwrap().list_state.clone();
+                frame.render_stateful_widget(
+                    search_results_pane,
+                    sidebar_layout[2],
+                    &mut list_state,
+                );
+            }
+        }
 
-        let info_pane = ContextPane::new(self.state.clone());
-        frame.render_widget(info_pane, main_layout[1]);
+        // the dependency explorer takes the place of the context while the
+        // graph is being navigated
+        let exploring = matches!(*self.state.current_pane(), Pane::Context);
+        if exploring {
+            let dependency_tree = DependencyTree::new(self.state.clone());
+            let mut list_state = ListState::default();
+            frame.render_stateful_widget(dependency_tree, main_layout[1], &mut list_state);
+        } else {
+            let info_pane = ContextPane::new(self.state.clone());
+            frame.render_widget(info_pane, main_layout[1]);
+        }
 
         let status_bar = StatusBar::new(self.state.clone());
         frame.render_widget(status_bar, layout[1]);
+
+        let confirm_dialog = ConfirmDialog::new(self.state.clone());
+        frame.render_widget(confirm_dialog, frame.area());
+
+        let prompt_dialog = PromptDialog::new(self.state.clone());
+        frame.render_widget(prompt_dialog, frame.area());
     }
 }
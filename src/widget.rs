@@ -1,5 +1,12 @@
+pub mod confirm_dialog;
 pub mod context_pane;
+pub mod dependency_tree;
+pub mod health_pane;
 pub mod info_pane;
+pub mod outdated_pane;
+pub mod prompt_dialog;
 pub mod search_input_pane;
 pub mod search_results_pane;
+pub mod services_pane;
 pub mod status_bar;
+pub mod taps_pane;
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use ratatui::{
+    layout::{Alignment, Constraint, Flex, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::state::State;
+
+/// Most lines listed below the prompt, the others are only counted
+const MAX_LISTED: usize = 10;
+
+/// Popup asking the user to confirm the pending package operation, drawn
+/// on top of the other panes
+pub struct ConfirmDialog {
+    state: Arc<State>,
+}
+
+impl Widget for ConfirmDialog {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let Some(confirmation) = self.state.confirmation.lock().unwrap().clone() else {
+            return;
+        };
+        let listed = confirmation.listed();
+        let mut lines = vec![Line::raw(confirmation.prompt())];
+        lines.extend(listed.iter().take(MAX_LISTED).map(Line::raw));
+        if listed.len() > MAX_LISTED {
+            lines.push(Line::raw(format!("and {} more", listed.len() - MAX_LISTED)));
+        }
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(Flex::Center)
+            .areas(area);
+        // tall enough for the wrapped lines, the keys and the borders
+        let width = usize::from(area.width.saturating_sub(2)).max(1);
+        let rows = lines
+            .iter()
+            .map(|line| line.width().div_ceil(width).max(1))
+            .sum::<usize>();
+        let height = u16::try_from(rows + 4).unwrap_or(u16::MAX);
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title("confirm")
+            .title_alignment(Alignment::Left)
+            .style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+        let key_style = Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD);
+        let mut keys = vec![
+            Span::styled("y", key_style),
+            Span::raw(" confirm  "),
+            Span::styled("n", key_style),
+            Span::raw(" cancel"),
+        ];
+        // the package is offered by several backends to choose from
+        if confirmation.aggregated && confirmation.sources.len() > 1 {
+            keys.push(Span::raw("  "));
+            keys.push(Span::styled("tab", key_style));
+            keys.push(Span::raw(" other backend"));
+        }
+        lines.push(Line::from(keys));
+        let dialog = Paragraph::new(lines)
+            .centered()
+            .wrap(Wrap { trim: true })
+            .block(block)
+            .style(Style::default().fg(Color::White));
+
+        Clear.render(area, buf);
+        dialog.render(area, buf);
+    }
+}
+
+impl ConfirmDialog {
+    pub fn new(state: Arc<State>) -> Self {
+        Self { state }
+    }
+}
@@ -2,9 +2,12 @@ use crate::state::{Pane, State};
 use ratatui::{
     layout::Alignment,
     style::{Color, Modifier, Style},
+    text::{Line, Text},
     widgets::{Block, BorderType, Paragraph, Widget},
 };
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 pub struct ContextPane {
     state: Arc<State>,
@@ -19,21 +22,60 @@ impl Widget for ContextPane {
             Pane::Context => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
             _ => Style::default().fg(Color::LightBlue),
         };
-        let block = Block::bordered()
+        let operation = self.state.operation.lock().unwrap().clone();
+        let mut block = Block::bordered()
             .border_type(BorderType::Rounded)
             // .title("4")
             .title_alignment(Alignment::Left)
             .style(block_style);
+        if let Some(operation) = &operation {
+            let elapsed = operation.started.elapsed();
+            let frame = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+            block = block.title(format!(
+                " {frame} {} {} ",
+                operation.label,
+                format_elapsed(elapsed)
+            ));
+        }
         let content = self.state.context_content.lock().unwrap().clone();
         let context_style = Style::default().fg(Color::White);
+        // `==> ` prefixed lines are section headings, the way brew prints them
+        let heading_style = Style::default()
+            .fg(Color::LightBlue)
+            .add_modifier(Modifier::BOLD);
+        let content = content
+            .lines()
+            .map(|line| match line.strip_prefix("==> ") {
+                Some(heading) => Line::styled(heading.to_string(), heading_style),
+                None => Line::raw(line.to_string()),
+            })
+            .collect::<Text>();
+        // follow the end of the output while an operation is running
+        let scroll = match operation {
+            Some(_) => content
+                .lines
+                .len()
+                .saturating_sub(area.height.saturating_sub(2) as usize),
+            None => 0,
+        };
         let context = Paragraph::new(content)
             .left_aligned()
             .block(block)
-            .style(context_style);
+            .style(context_style)
+            .scroll((scroll.min(u16::MAX as usize) as u16, 0));
         context.render(area, buf);
     }
 }
 
+/// Elapsed time in whole seconds, e.g. `42s` or `3m 05s`
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    match seconds / 60 {
+        0 => format!("{seconds}s"),
+        minutes => format!("{minutes}m {:02}s", seconds % 60),
+    }
+}
+
 impl ContextPane {
     pub fn new(state: Arc<State>) -> Self {
         Self { state }
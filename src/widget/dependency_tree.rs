@@ -0,0 +1,85 @@
+use crate::state::{NodeKind, Pane, State};
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, HighlightSpacing, List, ListItem, ListState, StatefulWidget},
+};
+use std::sync::Arc;
+
+/// Dependencies and dependents of the explored package, drawn as a tree
+/// with expandable nodes in place of the context pane
+pub struct DependencyTree {
+    state: Arc<State>,
+}
+
+impl StatefulWidget for DependencyTree {
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let block_style = match *self.state.current_pane() {
+            Pane::Context => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            _ => Style::default().fg(Color::LightBlue),
+        };
+        let tree = self.state.dependency_tree.lock().unwrap();
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(format!(" dependencies of {} ", tree.package_name))
+            .title_bottom("l expand  h collapse  enter explore  esc back")
+            .title_alignment(Alignment::Left)
+            .style(block_style);
+        let tree_style = Style::default().fg(Color::White);
+        let heading_style = Style::default()
+            .fg(Color::LightBlue)
+            .add_modifier(Modifier::BOLD);
+        let secondary_style = Style::default().fg(Color::DarkGray);
+        let rows = tree
+            .rows()
+            .into_iter()
+            .map(|(path, node)| {
+                let indent = "  ".repeat(path.len() - 1);
+                let marker = match (node.expanded, node.is_expandable()) {
+                    (true, _) => "▾ ",
+                    (false, true) => "▸ ",
+                    (false, false) => "  ",
+                };
+                let style = match node.kind {
+                    NodeKind::Heading => heading_style,
+                    _ => tree_style,
+                };
+                let mut spans = vec![
+                    Span::raw(indent),
+                    Span::styled(marker, secondary_style),
+                    Span::styled(node.name.clone(), style),
+                ];
+                if tree.pending.as_ref() == Some(&path) {
+                    spans.push(Span::styled(" loading…", secondary_style));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect::<Vec<ListItem>>();
+        let selected_style = Style::default()
+            .bg(Color::White)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD);
+        state.select(Some(tree.selected));
+        let tree = List::new(rows)
+            .block(block)
+            .style(tree_style)
+            .highlight_style(selected_style)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        tree.render(area, buf, state);
+    }
+
+    type State = ListState;
+}
+
+impl DependencyTree {
+    pub fn new(state: Arc<State>) -> Self {
+        Self { state }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::state::{Pane, State};
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, HighlightSpacing, List, ListItem, ListState, StatefulWidget},
+};
+use std::sync::Arc;
+use wherehouse::package_manager::Severity;
+
+/// Warnings of the health checks of the active backend, shown in place of
+/// the search results while the info pane is focused, once a check was
+/// asked for
+pub struct HealthPane {
+    state: Arc<State>,
+}
+
+impl StatefulWidget for HealthPane {
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let block_style = match *self.state.current_pane() {
+            Pane::Info => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            _ => Style::default().fg(Color::LightBlue),
+        };
+        let health = self.state.health.lock().unwrap();
+        let visible = health.visible();
+        let title = match (health.checked, health.warnings.len() - visible.len()) {
+            (false, _) => "1 health (checking)".to_string(),
+            (true, 0) => format!("1 health ({} warnings)", visible.len()),
+            (true, ignored) => format!("1 health ({} warnings, {ignored} ignored)", visible.len()),
+        };
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(title)
+            .title_bottom("C check  f fix  i ignore  u unignore all  r recheck")
+            .title_alignment(Alignment::Left)
+            .style(block_style);
+        let warnings_style = Style::default().fg(Color::White);
+        let secondary_style = Style::default().fg(Color::DarkGray);
+        let items = visible
+            .iter()
+            .map(|warning| {
+                let severity_style = match warning.severity {
+                    Severity::Warning => Style::default().fg(Color::Yellow),
+                    Severity::Error => Style::default().fg(Color::Red),
+                };
+                let fixable = match warning.fixes.is_empty() {
+                    true => "",
+                    false => "  fix",
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:7}  ", warning.severity), severity_style),
+                    Span::raw(warning.title.clone()),
+                    Span::styled(fixable, Style::default().fg(Color::Green)),
+                    Span::styled(format!("  {}", warning.check), secondary_style),
+                ]))
+                .style(warnings_style)
+            })
+            .collect::<Vec<ListItem>>();
+        let selected_style = Style::default()
+            .bg(Color::White)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD);
+        let items = List::new(items)
+            .block(block)
+            .style(warnings_style)
+            .highlight_style(selected_style)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        items.render(area, buf, state);
+    }
+
+    type State = ListState;
+}
+
+impl HealthPane {
+    pub fn new(state: Arc<State>) -> Self {
+        Self { state }
+    }
+}
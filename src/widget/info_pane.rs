@@ -3,6 +3,7 @@ use std::sync::Arc;
 use ratatui::{
     layout::Alignment,
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, BorderType, Paragraph, Widget},
 };
 
@@ -21,15 +22,37 @@ impl Widget for InfoPane {
             Pane::Info => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
             _ => Style::default().fg(Color::LightBlue),
         };
-        let block = Block::bordered()
+        let mut block = Block::bordered()
             .border_type(BorderType::Rounded)
             .title("1")
             .title_alignment(Alignment::Left)
             .style(block_style);
+        // the health list shows its own keys once a check was asked for
+        if matches!(*self.state.current_pane(), Pane::Info)
+            && !self.state.health.lock().unwrap().requested
+        {
+            block = block.title_bottom("C check health");
+        }
 
         let info_style = Style::default().fg(Color::White);
+        let active_style = Style::default()
+            .bg(Color::White)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD);
         let config = self.state.config.lock().unwrap();
-        let info = Paragraph::new(format!("{}", config.package_manager))
+        let package_managers = config
+            .available_package_managers
+            .iter()
+            .map(|package_manager| {
+                let label = format!(" {package_manager} ");
+                if config.aggregated || *package_manager == config.package_manager {
+                    Span::styled(label, active_style)
+                } else {
+                    Span::raw(label)
+                }
+            })
+            .collect::<Vec<Span>>();
+        let info = Paragraph::new(Line::from(package_managers))
             .left_aligned()
             .block(block)
             .style(info_style);
@@ -0,0 +1,112 @@
+use crate::state::{Pane, State};
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, HighlightSpacing, List, ListItem, ListState, StatefulWidget},
+};
+use std::sync::Arc;
+
+/// Installed packages with a newer version available, shown in place of
+/// the search results
+pub struct OutdatedPane {
+    state: Arc<State>,
+}
+
+impl StatefulWidget for OutdatedPane {
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let block_style = match *self.state.current_pane() {
+            Pane::Outdated => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            _ => Style::default().fg(Color::LightBlue),
+        };
+        let outdated = self.state.outdated.lock().unwrap();
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(format!(
+                "4 outdated ({}, {} marked)",
+                outdated.packages.len(),
+                outdated.selected.len()
+            ))
+            .title_bottom("space mark  u upgrade  U upgrade all  p pin  P unpin  r refresh")
+            .title_alignment(Alignment::Left)
+            .style(block_style);
+        let outdated_style = Style::default().fg(Color::White);
+        let version_style = Style::default().fg(Color::Green);
+        let pinned_style = Style::default().fg(Color::Yellow);
+        let secondary_style = Style::default().fg(Color::DarkGray);
+        let rows = outdated
+            .packages
+            .iter()
+            .map(|item| {
+                let version = format!(
+                    "{} -> {}",
+                    item.installed_version.as_deref().unwrap_or("?"),
+                    item.version.as_deref().unwrap_or("?")
+                );
+                let sources = item
+                    .sources
+                    .iter()
+                    .map(|source| source.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                [item.name.clone(), version, item.kind.to_string(), sources]
+            })
+            .collect::<Vec<[String; 4]>>();
+        // pad every column to its widest cell so that the columns line up
+        let mut widths = [0; 4];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let packages = outdated
+            .packages
+            .iter()
+            .zip(rows)
+            .map(|(item, row)| {
+                let [name, version, kind, sources] = row;
+                let checkbox = match outdated.selected.contains(&item.name) {
+                    true => "[x] ",
+                    false => "[ ] ",
+                };
+                let pinned = match item.pinned {
+                    true => "pinned  ",
+                    false => "        ",
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(checkbox),
+                    Span::raw(format!("{name:0$}  ", widths[0])),
+                    Span::styled(format!("{version:0$}  ", widths[1]), version_style),
+                    Span::raw(format!("{kind:0$}  ", widths[2])),
+                    Span::styled(pinned, pinned_style),
+                    Span::styled(sources, secondary_style),
+                ]))
+                .style(outdated_style)
+            })
+            .collect::<Vec<ListItem>>();
+        let selected_style = Style::default()
+            .bg(Color::White)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD);
+        let packages = List::new(packages)
+            .block(block)
+            .style(outdated_style)
+            .highlight_style(selected_style)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        packages.render(area, buf, state);
+    }
+
+    type State = ListState;
+}
+
+impl OutdatedPane {
+    pub fn new(state: Arc<State>) -> Self {
+        Self { state }
+    }
+}
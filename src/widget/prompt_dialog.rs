@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use ratatui::{
+    layout::{Alignment, Constraint, Flex, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::state::State;
+
+/// Popup asking the user to type the input of the pending command, drawn
+/// on top of the other panes
+pub struct PromptDialog {
+    state: Arc<State>,
+}
+
+impl Widget for PromptDialog {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let Some(prompt) = self.state.prompt.lock().unwrap().clone() else {
+            return;
+        };
+        let [area] = Layout::vertical([Constraint::Length(6)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title("input")
+            .title_alignment(Alignment::Left)
+            .style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+        let key_style = Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD);
+        let dialog = Paragraph::new(vec![
+            Line::raw(prompt.label),
+            Line::from(vec![
+                Span::styled(prompt.input, Style::default().fg(Color::White)),
+                Span::styled("█", Style::default().fg(Color::Gray)),
+            ]),
+            Line::from(vec![
+                Span::styled("enter", key_style),
+                Span::raw(" confirm  "),
+                Span::styled("esc", key_style),
+                Span::raw(" cancel"),
+            ]),
+        ])
+        .wrap(Wrap { trim: false })
+        .block(block)
+        .style(Style::default().fg(Color::White));
+
+        Clear.render(area, buf);
+        dialog.render(area, buf);
+    }
+}
+
+impl PromptDialog {
+    pub fn new(state: Arc<State>) -> Self {
+        Self { state }
+    }
+}
@@ -2,6 +2,7 @@ use crate::state::{Pane, State};
 use ratatui::{
     layout::Alignment,
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, BorderType, HighlightSpacing, List, ListItem, ListState, StatefulWidget},
 };
 use std::sync::Arc;
@@ -28,10 +29,77 @@ impl StatefulWidget for SearchResultsPane {
             .style(block_style);
         let search = self.state.search.lock().unwrap();
         let search_results_style = Style::default().fg(Color::White);
+        let installed_style = Style::default().fg(Color::Green);
+        let pinned_style = Style::default().fg(Color::Yellow);
+        let secondary_style = Style::default().fg(Color::DarkGray);
+        // underlined as well, since the highlighted row overrides the colour
+        let matched_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        let rows = search
+            .results
+            .iter()
+            .map(|item| {
+                let version = match (&item.installed_version, &item.version) {
+                    (Some(installed), Some(latest)) if installed != latest => {
+                        format!("{installed} -> {latest}")
+                    }
+                    (Some(installed), _) => installed.clone(),
+                    (None, Some(latest)) => latest.clone(),
+                    (None, None) => String::default(),
+                };
+                let sources = item
+                    .sources
+                    .iter()
+                    .map(|source| source.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                [
+                    item.name.clone(),
+                    version,
+                    item.kind.to_string(),
+                    item.repository.clone().unwrap_or_default(),
+                    sources,
+                ]
+            })
+            .collect::<Vec<[String; 5]>>();
+        // pad every column to its widest cell so that the columns line up
+        let mut widths = [0; 5];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
         let search_results = search
             .results
             .iter()
-            .map(|item| ListItem::new(item.clone()).style(search_results_style))
+            .zip(rows)
+            .map(|(item, row)| {
+                let [name, version, kind, repository, sources] = row;
+                let marker = match item.is_installed() {
+                    true => Span::styled("● ", installed_style),
+                    false => Span::raw("  "),
+                };
+                let pin = match item.pinned {
+                    true => Span::styled("⚑ ", pinned_style),
+                    false => Span::raw("  "),
+                };
+                let mut spans = vec![marker, pin];
+                spans.extend(highlighted(&name, &item.matched, matched_style));
+                spans.push(Span::raw(format!(
+                    "{:1$}  ",
+                    "",
+                    widths[0] - name.chars().count()
+                )));
+                spans.extend([
+                    Span::styled(format!("{version:0$}  ", widths[1]), installed_style),
+                    Span::raw(format!("{kind:0$}  ", widths[2])),
+                    Span::styled(format!("{repository:0$}  ", widths[3]), secondary_style),
+                    Span::styled(format!("{sources:0$}  ", widths[4]), secondary_style),
+                    Span::raw(item.description.clone().unwrap_or_default()),
+                ]);
+                ListItem::new(Line::from(spans)).style(search_results_style)
+            })
             .collect::<Vec<ListItem>>();
         let selected_style = Style::default()
             .bg(Color::White)
@@ -54,3 +122,29 @@ impl SearchResultsPane {
         Self { state }
     }
 }
+
+/// Split `name` into runs of characters that matched the search pattern,
+/// styled with `matched_style`, and runs that did not
+fn highlighted(name: &str, matched: &[usize], matched_style: Style) -> Vec<Span<'static>> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (index, c) in name.chars().enumerate() {
+        let is_matched = matched.contains(&index);
+        if is_matched != run_matched && !run.is_empty() {
+            spans.push(match run_matched {
+                true => Span::styled(std::mem::take(&mut run), matched_style),
+                false => Span::raw(std::mem::take(&mut run)),
+            });
+        }
+        run_matched = is_matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(match run_matched {
+            true => Span::styled(run, matched_style),
+            false => Span::raw(run),
+        });
+    }
+    spans
+}
@@ -0,0 +1,94 @@
+use crate::state::{Pane, State};
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, HighlightSpacing, List, ListItem, ListState, StatefulWidget},
+};
+use std::sync::Arc;
+
+/// Background services of the active backend, shown in place of the search
+/// results
+pub struct ServicesPane {
+    state: Arc<State>,
+}
+
+impl StatefulWidget for ServicesPane {
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let block_style = match *self.state.current_pane() {
+            Pane::Services => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            _ => Style::default().fg(Color::LightBlue),
+        };
+        let services = self.state.services.lock().unwrap();
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(format!("6 services ({})", services.services.len()))
+            .title_bottom("s start  S stop  R restart  o run once  c cleanup  r refresh")
+            .title_alignment(Alignment::Left)
+            .style(block_style);
+        let services_style = Style::default().fg(Color::White);
+        let secondary_style = Style::default().fg(Color::DarkGray);
+        let rows = services
+            .services
+            .iter()
+            .map(|service| {
+                [
+                    service.name.clone(),
+                    service.status.clone(),
+                    service.user.clone().unwrap_or_default(),
+                ]
+            })
+            .collect::<Vec<[String; 3]>>();
+        // pad every column to its widest cell so that the columns line up
+        let mut widths = [0; 3];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let items = services
+            .services
+            .iter()
+            .zip(rows)
+            .map(|(service, row)| {
+                let [name, status, user] = row;
+                let status_style = match service.status.as_str() {
+                    "started" | "scheduled" => Style::default().fg(Color::Green),
+                    "error" => Style::default().fg(Color::Red),
+                    _ => secondary_style,
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{name:0$}  ", widths[0])),
+                    Span::styled(format!("{status:0$}  ", widths[1]), status_style),
+                    Span::raw(format!("{user:0$}  ", widths[2])),
+                    Span::styled(service.file.clone().unwrap_or_default(), secondary_style),
+                ]))
+                .style(services_style)
+            })
+            .collect::<Vec<ListItem>>();
+        let selected_style = Style::default()
+            .bg(Color::White)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD);
+        let items = List::new(items)
+            .block(block)
+            .style(services_style)
+            .highlight_style(selected_style)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        items.render(area, buf, state);
+    }
+
+    type State = ListState;
+}
+
+impl ServicesPane {
+    pub fn new(state: Arc<State>) -> Self {
+        Self { state }
+    }
+}
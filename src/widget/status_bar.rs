@@ -3,7 +3,7 @@ use std::sync::Arc;
 use ratatui::{
     layout::{Constraint, Layout},
     style::{Color, Modifier, Style, Stylize},
-    text::Span,
+    text::{Line, Span},
     widgets::{Paragraph, Widget},
 };
 
@@ -23,6 +23,8 @@ impl Widget for StatusBar {
         let current_pane = self.state.current_pane.lock().unwrap();
         let search = self.state.search.lock().unwrap();
 
+        let error = self.state.error.lock().unwrap();
+
         let left_text = match *current_pane {
             Pane::SearchInput | Pane::SearchResults => {
                 format!(" {} | {} ", *input_mode, search.source)
@@ -31,19 +33,41 @@ impl Widget for StatusBar {
         };
         let status_bar_layout =
             Layout::horizontal(vec![Constraint::Percentage(70), Constraint::Fill(1)]).split(area);
-        let status_bar_left = Span::styled(
-            left_text,
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
-        );
-        let status_bar_right = Paragraph::new(format!(
-            " {} {} | {} {} ",
-            config.app_name,
-            config.app_version,
-            config.package_manager,
-            config.package_manager_version,
-        ))
+        // only the first line fits, the full message is shown in the
+        // context pane
+        let status_bar_left = match error.as_ref() {
+            Some((_, error)) => Line::from(vec![
+                Span::styled(
+                    left_text,
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!(" {} ", error.to_string().lines().next().unwrap_or_default()),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ),
+            ]),
+            None => Line::from(Span::styled(
+                left_text,
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        };
+        let status_bar_right = match config.aggregated {
+            true => Paragraph::new(format!(
+                " {} {} | all backends ",
+                config.app_name, config.app_version,
+            )),
+            false => Paragraph::new(format!(
+                " {} {} | {} {} ",
+                config.app_name,
+                config.app_version,
+                config.package_manager,
+                config.package_manager_version,
+            )),
+        }
         .right_aligned()
         .fg(Color::Green);
         status_bar_left.render(status_bar_layout[0], buf);
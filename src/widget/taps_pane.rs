@@ -0,0 +1,91 @@
+use crate::state::{Pane, State};
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, HighlightSpacing, List, ListItem, ListState, StatefulWidget},
+};
+use std::sync::Arc;
+
+/// Taps added to the active backend, shown in place of the search results
+pub struct TapsPane {
+    state: Arc<State>,
+}
+
+impl StatefulWidget for TapsPane {
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let block_style = match *self.state.current_pane() {
+            Pane::Taps => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            _ => Style::default().fg(Color::LightBlue),
+        };
+        let taps = self.state.taps.lock().unwrap();
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(format!("5 taps ({})", taps.taps.len()))
+            .title_bottom("a add  d remove  r refresh")
+            .title_alignment(Alignment::Left)
+            .style(block_style);
+        let taps_style = Style::default().fg(Color::White);
+        let count_style = Style::default().fg(Color::Green);
+        let secondary_style = Style::default().fg(Color::DarkGray);
+        let rows = taps
+            .taps
+            .iter()
+            .map(|tap| {
+                [
+                    tap.name.clone(),
+                    format!("{} formulae", tap.formulae),
+                    format!("{} casks", tap.casks),
+                    tap.last_updated.clone().unwrap_or_default(),
+                ]
+            })
+            .collect::<Vec<[String; 4]>>();
+        // pad every column to its widest cell so that the columns line up
+        let mut widths = [0; 4];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let items = taps
+            .taps
+            .iter()
+            .zip(rows)
+            .map(|(tap, row)| {
+                let [name, formulae, casks, last_updated] = row;
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{name:0$}  ", widths[0])),
+                    Span::styled(format!("{formulae:0$}  ", widths[1]), count_style),
+                    Span::styled(format!("{casks:0$}  ", widths[2]), count_style),
+                    Span::raw(format!("{last_updated:0$}  ", widths[3])),
+                    Span::styled(tap.remote.clone().unwrap_or_default(), secondary_style),
+                ]))
+                .style(taps_style)
+            })
+            .collect::<Vec<ListItem>>();
+        let selected_style = Style::default()
+            .bg(Color::White)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD);
+        let items = List::new(items)
+            .block(block)
+            .style(taps_style)
+            .highlight_style(selected_style)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        items.render(area, buf, state);
+    }
+
+    type State = ListState;
+}
+
+impl TapsPane {
+    pub fn new(state: Arc<State>) -> Self {
+        Self { state }
+    }
+}